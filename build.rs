@@ -1,7 +1,6 @@
 #![allow(unused_must_use)]
 
-extern crate gcc;
-extern crate rustc_cfg;
+extern crate cc;
 extern crate num_bigint;
 
 use std::env;
@@ -18,9 +17,16 @@ fn main() {
 
     gen_bases(&mut f);
 
-    if let Ok(_) = env::var("CARGO_FEATURE_ASM") {
+    // The limb size is exposed as `cfg(limb_width = "..")`, which is what the
+    // crate uses instead of `target_pointer_width`
+    println!("cargo:rustc-cfg=limb_width=\"{}\"", get_target_limb_size());
+
+    // The asm implementations only work with 64-bit limbs
+    if env::var("CARGO_FEATURE_ASM").is_ok() && get_target_limb_size() == 64 {
         compile_asm();
     }
+    println!("cargo:rustc-check-cfg=cfg(asm)");
+    println!("cargo:rustc-check-cfg=cfg(limb_width, values(\"32\", \"64\"))");
     println!("cargo:rerun-if-changed=build.rs");
 }
 
@@ -41,7 +47,7 @@ fn compile_asm() {
                     "src/ll/asm/addmul_1.S",
                 ];
 
-                gcc::compile_library("libasm.a", asm_srcs);
+                cc::Build::new().files(asm_srcs).compile("asm");
                 // Use a cfg param so turning the feature on when we don't have
                 // asm impls available doesn't cause compile errors
                 println!("cargo:rustc-cfg=asm");
@@ -100,7 +106,11 @@ fn gen_base(f: &mut File, limb_size: usize, base: usize) {
              base, digits_per_limb, big_base);
 }
 
+// Limbs are a word wide, unless the `force-32-bit-limbs` feature asks for
+// 32-bit ones.
 fn get_target_limb_size() -> usize {
-    let cfg = rustc_cfg::Cfg::new(env::var_os("TARGET").unwrap()).unwrap();
-    return cfg.target_pointer_width.parse().unwrap();
+    if env::var("CARGO_FEATURE_FORCE_32_BIT_LIMBS").is_ok() {
+        return 32;
+    }
+    env::var("CARGO_CFG_TARGET_POINTER_WIDTH").unwrap().parse().unwrap()
 }
@@ -5,6 +5,7 @@ extern crate framp as ramp;
 use ramp::Int;
 use ramp::ll::limb::Limb;
 
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
 fn main() {
     let n = std::env::args_os().nth(1)
         .and_then(|s| s.into_string().ok())
@@ -27,6 +28,12 @@ pub struct Context {
     den: Int,
     num: Int
 }
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Context {
     pub fn new() -> Context {
         Context {
@@ -38,22 +45,21 @@ impl Context {
         }
     }
     fn extract_digit(&mut self, nth: Limb) -> Limb {
-        self.tmp1.clone_from(&self.num);
-        self.tmp1 *= nth;
-        self.tmp1 += &self.acc;
+        self.tmp1.clone_from(&self.acc);
+        self.tmp1.add_mul_limb(&self.num, nth);
         self.tmp1 /= &self.den;
 
-        return self.tmp1.to_single_limb();
+        self.tmp1.to_single_limb()
     }
     fn eliminate_digit(&mut self, d: Limb) {
-        self.acc -= self.den.clone() * d;
+        self.acc.sub_mul_limb(&self.den, d);
         self.acc *= 10;
         self.num *= 10;
     }
     fn next_term(&mut self) {
         self.k = self.k + 1;
         let k2 = self.k * 2 + 1;
-        self.acc += &self.num + &self.num;
+        self.acc.add_mul_limb(&self.num, Limb(2));
         self.acc *= k2;
 
         self.den *= k2;
@@ -0,0 +1,97 @@
+// Copyright 2015 The Ramp Developers
+//
+//    Licensed under the Apache License, Version 2.0 (the "License");
+//    you may not use this file except in compliance with the License.
+//    You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+//    Unless required by applicable law or agreed to in writing, software
+//    distributed under the License is distributed on an "AS IS" BASIS,
+//    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//    See the License for the specific language governing permissions and
+//    limitations under the License.
+
+//! Correctly rounded conversions to binary floating point formats, shared by `Int` and
+//! `Rational`.
+
+use int::Int;
+
+/// The layout of an IEEE 754 binary format.
+#[derive(Clone, Copy)]
+pub struct Format {
+    /// Significand bits, including the implicit leading one
+    pub mant_bits: usize,
+    pub exp_bits: usize,
+}
+
+pub const F32: Format = Format { mant_bits: 24, exp_bits: 8 };
+pub const F64: Format = Format { mant_bits: 53, exp_bits: 11 };
+
+/**
+ * Returns the bit pattern of the float nearest to `mag * 2^exp`, with ties going to the
+ * even significand. Values too large for the format become infinity and values too small
+ * become zero.
+ *
+ * `mag` must be non-negative. If `sticky` is set, the value is taken to be slightly more
+ * than `mag * 2^exp`, by less than `2^exp`. In that case `mag` must have at least two more
+ * bits than the significand so the rounding can't depend on how much more it is.
+ */
+pub fn to_bits(mag: &Int, exp: isize, sticky: bool, neg: bool, fmt: Format) -> u64 {
+    debug_assert!(mag.sign() >= 0);
+    let sign = if neg { 1u64 << (fmt.exp_bits + fmt.mant_bits - 1) } else { 0 };
+    if mag.sign() == 0 {
+        return sign;
+    }
+
+    let bias = (1isize << (fmt.exp_bits - 1)) - 1;
+    let mant = fmt.mant_bits as isize;
+    let min_lsb = 1 - bias - (mant - 1);
+    let max_biased = (1u64 << fmt.exp_bits) - 1;
+
+    let len = mag.bit_length() as isize;
+    let top = len - 1 + exp;
+    if top > bias {
+        // Even the truncated value is too big
+        return sign | (max_biased << (fmt.mant_bits - 1));
+    }
+
+    // The exponent of the lowest significand bit, raised for subnormals
+    let mut lsb = ::std::cmp::max(top - (mant - 1), min_lsb);
+    let shift = lsb - exp;
+
+    let mut m = if shift <= 0 {
+        debug_assert!(!sticky);
+        (mag << (-shift) as usize).to_u64_wrapping()
+    } else if shift > len {
+        // Less than half of the smallest subnormal
+        return sign;
+    } else {
+        debug_assert!(!sticky || shift >= 2);
+        let shift = shift as usize;
+        let m = (mag >> shift).to_u64_wrapping();
+        let half = mag.bit(shift - 1);
+        let rest = sticky || mag.trailing_zeros() < shift - 1;
+        if half && (rest || m & 1 == 1) {
+            m + 1
+        } else {
+            m
+        }
+    };
+
+    if m == 1 << fmt.mant_bits {
+        m >>= 1;
+        lsb += 1;
+    }
+
+    if m >= 1 << (fmt.mant_bits - 1) {
+        let biased = (lsb + (mant - 1) + bias) as u64;
+        if biased >= max_biased {
+            return sign | (max_biased << (fmt.mant_bits - 1));
+        }
+        sign | (biased << (fmt.mant_bits - 1)) | (m & ((1 << (fmt.mant_bits - 1)) - 1))
+    } else {
+        // Subnormal, which has a biased exponent of zero
+        sign | m
+    }
+}
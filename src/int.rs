@@ -21,29 +21,36 @@ use std::cmp::{
     Ord, Eq,
     PartialOrd, PartialEq
 };
+#[cfg(feature = "std")]
 use std::error::Error;
-use std::{io, mem, fmt, hash};
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::cell::Cell;
+use std::{mem, fmt, hash};
+use std::convert::TryFrom;
 use std::ops::{
     Add, Sub, Mul, Div, Rem, Neg,
     AddAssign, SubAssign, MulAssign, DivAssign, RemAssign,
     Shl, Shr, BitAnd, BitOr, BitXor,
     ShlAssign, ShrAssign, BitAndAssign, BitOrAssign, BitXorAssign,
 };
-use std::ptr::Unique;
 use std::str::FromStr;
+use std::string::{String, ToString};
+use std::vec::Vec;
+#[cfg(feature = "std")]
 use rand::Rng;
 
-use hamming;
-use alloc;
+#[cfg(feature = "std")]
+use hamming::weight;
 use num_integer::Integer;
 use num_traits::{Num, Zero, One};
 
+use float;
 use ll;
 use ll::limb::{BaseInt, Limb};
 use ll::limb_ptr::{Limbs, LimbsMut};
 
-use alloc::raw_vec::RawVec;
-
 use traits::DivRem;
 
 
@@ -77,14 +84,18 @@ use traits::DivRem;
  *
  * `Int` supports all the formatting traits, allowing it to be used just like a regular integer
  * when used in `format!` and similar macros. `Int` also supports conversion to primitive integer
- * types, truncating if the `Int` cannot fit into the target type. Conversion to primtive integers
- * is done with the `From` trait:
+ * types with the `TryFrom` trait, which fails if the `Int` cannot fit into the target type.
+ * Methods like `to_i32_wrapping` truncate instead, just like an `as` cast:
  *
  *   ```
  *   # use framp::Int;
+ *   use std::convert::TryFrom;
+ *
  *   let big_i   = Int::from(123456789);
- *   let i = i32::from(&big_i);
+ *   let i = i32::try_from(&big_i).unwrap();
  *   assert_eq!(123456789, i);
+ *   assert!(i8::try_from(&big_i).is_err());
+ *   assert_eq!(big_i.to_i8_wrapping(), 123456789i32 as i8);
  *   ```
  *
  * ## Usage
@@ -123,11 +134,30 @@ use traits::DivRem;
  *
  */
 pub struct Int {
-    ptr: Unique<Limb>,
-    size: i32,
-    cap: u32
+    data: Storage,
+    size: isize,
+    cap: usize
+}
+
+/// Number of limbs an `Int` can hold without allocating.
+const INLINE_LIMBS: usize = 2;
+
+/**
+ * The backing memory of an `Int`. Values needing at most `INLINE_LIMBS` limbs are stored directly
+ * in the `Int`, larger ones live on the heap. Which field is active is determined by the `cap`
+ * of the owning `Int`: it is exactly `INLINE_LIMBS` for inline storage and strictly greater for
+ * heap storage.
+ */
+#[derive(Copy, Clone)]
+union Storage {
+    inline: [Limb; INLINE_LIMBS],
+    heap: *mut Limb,
 }
 
+// `Int` uniquely owns its heap allocation, if it has one.
+unsafe impl Send for Int {}
+unsafe impl Sync for Int {}
+
 impl Int {
     pub fn zero() -> Int {
         <Int as Zero>::zero()
@@ -139,9 +169,9 @@ impl Int {
 
     /// Creates a new Int from the given Limb.
     pub fn from_single_limb(limb: Limb) -> Int {
-        let mut i = Int::with_capacity(1);
+        let mut i = Int::zero();
         unsafe {
-            *i.ptr.get_mut() = limb;
+            *i.limbs_uninit() = limb;
         }
         i.size = 1;
 
@@ -149,43 +179,89 @@ impl Int {
     }
 
     /**
-     * Views the internal memory as a `RawVec`, which can be
-     * manipulated to change `self`'s allocation.
+     * Changes the allocation of `self` to hold `cap` limbs, moving between the inline and heap
+     * storage as needed. Capacities below `INLINE_LIMBS` are rounded up. Existing limbs are
+     * preserved as far as they fit, and any new limbs are zeroed.
+     *
+     * Panics if `cap` is more limbs than an `Int` can hold or the memory can't be allocated.
+     */
+    fn set_capacity(&mut self, cap: usize) {
+        unwrap_alloc(self.try_set_capacity(cap))
+    }
+
+    /**
+     * Like `set_capacity`, but returns an error if `cap` is too large or the memory can't be
+     * allocated. `self` is unchanged in that case.
      */
-    fn with_raw_vec<F: FnOnce(&mut RawVec<Limb>)>(&mut self, f: F) {
+    fn try_set_capacity(&mut self, cap: usize) -> Result<(), AllocError> {
+        let new_cap = std::cmp::max(cap, INLINE_LIMBS);
+        let old_cap = self.cap;
+        if new_cap == old_cap { return Ok(()); }
+        debug_assert!(self.abs_size() as usize <= new_cap);
+
+        // The size is stored as an `isize`, so any more limbs could never be used
+        if new_cap > isize::MAX as usize {
+            return Err(AllocError { kind: AllocErrorKind::CapacityOverflow });
+        }
+        if new_cap > old_cap && new_cap > INLINE_LIMBS &&
+            new_cap > get_limb_limit() {
+            return Err(AllocError { kind: AllocErrorKind::LimitExceeded });
+        }
+        let limb_size = std::mem::size_of::<Limb>();
+        let bytes = match new_cap.checked_mul(limb_size) {
+            Some(bytes) if bytes <= isize::MAX as usize => bytes,
+            _ => return Err(AllocError { kind: AllocErrorKind::CapacityOverflow }),
+        };
+
         unsafe {
-            let old_cap = self.cap as usize;
-            let mut vec = RawVec::from_raw_parts(self.ptr.get_mut(), old_cap);
-            // if `f` panics, let `vec` do the cleaning up, not self.
-            self.cap = 0;
+            if new_cap == INLINE_LIMBS {
+                // Moving back from the heap into the inline storage
+                let heap = self.data.heap;
+                let mut inline = [Limb(0); INLINE_LIMBS];
+                std::ptr::copy_nonoverlapping(heap, inline.as_mut_ptr(), INLINE_LIMBS);
+                ::mem::deallocate_bytes(heap as *mut u8, old_cap * limb_size);
+                self.data = Storage { inline };
+            } else {
+                // `try_allocate_bytes` zeroes the memory, so only the old limbs need copying
+                let heap = ::mem::try_allocate_bytes(bytes) as *mut Limb;
+                if heap.is_null() {
+                    return Err(AllocError { kind: AllocErrorKind::AllocFailed(bytes) });
+                }
+                let old = self.ptr();
+                std::ptr::copy_nonoverlapping(old, heap, std::cmp::min(old_cap, new_cap));
+                if old_cap > INLINE_LIMBS {
+                    ::mem::deallocate_bytes(old as *mut u8, old_cap * limb_size);
+                }
+                self.data = Storage { heap };
+            }
+        }
+        self.cap = new_cap;
+        Ok(())
+    }
 
-            f(&mut vec);
+    /// Whether the limbs are stored inline, rather than on the heap.
+    #[inline(always)]
+    fn is_inline(&self) -> bool {
+        self.cap == INLINE_LIMBS
+    }
 
-            // update `self` for any changes that happened
-            self.ptr = Unique::new(vec.ptr());
-            let new_cap = vec.cap();
-            assert!(new_cap <= std::u32::MAX as usize);
-            self.cap = new_cap as u32;
-            // ownership has transferred back into `self`, so make
-            // sure that allocation isn't freed by `vec`.
-            std::mem::forget(vec);
+    #[inline(always)]
+    fn ptr(&self) -> *const Limb {
+        unsafe {
+            if self.is_inline() { self.data.inline.as_ptr() } else { self.data.heap }
+        }
+    }
 
-            if old_cap < new_cap {
-                // the allocation got larger, new Limbs should be
-                // zero.
-                let self_ptr = self.limbs_uninit();
-                std::ptr::write_bytes(&mut *self_ptr.offset(old_cap as isize) as *mut _ as *mut u8,
-                                      0,
-                                      (new_cap - old_cap) * std::mem::size_of::<Limb>());
-            }
+    #[inline(always)]
+    fn ptr_mut(&mut self) -> *mut Limb {
+        unsafe {
+            if self.is_inline() { self.data.inline.as_mut_ptr() } else { self.data.heap }
         }
     }
 
-    fn with_capacity(cap: u32) -> Int {
+    fn with_capacity(cap: usize) -> Int {
         let mut ret = Int::zero();
-        if cap != 0 {
-            ret.with_raw_vec(|v| v.reserve_exact(0, cap as usize))
-        }
+        ret.set_capacity(cap);
         ret
     }
 
@@ -219,14 +295,38 @@ impl Int {
     #[inline]
     pub fn to_single_limb(&self) -> Limb {
         if self.sign() == 0 {
-            return Limb(0);
+            Limb(0)
         } else {
-            return unsafe { *self.ptr.get() };
+            unsafe { *self.ptr() }
+        }
+    }
+
+    /**
+     * Returns the limbs of the absolute value of self, least-significant first. The slice is
+     * normalized, so it's empty for zero and otherwise ends with a non-zero limb. Together with
+     * `from_limbs`, this connects `Int` to the functions in `ll::safe`.
+     */
+    #[inline]
+    pub fn as_limbs(&self) -> &[Limb] {
+        unsafe { std::slice::from_raw_parts(self.ptr(), self.abs_size() as usize) }
+    }
+
+    /**
+     * Creates a new, non-negative Int from the given limbs, least-significant first. Zero limbs in
+     * the most-significant positions are allowed.
+     */
+    pub fn from_limbs(limbs: &[Limb]) -> Int {
+        let len = ll::safe::normalized_len(limbs);
+        let mut i = Int::with_capacity(len);
+        unsafe {
+            std::ptr::copy_nonoverlapping(limbs.as_ptr(), i.ptr_mut(), len);
         }
+        i.size = len as isize;
+        i
     }
 
     #[inline(always)]
-    fn abs_size(&self) -> i32 {
+    fn abs_size(&self) -> isize {
         self.size.abs()
     }
 
@@ -260,33 +360,56 @@ impl Int {
      * is wasteful
      */
     pub fn abs_hash<H>(&self, state: &mut H) where H: hash::Hasher {
-        use std::hash::Hash;
-        let mut size = self.abs_size();
-        unsafe {
-            let mut ptr = self.limbs();
-            while size > 0 {
-                let l = *ptr;
-                l.hash(state);
-
-                ptr = ptr.offset(1);
-                size -= 1;
+        let limb_bytes = Limb::BITS / 8;
+        let len = if self.sign() == 0 { 0 } else { self.bit_length().div_ceil(8) };
+
+        // Hash the magnitude as its length in bytes followed by the bytes
+        // themselves, least significant first. Both the bytes and the way they
+        // are split between `write` calls are the same for any `Limb` size, so
+        // the hash doesn't depend on it.
+        let mut buf = [0u8; 64];
+        let mut n = 0;
+        buf[..8].copy_from_slice(&(len as u64).to_le_bytes());
+        state.write(&buf[..8]);
+
+        let mut remaining = len;
+        for k in 0..self.abs_size() {
+            let l = unsafe { *self.limbs().offset(k) };
+            for j in 0..std::cmp::min(limb_bytes, remaining) {
+                buf[n] = (l.0 >> (8 * j)) as u8;
+                n += 1;
+                if n == buf.len() {
+                    state.write(&buf);
+                    n = 0;
+                }
             }
+            remaining -= std::cmp::min(limb_bytes, remaining);
+        }
+        if n > 0 {
+            state.write(&buf[..n]);
         }
     }
 
+    /**
+     * Returns a hash of the value that is the same on every platform and for every build
+     * of this library, so it can be stored or compared between programs.
+     *
+     * It is the result of hashing self with `StableHasher`.
+     */
+    pub fn stable_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = StableHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
     /**
      * Try to shrink the allocated data for this Int.
      */
     pub fn shrink_to_fit(&mut self) {
-        let mut size = self.abs_size() as usize;
-
-        if (self.cap as usize) == size { return; } // already as small as possible
-
-        if size == 0 { size = 1; } // Keep space for at least one limb around
-
-        self.with_raw_vec(|v| {
-            v.shrink_to_fit(size);
-        })
+        // Small values always fit in the inline storage
+        let size = self.abs_size() as usize;
+        self.set_capacity(size);
     }
 
     /**
@@ -300,13 +423,13 @@ impl Int {
             return "0".to_string();
         }
 
-        if base < 2 || base > 36 {
+        if !(2..=36).contains(&base) {
             panic!("Invalid base: {}", base);
         }
 
         let size = self.abs_size();
         let mut num_digits = unsafe {
-            ll::base::num_base_digits(self.limbs(), size - 1, base as u32)
+            ll::base::num_base_digits(self.limbs(), size - 1, base as u64)
         };
 
         if self.sign() == -1 {
@@ -315,2277 +438,3609 @@ impl Int {
 
         let mut buf : Vec<u8> = Vec::with_capacity(num_digits);
 
-        self.write_radix(&mut buf, base, upper).unwrap();
+        self.radix_chunks(base, upper, |chunk| {
+            buf.extend_from_slice(chunk);
+            Ok::<(), ()>(())
+        }).unwrap();
 
         unsafe { String::from_utf8_unchecked(buf) }
     }
 
+    /**
+     * Returns a builder for writing self out with digit grouping, a chosen base or in
+     * scientific notation. By default it gives the same output as `Display`.
+     *
+     * ```
+     * # extern crate framp as ramp;
+     * # use ramp::Int;
+     * # fn main() {
+     * let i = Int::from(1234567);
+     * assert_eq!(i.format().group(',', 3).to_string(), "1,234,567");
+     * assert_eq!(format!("{:>8}", i.format().scientific(3)), "  1.23e6");
+     * assert_eq!(i.format().radix(16).group('_', 4).to_string(), "12_d687");
+     * # }
+     * ```
+     */
+    pub fn format<'a>(&'a self) -> IntFormat<'a> {
+        IntFormat {
+            int: self,
+            radix: 10,
+            upper: false,
+            group: None,
+            sig_digits: None,
+        }
+    }
+
+    /**
+     * Writes the value of self in base `base` to `w`, in the same form as `to_str_radix`.
+     *
+     * Digits are handed to `w` in small chunks rather than built into a `String` first.
+     * For bases that aren't a power of two, though, all of the digits are still worked out
+     * before any are written, in a buffer of one byte per digit plus a copy of `self`.
+     *
+     * Panics if `base` is less than two or greater than 36.
+     */
+    #[cfg(feature = "std")]
     pub fn write_radix<W: io::Write>(&self, w: &mut W, base: u8, upper: bool) -> io::Result<()> {
+        self.radix_chunks(base, upper, |chunk| w.write_all(chunk))
+    }
+
+    /// Produces the digits for `write_radix`, handing them to `out` a chunk at a time.
+    fn radix_chunks<E, F>(&self, base: u8, upper: bool, mut out: F) -> Result<(), E>
+        where F: FnMut(&[u8]) -> Result<(), E>
+    {
         debug_assert!(self.well_formed());
 
+        if !(2..=36).contains(&base) {
+            panic!("Invalid base: {}", base);
+        }
+
         if self.sign() == -1 {
-            try!(w.write_all(b"-"));
+            out(b"-")?;
         }
 
         let letter = if upper { b'A' } else { b'a' };
         let size = self.abs_size();
 
+        let mut chunk = [0u8; 256];
+        let mut len = 0;
+        // The first error is kept and the remaining digits dropped, as there's
+        // no way to stop `to_base` early
+        let mut res = Ok(());
+
         unsafe {
-            ll::base::to_base(base as u32, self.limbs(), size, |b| {
-                if b < 10 {
-                    w.write_all(&[b + b'0']).unwrap();
-                } else {
-                    w.write_all(&[(b - 10) + letter]).unwrap();
+            ll::base::to_base(base as u64, self.limbs(), size, |b: u8| {
+                chunk[len] = if b < 10 { b + b'0' } else { (b - 10) + letter };
+                len += 1;
+                if len == chunk.len() {
+                    if res.is_ok() {
+                        res = out(&chunk);
+                    }
+                    len = 0;
                 }
             });
         }
 
-        Ok(())
+        res?;
+        out(&chunk[..len])
     }
 
     /**
-     * Creates a new Int from the given string in base `base`.
+     * Reads an Int in base `base` from `r`, in the same form `from_str_radix` accepts: an
+     * optional `-` followed by digits, up to the end of the input.
+     *
+     * The input is processed in small chunks as it's read, so the memory used is
+     * proportional to the size of the result rather than the length of the input.
+     * Invalid input gives an error of kind `InvalidData` wrapping a `ParseIntError`.
+     *
+     * Panics if `base` is less than two or greater than 36.
      */
-    pub fn from_str_radix(mut src: &str, base: u8) -> Result<Int, ParseIntError> {
-        if base < 2 || base > 36 {
+    #[cfg(feature = "std")]
+    pub fn read_radix<R: io::Read>(r: &mut R, base: u8) -> io::Result<Int> {
+        if !(2..=36).contains(&base) {
             panic!("Invalid base: {}", base);
         }
 
-        if src.len() == 0 {
-            return Err(ParseIntError { kind: ErrorKind::Empty });
-        }
-
-        let mut sign = 1;
-        if src.starts_with('-') {
-            sign = -1;
-            src = &src[1..];
-        }
-
-        if src.len() == 0 {
-            return Err(ParseIntError { kind: ErrorKind::Empty });
-        }
-
-        let mut buf = Vec::with_capacity(src.len());
+        let mut acc = DigitAccumulator::new(base);
+        let mut buf = [0u8; 4096];
+        let mut offset = 0;
+        let mut neg = false;
 
-        for c in src.bytes() {
-            let b = match c {
-                b'0'...b'9' => c - b'0',
-                b'A'...b'Z' => (c - b'A') + 10,
-                b'a'...b'z' => (c - b'a') + 10,
-                _ => {
-                    return Err(ParseIntError { kind: ErrorKind::InvalidDigit });
-                }
+        loop {
+            let n = match r.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
             };
 
-            if b >= base { return Err(ParseIntError { kind: ErrorKind::InvalidDigit }); }
-
-            buf.push(b);
+            for &c in &buf[..n] {
+                if offset == 0 && c == b'-' {
+                    neg = true;
+                } else {
+                    let b = digit_value(c);
+                    if b >= base {
+                        let err = ParseIntError { kind: ErrorKind::InvalidDigit(offset) };
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+                    }
+                    acc.push(b).map_err(alloc_io_error)?;
+                }
+                offset += 1;
+            }
         }
 
-        let num_digits = ll::base::base_digits_to_len(src.len(), base as u32);
-
-        let mut i = Int::with_capacity(num_digits as u32);
-
-        unsafe {
-            let size = ll::base::from_base(i.limbs_uninit(), buf.as_ptr(), buf.len() as i32, base as u32);
-            i.size = (size as i32) * sign;
+        if acc.digits == 0 {
+            let err = ParseIntError { kind: ErrorKind::Empty };
+            return Err(io::Error::new(io::ErrorKind::InvalidData, err));
         }
 
+        let mut i = acc.finish().map_err(alloc_io_error)?;
+        if neg {
+            i.negate();
+        }
         Ok(i)
     }
 
     /**
-     * Divide self by other, returning the quotient, Q, and remainder, R as (Q, R).
+     * Returns an iterator over the digits of the absolute value of self in base `base`,
+     * most significant first. The iterator is double-ended, so `.rev()` gives the digits
+     * least significant first. Zero has the single digit `0`.
      *
-     * With N = self, D = other, Q and R satisfy: `N = QD + R`.
-     * The sign of `Q` and `R` are the same.
+     * Panics if `base` is less than two or greater than 2^32.
      *
-     * This will panic if `other` is zero.
+     * ```
+     * # extern crate framp as ramp;
+     * # use ramp::Int;
+     * # fn main() {
+     * let i = Int::from(1234);
+     * assert_eq!(i.digits(10).collect::<Vec<_>>(), [1, 2, 3, 4]);
+     * assert_eq!(i.digits(10).rev().collect::<Vec<_>>(), [4, 3, 2, 1]);
+     * assert_eq!(i.digits(1000).sum::<u32>(), 235);
+     * # }
+     * ```
      */
-    pub fn divmod(&self, other: &Int) -> (Int, Int) {
-        debug_assert!(self.well_formed());
-        debug_assert!(other.well_formed());
-        if other.sign() == 0 {
-            ll::divide_by_zero();
-        }
-        if self.sign() == 0 {
-            return (self.clone(), Int::zero())
+    pub fn digits(&self, base: u64) -> Digits {
+        if !(2..=ll::base::MAX_BASE).contains(&base) {
+            panic!("Invalid base: {}", base);
         }
 
-        let out_size = if self.abs_size() < other.abs_size() {
-            1
-        } else {
-            (self.abs_size() - other.abs_size()) + 1
-        };
-
-        let out_sign = self.sign() * other.sign();
-        let mut q = Int::with_capacity(out_size as u32);
-        q.size = out_size * out_sign;
-
-        let mut r = Int::with_capacity(other.abs_size() as u32);
-        r.size = other.abs_size() * self.sign();
-
+        let size = self.abs_size();
+        let mut digits = Vec::with_capacity(unsafe {
+            ll::base::num_base_digits(self.limbs(), size, base)
+        });
         unsafe {
-            ll::divrem(q.limbs_mut(), r.limbs_mut(),
-                       self.limbs(), self.abs_size(),
-                       other.limbs(), other.abs_size());
+            ll::base::to_base(base, self.limbs(), size, |d: u32| digits.push(d));
         }
+        Digits { inner: digits.into_iter() }
+    }
 
-        q.normalize();
-        r.normalize();
+    /**
+     * Creates a new, non-negative, Int from its digits in base `base`, most significant
+     * first. This is the inverse of `digits`. An empty slice gives zero.
+     *
+     * Panics if `base` is less than two or greater than 2^32, or if any digit is not
+     * less than `base`.
+     */
+    pub fn from_digits(digits: &[u32], base: u64) -> Int {
+        if !(2..=ll::base::MAX_BASE).contains(&base) {
+            panic!("Invalid base: {}", base);
+        }
+        if let Some(&d) = digits.iter().find(|&&d| d as u64 >= base) {
+            panic!("Invalid digit {} for base {}", d, base);
+        }
+        if digits.is_empty() {
+            return Int::zero();
+        }
 
-        (q, r)
+        let mut i = Int::with_capacity(ll::base::base_digits_to_len(digits.len(), base));
+        unsafe {
+            let size = ll::base::from_base(i.limbs_uninit(), digits.as_ptr(),
+                                           digits.len() as isize, base);
+            i.size = size as isize;
+        }
+        i.normalize();
+        i
     }
 
     /**
-     * Raises self to the power of exp
+     * Creates a new Int from the given string in base `base`.
+     *
+     * The string must consist of an optional `-` followed by one or more digits, with
+     * nothing else around it. Use `IntParser` to accept other forms.
      */
-    pub fn pow(&self, exp: usize) -> Int {
-        debug_assert!(self.well_formed());
-        match exp {
-            0 => Int::one(),
-            1 => self.clone(),
-            2 => self.square(),
-            _ => {
-                let mut signum = self.sign();
-                if signum == 0 {
-                    return Int::zero();
-                }
-                if exp & 1 == 0 {
-                    signum = 1
-                }
-
-                let ret_sz = unsafe {
-                    ll::pow::num_pow_limbs(self.limbs(), self.abs_size(), exp as u32)
-                };
-                let mut ret = Int::with_capacity(ret_sz as u32);
-                ret.size = ret_sz * signum;
+    pub fn from_str_radix(src: &str, base: u8) -> Result<Int, ParseIntError> {
+        IntParser::new().radix(base).parse(src)
+    }
 
-                unsafe {
-                    ll::pow::pow(ret.limbs_mut(), self.limbs(), self.abs_size(), exp as u32);
-                }
+    /**
+     * Creates a new, non-negative, Int from its magnitude as little-endian bytes.
+     */
+    pub fn from_bytes_le(bytes: &[u8]) -> Int {
+        Int::from_le_byte_iter(bytes.len(), bytes.iter().cloned())
+    }
 
-                ret.normalize();
+    /**
+     * Creates a new, non-negative, Int from its magnitude as big-endian bytes.
+     */
+    pub fn from_bytes_be(bytes: &[u8]) -> Int {
+        Int::from_le_byte_iter(bytes.len(), bytes.iter().rev().cloned())
+    }
 
-                ret
+    /**
+     * Creates a new Int from its two's complement representation as little-endian bytes.
+     * An empty slice gives zero.
+     */
+    pub fn from_signed_bytes_le(bytes: &[u8]) -> Int {
+        match bytes.last() {
+            Some(&b) if b & 0x80 != 0 => {
+                let mut mag = bytes.to_vec();
+                twos_complement_le(&mut mag);
+                -Int::from_bytes_le(&mag)
             }
+            _ => Int::from_bytes_le(bytes),
         }
     }
 
     /**
-     * Returns the square of `self`.
+     * Creates a new Int from its two's complement representation as big-endian bytes.
+     * An empty slice gives zero.
      */
-    pub fn square(&self) -> Int {
-        debug_assert!(self.well_formed());
-        let s = self.sign();
-        if s == 0 {
-            Int::zero()
-        } else if self.abs_size() == 1 {
-            let a = self.clone() * self.to_single_limb();
-            if s == -1 {
-                a.abs()
-            } else if s == 1 {
-                a
-            } else {
-                unreachable!()
-            }
-        } else {
-            let sz = self.abs_size() * 2;
-            let mut ret = Int::with_capacity(sz as u32);
-            ret.size = sz;
-            unsafe {
-                ll::sqr(ret.limbs_mut(), self.limbs(), self.abs_size());
-            }
-            ret.normalize();
+    pub fn from_signed_bytes_be(bytes: &[u8]) -> Int {
+        let mut le = bytes.to_vec();
+        le.reverse();
+        Int::from_signed_bytes_le(&le)
+    }
 
-            ret
+    /**
+     * Returns the magnitude of self as little-endian bytes, using the fewest bytes
+     * possible. Zero is returned as a single zero byte.
+     */
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        let mut bytes = self.abs_bytes_le();
+        if bytes.is_empty() {
+            bytes.push(0);
         }
+        bytes
     }
 
-    // DESTRUCTIVE square. Is there a more idiomatic way of doing this?
-    pub fn dsquare(mut self) -> Int {
-        debug_assert!(self.well_formed());
-        let s = self.sign();
-        if s == 0 {
-            Int::zero()
-        } else if self.abs_size() == 1 {
-            let l = self.to_single_limb();
-            self = self * l;
-            if s == -1 {
-                self.abs()
-            } else if s == 1 {
-                self
-            } else {
-                unreachable!()
-            }
-        } else {
-            self.square()
+    /**
+     * Returns the magnitude of self as big-endian bytes, using the fewest bytes
+     * possible. Zero is returned as a single zero byte.
+     */
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let mut bytes = self.to_bytes_le();
+        bytes.reverse();
+        bytes
+    }
+
+    /**
+     * Returns the magnitude of self as exactly `len` little-endian bytes, padded with
+     * zeros, or `None` if it doesn't fit.
+     */
+    pub fn to_bytes_le_padded(&self, len: usize) -> Option<Vec<u8>> {
+        let mut bytes = self.abs_bytes_le();
+        if bytes.len() > len {
+            return None;
         }
+        bytes.resize(len, 0);
+        Some(bytes)
     }
 
     /**
-     * Compute the sqrt of this number, returning its floor, S,  and the
-     * remainder, R, as Some((S, R)), or None if this number is negative.
-     *
-     * The numbers S, R are both positive and satisfy `self = S * S +
-     * R`.
+     * Returns the magnitude of self as exactly `len` big-endian bytes, padded with
+     * zeros, or `None` if it doesn't fit.
      */
-    pub fn sqrt_rem(mut self) -> Option<(Int, Int)> {
-        debug_assert!(self.well_formed());
+    pub fn to_bytes_be_padded(&self, len: usize) -> Option<Vec<u8>> {
+        self.to_bytes_le_padded(len).map(|mut bytes| {
+            bytes.reverse();
+            bytes
+        })
+    }
 
+    /**
+     * Returns the two's complement representation of self as little-endian bytes, using
+     * the fewest bytes that still preserve the sign.
+     */
+    pub fn to_signed_bytes_le(&self) -> Vec<u8> {
+        let mut bytes = self.abs_bytes_le();
         if self.sign() < 0 {
-            return None
+            twos_complement_le(&mut bytes);
+            // The magnitude is non-zero, so there is always a last byte
+            if bytes[bytes.len() - 1] & 0x80 == 0 {
+                bytes.push(0xff);
+            }
+        } else if bytes.last().is_none_or(|&b| b & 0x80 != 0) {
+            bytes.push(0);
         }
+        bytes
+    }
 
-        // the floor of a (correctly rounded) f64 sqrt gives the right
-        // answer, until this number (it is 67108865**2 - 1, but
-        // f64::sqrt is rounded *up* to 67108865 precisely).
-        if self < 4_503_599_761_588_224_u64 {
-            let this = u64::from(&self);
-            let sqrt = (this as f64).sqrt().floor() as u64;
-            let rem = this - sqrt * sqrt;
-
-            // reuse the memory
-            self.size = 0;
-            self.push(Limb(sqrt as BaseInt));
-            self.normalize();
+    /**
+     * Returns the two's complement representation of self as big-endian bytes, using
+     * the fewest bytes that still preserve the sign.
+     */
+    pub fn to_signed_bytes_be(&self) -> Vec<u8> {
+        let mut bytes = self.to_signed_bytes_le();
+        bytes.reverse();
+        bytes
+    }
 
-            Some((self,
-                  Int::from(rem)))
+    /**
+     * Encodes self as an ASN.1 DER INTEGER: the tag, the length and the minimal two's
+     * complement big-endian content.
+     */
+    pub fn to_der(&self) -> Vec<u8> {
+        let content = self.to_signed_bytes_be();
+        let len = content.len();
+
+        let mut der = Vec::with_capacity(len + 2 + mem::size_of::<usize>());
+        der.push(DER_INTEGER_TAG);
+        if len < 0x80 {
+            der.push(len as u8);
         } else {
-            let n = self.bit_length();
-            let l = (n as usize - 1) / 4;
-            assert!(l > 0);
-
-            let mask = (Int::from(1) << l) - 1;
-            let low = &self & &mask;
-            self >>= l;
-            let mut middle = &self & mask;
-            self >>= l;
-            let (high_sqrt, mut high_rem) = self.sqrt_rem().unwrap();
-
-            high_rem <<= l;
-            middle |= high_rem;
-            let (q, u) = middle.divmod(&(&high_sqrt << 1));
-
-            let mut s = (high_sqrt << l) + &q;
-            let mut r = (u << l) + low - q.dsquare();
-
-            if r < 0 {
-                r += &s << 1;
-                r -= 1;
-                s -= 1;
+            let len_bytes = mem::size_of::<usize>() - len.leading_zeros() as usize / 8;
+            der.push(0x80 | len_bytes as u8);
+            for i in (0..len_bytes).rev() {
+                der.push((len >> (8 * i)) as u8);
             }
-            debug_assert!(r >= 0);
-            Some((s, r))
         }
+        der.extend_from_slice(&content);
+        der
     }
 
     /**
-     * Negates `self` in-place
-     */
-    pub fn negate(&mut self) {
-        self.size *= -1;
-    }
-
-    /**
-     * Returns whether or not this number is even.
+     * Decodes an ASN.1 DER INTEGER that makes up the whole of `der`.
      *
-     * Returns 0 if `self == 0`
+     * Only the distinguished encoding is accepted: the length must use the fewest
+     * bytes possible and the content must be the minimal two's complement
+     * representation of the value.
      */
-    #[inline]
-    pub fn is_even(&self) -> bool {
-        debug_assert!(self.well_formed());
-        (self.to_single_limb().0 & 1) == 0
-    }
+    pub fn from_der(der: &[u8]) -> Result<Int, DerError> {
+        let (&tag, rest) = match der.split_first() {
+            Some(x) => x,
+            None => return Err(DerError { kind: DerErrorKind::Truncated }),
+        };
+        if tag != DER_INTEGER_TAG {
+            return Err(DerError { kind: DerErrorKind::UnexpectedTag(tag) });
+        }
 
-    /**
-     * Returns the number of trailing zero bits in this number
-     *
-     * Returns 0 if `self == 0`
-     */
-    #[inline]
-    pub fn trailing_zeros(&self) -> u32 {
-        debug_assert!(self.well_formed());
-        if self.sign() == 0 {
-            0
+        let (&first, mut rest) = match rest.split_first() {
+            Some(x) => x,
+            None => return Err(DerError { kind: DerErrorKind::Truncated }),
+        };
+        let len = if first < 0x80 {
+            first as usize
         } else {
-            unsafe {
-                ll::scan_1(self.limbs(), self.abs_size())
+            let len_bytes = (first & 0x7f) as usize;
+            // Indefinite lengths (0x80) aren't allowed in DER, and lengths
+            // that don't fit in a usize can't be satisfied anyway
+            if len_bytes == 0 || len_bytes > mem::size_of::<usize>() {
+                return Err(DerError { kind: DerErrorKind::InvalidLength });
             }
+            if rest.len() < len_bytes {
+                return Err(DerError { kind: DerErrorKind::Truncated });
+            }
+            let len = rest[..len_bytes].iter().fold(0, |l, &b| (l << 8) | b as usize);
+            if rest[0] == 0 || len < 0x80 {
+                return Err(DerError { kind: DerErrorKind::InvalidLength });
+            }
+            rest = &rest[len_bytes..];
+            len
+        };
+
+        if rest.len() < len {
+            return Err(DerError { kind: DerErrorKind::Truncated });
+        }
+        if rest.len() > len {
+            return Err(DerError { kind: DerErrorKind::TrailingData });
+        }
+
+        if rest.is_empty() {
+            return Err(DerError { kind: DerErrorKind::EmptyContent });
+        }
+        // A leading 0x00 or 0xff is only needed when it carries the sign
+        if rest.len() > 1 && (rest[0] == 0 || rest[0] == 0xff) && (rest[0] ^ rest[1]) & 0x80 == 0 {
+            return Err(DerError { kind: DerErrorKind::NonMinimal });
         }
+
+        Ok(Int::from_signed_bytes_be(rest))
     }
 
     /**
-     * Returns the number of ones (the population count) in this number
-     *
-     * If this number is negative, it has infinitely many ones (in
-     * two's complement), so this returns usize::MAX.
+     * Writes self as an SSH `mpint` (RFC 4251): a 32-bit big-endian length followed by
+     * the minimal two's complement big-endian bytes. Zero is written with no content
+     * bytes.
      */
-    pub fn count_ones(&self) -> usize {
-        debug_assert!(self.well_formed());
-        if self.sign() < 0 {
-            std::usize::MAX
-        } else {
-            let bytes = unsafe {
-                std::slice::from_raw_parts(self.ptr.get() as *const _ as *const u8,
-                                           self.abs_size() as usize * std::mem::size_of::<Limb>())
-            };
-            hamming::weight(bytes) as usize
+    #[cfg(feature = "std")]
+    pub fn write_ssh_mpint<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        let bytes = if self.sign() == 0 { Vec::new() } else { self.to_signed_bytes_be() };
+        if bytes.len() > u32::MAX as usize {
+            return Err(invalid_input("integer too large for an SSH mpint"));
         }
+        w.write_all(&be_u32(bytes.len() as u32))?;
+        w.write_all(&bytes)
     }
 
     /**
-     * Returns the number of bits required to represent (the absolute
-     * value of) this number, that is, `floor(log2(abs(self))) + 1`.
-     *
-     * Returns 1 if `self == 0`.
+     * Reads an SSH `mpint` (RFC 4251) as written by `write_ssh_mpint`. Encodings with
+     * unnecessary leading `0x00` or `0xff` bytes are rejected.
      */
-    #[inline]
-    pub fn bit_length(&self) -> u32 {
-        if *self == 0 {
-            1
-        } else {
-            unsafe {
-                ll::base::num_base_digits(self.limbs(), self.abs_size(), 2) as u32
-            }
+    #[cfg(feature = "std")]
+    pub fn read_ssh_mpint<R: io::Read>(r: &mut R) -> io::Result<Int> {
+        let len = read_be_u32(r)?;
+        let bytes = read_len(r, len as usize)?;
+
+        // Zero has no content bytes, and a leading 0x00 or 0xff is only
+        // allowed when it carries the sign
+        let redundant = match bytes.len() {
+            0 => false,
+            1 => bytes[0] == 0,
+            _ => (bytes[0] == 0 || bytes[0] == 0xff) && (bytes[0] ^ bytes[1]) & 0x80 == 0,
+        };
+        if redundant {
+            return Err(invalid_data("SSH mpint is not minimally encoded"));
         }
+
+        Ok(Int::from_signed_bytes_be(&bytes))
     }
 
     /**
-     * Returns the value of the `bit`th bit in this number, as if it
-     * were represented in two's complement.
+     * Writes self as an OpenPGP MPI (RFC 4880): a 16-bit big-endian bit count followed
+     * by the big-endian magnitude bytes.
+     *
+     * MPIs are unsigned, so this fails with `InvalidInput` if self is negative or has
+     * more than 65535 bits.
      */
-    #[inline]
-    pub fn bit(&self, bit: u32) -> bool {
-        let word = (bit / Limb::BITS as u32) as isize;
-        let subbit = bit % Limb::BITS as u32;
-        if word < self.abs_size() as isize {
-            let b = unsafe {
-                let w: Limb = *self.limbs().offset(word);
-                w.0 & (1 << subbit) != 0
-            };
-            if self.sign() >= 0 {
-                b
-            } else {
-                let first_one = self.trailing_zeros();
-                // the number is negative, so, in two's complement,
-                // bits up to and including the first one are the same
-                // as their sign-magnitude values (... ^ false), while
-                // bits beyond that are complemented (... ^ true)
-                b ^ (bit > first_one)
-            }
-        } else {
-            // we're beyond the in-memory limbs, so the bits are
-            // either all zeros (positive) or all ones (negative)
-            self.sign() < 0
+    #[cfg(feature = "std")]
+    pub fn write_pgp_mpi<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        if self.sign() < 0 {
+            return Err(invalid_input("OpenPGP MPIs can't be negative"));
         }
+        let bits = if self.sign() == 0 { 0 } else { self.bit_length() };
+        if bits > 0xffff {
+            return Err(invalid_input("integer too large for an OpenPGP MPI"));
+        }
+        w.write_all(&[(bits >> 8) as u8, bits as u8])?;
+        let mut bytes = self.abs_bytes_le();
+        bytes.reverse();
+        w.write_all(&bytes)
     }
 
     /**
-     * Set the `bit`th bit of this number to `bit_val`, treating
-     * negative numbers as if they're stored in two's complement.
+     * Reads an OpenPGP MPI (RFC 4880) as written by `write_pgp_mpi`. The bit count must
+     * match the bit length of the value exactly.
      */
-    pub fn set_bit(&mut self, bit: u32, bit_val: bool) {
-        debug_assert!(self.well_formed());
-        let word = bit / Limb::BITS as u32;
-        let subbit = bit % Limb::BITS as u32;
-        let flag = Limb(1 << subbit);
-
-        let sign = self.sign();
-
-        unsafe {
-
-            if word >= self.abs_size() as u32 {
-                // the bit is beyond the end, so more space is needed,
-                // and we need to be careful to ensure it's all zero
-                // because they'll all be part of the number itself
-                // used once the bit is set
-                self.ensure_capacity(word + 1);
-
-                let size = self.abs_size();
-                ll::zero(self.limbs_uninit().offset(size as isize), word as i32 - size + 1);
-
-                self.size = word as i32 + 1;
-                if sign < 0 {
-                    self.size = -self.size
-                }
-            }
-
-            if sign < 0 {
-                // this could probably be replaced by something
-                // similar to what `bit` does
-                self.negate_twos_complement();
-            }
-
-            let mut ptr = self.limbs_mut().offset(word as isize);
-            let val = if bit_val {
-                *ptr | flag
-            } else {
-                *ptr & !flag
-            };
-            *ptr = val;
+    #[cfg(feature = "std")]
+    pub fn read_pgp_mpi<R: io::Read>(r: &mut R) -> io::Result<Int> {
+        let mut len = [0; 2];
+        r.read_exact(&mut len)?;
+        let bits = (len[0] as usize) << 8 | len[1] as usize;
+        let bytes = read_len(r, bits.div_ceil(8))?;
 
-            if sign < 0 {
-                // put self back to normal
-                self.negate_twos_complement();
-            }
+        let i = Int::from_bytes_be(&bytes);
+        let actual_bits = if i.sign() == 0 { 0 } else { i.bit_length() };
+        if actual_bits != bits {
+            return Err(invalid_data("OpenPGP MPI bit count doesn't match its value"));
         }
-        self.normalize()
-    }
 
-    // get a Limbs to all limbs currently initialised/in use
-    fn limbs(&self) -> Limbs {
-        unsafe {
-            Limbs::new(self.ptr.get(), 0, self.abs_size())
-        }
-    }
-    // get a LimbsMut to all limbs currently initialised/in use
-    fn limbs_mut(&mut self) -> LimbsMut {
-        unsafe {
-            LimbsMut::new(self.ptr.get_mut(), 0, self.abs_size())
-        }
-    }
-    // get a LimbsMut to all allocated limbs
-    unsafe fn limbs_uninit(&mut self) -> LimbsMut {
-        LimbsMut::new(self.ptr.get_mut(), 0, self.cap as i32)
+        Ok(i)
     }
 
-    fn ensure_capacity(&mut self, cap: u32) {
-        if cap > self.cap {
-            let old_cap = self.cap as usize;
-            self.with_raw_vec(|v| {
-                v.reserve_exact(old_cap, cap as usize - old_cap)
-            })
+    /**
+     * Writes self in the format used by GMP's `mpz_out_raw`: a 32-bit big-endian two's
+     * complement byte count, negated for negative values, followed by the big-endian
+     * magnitude bytes.
+     */
+    #[cfg(feature = "std")]
+    pub fn write_gmp_raw<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        let mut bytes = self.abs_bytes_le();
+        bytes.reverse();
+        if bytes.len() > i32::MAX as usize {
+            return Err(invalid_input("integer too large for the GMP raw format"));
         }
+        let size = if self.sign() < 0 { -(bytes.len() as i32) } else { bytes.len() as i32 };
+        w.write_all(&be_u32(size as u32))?;
+        w.write_all(&bytes)
     }
 
-    fn push(&mut self, limb: Limb) {
-        let new_size = (self.abs_size() + 1) as u32;
-        self.ensure_capacity(new_size);
-        unsafe {
-            let pos = self.abs_size();
-            *self.limbs_uninit().offset(pos as isize) = limb;
-            // If it was previously empty, then just make it positive,
-            // otherwise maintain the signedness
-            if self.size == 0 {
-                self.size = 1;
-            } else {
-                self.size += self.sign();
-            }
-        }
+    /**
+     * Reads an integer in the format used by GMP's `mpz_inp_raw`, as written by
+     * `write_gmp_raw`. Like GMP, leading zero bytes in the magnitude are accepted.
+     */
+    #[cfg(feature = "std")]
+    pub fn read_gmp_raw<R: io::Read>(r: &mut R) -> io::Result<Int> {
+        let size = read_be_u32(r)? as i32;
+        let len = (size as i64).unsigned_abs() as usize;
+        let bytes = read_len(r, len)?;
+
+        let i = Int::from_bytes_be(&bytes);
+        Ok(if size < 0 { -i } else { i })
     }
 
     /**
-     * Adjust the size field so the most significant limb is non-zero
+     * Writes self as unsigned LEB128: seven bits per byte, least significant group first,
+     * with the high bit set on every byte but the last.
+     *
+     * Fails with `InvalidInput` if self is negative.
      */
-    fn normalize(&mut self) {
-        if self.size == 0 { return }
-        let sign = self.sign();
-        unsafe {
-            while self.size != 0 &&
-                *self.ptr.offset((self.abs_size() - 1) as isize) == 0 {
-
-                self.size -= sign;
-            }
+    #[cfg(feature = "std")]
+    pub fn write_uleb128<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        if self.sign() < 0 {
+            return Err(invalid_input("unsigned LEB128 can't encode negative values"));
         }
-        debug_assert!(self.well_formed());
+        let groups = self.bit_length().div_ceil(7);
+        w.write_all(&pack_7bit_groups(&self.abs_bytes_le(), 0, groups))
     }
 
     /**
-     * Make sure the Int is "well-formed", i.e. that the size doesn't exceed the
-     * the capacity and that the most significant limb is non-zero
+     * Reads an unsigned LEB128 value, as written by `write_uleb128`.
      */
-    fn well_formed(&self) -> bool {
-        if self.size == 0 { return true; }
-
-        if (self.abs_size() as u32) > self.cap {
-            return false;
-        }
+    #[cfg(feature = "std")]
+    pub fn read_uleb128<R: io::Read>(r: &mut R) -> io::Result<Int> {
+        let (bytes, _, _) = read_7bit_groups(r)?;
+        Ok(Int::from_bytes_le(&bytes))
+    }
 
-        let high_limb = unsafe {
-            *self.ptr.offset((self.abs_size() - 1) as isize)
+    /**
+     * Writes self as signed LEB128, which is like unsigned LEB128 applied to the two's
+     * complement representation, stopping once the remaining bits are all copies of the
+     * sign bit.
+     */
+    #[cfg(feature = "std")]
+    pub fn write_sleb128<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        // Bits needed for the value plus the sign bit
+        let bits = if self.sign() < 0 {
+            (-self - Limb(1)).bit_length() + 1
+        } else {
+            self.bit_length() + 1
         };
-
-        return high_limb != 0;
+        let fill = if self.sign() < 0 { 0xff } else { 0 };
+        w.write_all(&pack_7bit_groups(&self.to_signed_bytes_le(), fill, bits.div_ceil(7)))
     }
 
     /**
-     * convert self into two's complement format (i.e. *self =
-     * (!*self) + 1)
+     * Reads a signed LEB128 value, as written by `write_sleb128`.
      */
-    fn negate_twos_complement(&mut self) {
-        unsafe {
-            let self_ptr = self.limbs_mut();
-            let carry = ll::twos_complement(self_ptr, self_ptr.as_const(), self.abs_size());
-            if carry != 0 {
-                self.push(carry)
-            }
+    #[cfg(feature = "std")]
+    pub fn read_sleb128<R: io::Read>(r: &mut R) -> io::Result<Int> {
+        let (bytes, groups, last) = read_7bit_groups(r)?;
+        let i = Int::from_bytes_le(&bytes);
+        if last & 0x40 != 0 {
+            Ok(i - (Int::one() << (7 * groups)))
+        } else {
+            Ok(i)
         }
-        self.size = -self.size;
     }
 
-    /// Calculates the Greatest Common Divisor (GCD) of the number and `other`.
-    ///
-    /// The result is always positive.
-    #[inline]
-    pub fn gcd(&self, other: &Int) -> Int {
-        debug_assert!(self.well_formed());
-        debug_assert!(other.well_formed());
-
-        let (mut a, mut b) = if self.abs_size() >= other.abs_size() {
-            ((*self).clone(), (*other).clone())
+    /**
+     * Writes self with zigzag encoding, as used by protobuf's `sint` types: non-negative
+     * values `n` map to `2n` and negative ones to `-2n - 1`, which is then written as
+     * unsigned LEB128.
+     */
+    #[cfg(feature = "std")]
+    pub fn write_zigzag<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        let z = if self.sign() < 0 {
+            (self.clone().abs() << 1) - Limb(1)
         } else {
-            ((*other).clone(), (*self).clone())
+            self << 1
         };
+        z.write_uleb128(w)
+    }
 
-        if a == Int::zero() {
-            return b;
-        }
-
-        if b == Int::zero() {
-            return a;
+    /**
+     * Reads a zigzag encoded value, as written by `write_zigzag`.
+     */
+    #[cfg(feature = "std")]
+    pub fn read_zigzag<R: io::Read>(r: &mut R) -> io::Result<Int> {
+        let z = Int::read_uleb128(r)?;
+        if z.is_even() {
+            Ok(z >> 1)
+        } else {
+            Ok(-((z + Limb(1)) >> 1))
         }
+    }
 
-        let out_size = a.abs_size();
-        let mut r = Int::with_capacity(out_size as u32);
-        r.size = out_size;
+    // Builds a non-negative Int from `len` bytes, least significant first. The
+    // limbs are filled a byte at a time so the result doesn't depend on the
+    // size or endianness of `Limb`.
+    fn from_le_byte_iter<I: Iterator<Item=u8>>(len: usize, bytes: I) -> Int {
+        let limb_bytes = Limb::BITS / 8;
+        let n = len.div_ceil(limb_bytes);
 
+        let mut i = Int::with_capacity(n);
         unsafe {
-            ll::gcd(r.limbs_mut(), a.limbs_mut(), a.abs_size(), b.limbs_mut(), b.abs_size());
-            r.normalize();
-            r
+            let wp = i.limbs_uninit();
+            ll::zero(wp, n as isize);
+            for (k, b) in bytes.enumerate() {
+                let mut lp = wp.offset((k / limb_bytes) as isize);
+                *lp = *lp | Limb((b as BaseInt) << (8 * (k % limb_bytes)));
+            }
         }
+        i.size = n as isize;
+        i.normalize();
+        i
     }
 
-    /// Calculates the Lowest Common Multiple (LCM) of the number and `other`.
-    #[inline]
-    pub fn lcm(&self, other: &Int) -> Int {
-        (self * other).abs() / self.gcd(other)
-    }
+    // The magnitude of self as little-endian bytes, with no trailing zeros.
+    // Zero gives an empty vector.
+    fn abs_bytes_le(&self) -> Vec<u8> {
+        let limb_bytes = Limb::BITS / 8;
+        let size = self.abs_size();
 
-    pub fn to_f64(&self) -> f64 {
-        let sz = self.abs_size();
-        if sz == 0 {
-            return 0.0;
+        let mut bytes = Vec::with_capacity(size as usize * limb_bytes);
+        let lp = self.limbs();
+        for k in 0..size {
+            let l = unsafe { *lp.offset(k) };
+            for j in 0..limb_bytes {
+                bytes.push((l.0 >> (8 * j)) as u8);
+            }
         }
-
-        let mut highest_limb = unsafe {
-            *self.limbs().offset((sz - 1) as isize)
-        };
-        let leading_zeros = highest_limb.leading_zeros();
-        let mut shifted = 0;
-        if leading_zeros > 11 && sz > 1 {
-            highest_limb = highest_limb << leading_zeros;
-            let next_limb = unsafe {
-                *self.limbs().offset((sz - 2) as isize)
-            };
-
-            highest_limb = highest_limb | (next_limb >> (Limb::BITS - leading_zeros as usize));
-            shifted = leading_zeros;
+        while bytes.last() == Some(&0) {
+            bytes.pop();
         }
-
-        let exp = ((sz - 1) * Limb::BITS as i32) - shifted as i32;
-
-        let f = highest_limb.0 as f64;
-        let exp = (2.0f64).powi(exp);
-        f * exp
+        bytes
     }
 
-    /// Computes `self` to the power of `exp` modulus `modulus`.
-    ///
-    /// # Panic
-    ///
-    /// * Panics if modulus is negative.
-    /// * Panics if self is negative.
-    /// * Panics if exp is negative.
-    pub fn modpow(&self, exp:&Int, modulus:&Int) -> Int {
-        assert!(self.sign() >= 0);
-        assert!(exp.sign() >= 0);
-        assert!(modulus.sign() >= 0);
-
-        if exp.is_zero() || self == &Int::one() {
-            return if modulus == &Int::one() { Int::zero() } else { Int::one() }
+    /**
+     * Divide self by other, returning the quotient, Q, and remainder, R as (Q, R).
+     *
+     * With N = self, D = other, Q and R satisfy: `N = QD + R`.
+     * The sign of `Q` and `R` are the same.
+     *
+     * This will panic if `other` is zero.
+     */
+    pub fn divmod(&self, other: &Int) -> (Int, Int) {
+        debug_assert!(self.well_formed());
+        debug_assert!(other.well_formed());
+        if other.sign() == 0 {
+            ll::divide_by_zero();
         }
-
-        if self.is_zero() && exp.sign() > 1 {
-            return Self::zero();
+        if self.sign() == 0 {
+            return (self.clone(), Int::zero())
         }
 
-        if !modulus.is_even() {
-            return self.odd_modpow(exp, modulus);
-        }
+        let out_size = if self.abs_size() < other.abs_size() {
+            1
+        } else {
+            (self.abs_size() - other.abs_size()) + 1
+        };
 
-        let j = modulus.trailing_zeros() as usize;
-        if j+1 == modulus.bit_length() as usize {
-            return self.modpow2(&exp, j);
-        }
+        let out_sign = self.size.signum() * other.size.signum();
+        let mut q = Int::with_capacity(out_size as usize);
+        q.size = out_size * out_sign;
 
-        let q = modulus >> j;
+        let mut r = Int::with_capacity(other.abs_size() as usize);
+        r.size = other.abs_size() * self.size.signum();
 
-        let x1 = self.odd_modpow(exp, &q);
-        let x2 = self.modpow2(exp, j);
+        unsafe {
+            ll::divrem(q.limbs_mut(), r.limbs_mut(),
+                       self.limbs(), self.abs_size(),
+                       other.limbs(), other.abs_size());
+        }
 
-        let y = ((&x2-&x1) * q.inverse_for_powof2(j)) & ((Int::one()<<j) - 1);
+        q.normalize();
+        r.normalize();
 
-        x1 + q*y
+        (q, r)
     }
 
-}
+    /**
+     * Raises self to the power of exp
+     */
+    pub fn pow(&self, exp: usize) -> Int {
+        unwrap_alloc(self.try_pow(exp))
+    }
 
-impl Clone for Int {
-    fn clone(&self) -> Int {
+    /**
+     * Raises self to the power of exp, returning an error instead of panicking if
+     * the result is too large to represent or its memory can't be allocated.
+     *
+     * Like `try_mul`, this also covers the scratch space used by the intermediate
+     * multiplications.
+     */
+    pub fn try_pow(&self, exp: usize) -> Result<Int, AllocError> {
         debug_assert!(self.well_formed());
+        match exp {
+            0 => Ok(Int::one()),
+            1 => Ok(self.clone()),
+            2 if self.abs_size() <= 1 => Ok(self.square()),
+            2 => {
+                let sz = self.abs_size() as usize * 2;
+                let mut ret = Int::zero();
+                ret.try_set_capacity(sz)?;
+                ret.size = sz as isize;
+                let mut tmp = ::mem::TmpAllocator::new();
+                let scratch = try_scratch(&mut tmp, ll::sqr_scratch_limbs(self.abs_size()))?;
+                unsafe {
+                    ll::sqr_with_scratch(ret.limbs_mut(), self.limbs(), self.abs_size(), scratch);
+                }
+                ret.normalize();
 
-        if self.sign() == 0 {
-            return Int::zero();
-        }
+                Ok(ret)
+            }
+            _ => {
+                let mut signum = self.size.signum();
+                if signum == 0 {
+                    return Ok(Int::zero());
+                }
+                if exp & 1 == 0 {
+                    signum = 1
+                }
+                if self.abs_size() == 1 && self.to_single_limb() == 1 {
+                    return Ok(Int::from(signum));
+                }
 
-        let mut new = Int::with_capacity(self.abs_size() as u32);
-        unsafe {
-            ll::copy_incr(self.limbs(), new.limbs_uninit(), self.abs_size());
-        }
-        new.size = self.size;
-        new
-    }
+                let ret_sz = match self.pow_limbs(exp) {
+                    Some(sz) => sz,
+                    None => return Err(AllocError { kind: AllocErrorKind::CapacityOverflow }),
+                };
+                let mut ret = Int::zero();
+                ret.try_set_capacity(ret_sz)?;
+                ret.size = ret_sz as isize * signum;
+                let mut tmp = ::mem::TmpAllocator::new();
+                let scratch = try_scratch(&mut tmp, ll::pow::pow_scratch_limbs(ret_sz as isize))?;
 
-    fn clone_from(&mut self, other: &Int) {
-        debug_assert!(self.well_formed());
-        debug_assert!(other.well_formed());
+                unsafe {
+                    ll::pow::pow_with_scratch(ret.limbs_mut(), self.limbs(), self.abs_size(), exp,
+                                              scratch);
+                }
 
-        if other.sign() == 0 {
-            self.size = 0;
-            return;
-        }
-        self.ensure_capacity(other.abs_size() as u32);
-        unsafe {
-            ll::copy_incr(other.limbs(), self.limbs_uninit(), other.abs_size());
-            self.size = other.size;
+                ret.normalize();
+
+                Ok(ret)
+            }
         }
     }
-}
 
-impl std::default::Default for Int {
-    #[inline]
-    fn default() -> Int {
-        Int::zero()
+    /**
+     * Returns the number of limbs `ll::pow::pow` needs for `self` raised to the power of `exp`,
+     * or `None` if that's more than can be handled. This is the same estimate as
+     * `ll::pow::num_pow_limbs`, but computed without risk of overflow.
+     */
+    fn pow_limbs(&self, exp: usize) -> Option<usize> {
+        let n = self.abs_size() as u64 - 1;
+        let high_limb = unsafe { *self.limbs().offset(n as isize) };
+        let lg2 = Limb::BITS as u64 - high_limb.0.leading_zeros() as u64;
+
+        // `ll::pow::pow` needs twice the result size in scratch space, and `num_pow_limbs` works
+        // with `isize`s.
+        let max = isize::MAX as u64 / 2 - 1;
+        let exp = exp as u64;
+        let lg2e = match lg2.checked_mul(exp) {
+            Some(lg2e) if lg2e <= max => lg2e,
+            _ => return None,
+        };
+        match exp.checked_mul(n).and_then(|en| en.checked_add(lg2e / Limb::BITS as u64 + 1)) {
+            Some(sz) if sz <= max => Some(sz as usize),
+            _ => None,
+        }
     }
-}
 
-impl Drop for Int {
-    fn drop(&mut self) {
-        if self.cap > 0 {
+    /**
+     * Returns the square of `self`.
+     */
+    pub fn square(&self) -> Int {
+        debug_assert!(self.well_formed());
+        let s = self.sign();
+        if s == 0 {
+            Int::zero()
+        } else if self.abs_size() == 1 {
+            let a = self.clone() * self.to_single_limb();
+            if s == -1 {
+                a.abs()
+            } else if s == 1 {
+                a
+            } else {
+                unreachable!()
+            }
+        } else {
+            let sz = self.abs_size() * 2;
+            let mut ret = Int::with_capacity(sz as usize);
+            ret.size = sz;
             unsafe {
-                drop(RawVec::from_raw_parts(self.ptr.get_mut(),
-                                            self.cap as usize));
+                ll::sqr(ret.limbs_mut(), self.limbs(), self.abs_size());
             }
-            self.cap = 0;
-            self.size = 0;
+            ret.normalize();
+
+            ret
         }
     }
-}
 
-impl PartialEq<Int> for Int {
-    #[inline]
-    fn eq(&self, other: &Int) -> bool {
+    // DESTRUCTIVE square. Is there a more idiomatic way of doing this?
+    pub fn dsquare(mut self) -> Int {
         debug_assert!(self.well_formed());
-        debug_assert!(other.well_formed());
-        if self.size == other.size {
-            unsafe {
-                ll::cmp(self.limbs(), other.limbs(), self.abs_size()) == Ordering::Equal
+        let s = self.sign();
+        if s == 0 {
+            Int::zero()
+        } else if self.abs_size() == 1 {
+            let l = self.to_single_limb();
+            self *= l;
+            if s == -1 {
+                self.abs()
+            } else if s == 1 {
+                self
+            } else {
+                unreachable!()
             }
         } else {
-            false
+            self.square()
         }
     }
-}
 
-impl PartialEq<Limb> for Int {
-    #[inline]
-    fn eq(&self, other: &Limb) -> bool {
-        if *other == 0 && self.size == 0 {
-            return true;
+    /**
+     * Compute the sqrt of this number, returning its floor, S,  and the
+     * remainder, R, as Some((S, R)), or None if this number is negative.
+     *
+     * The numbers S, R are both positive and satisfy `self = S * S +
+     * R`.
+     */
+    pub fn sqrt_rem(mut self) -> Option<(Int, Int)> {
+        debug_assert!(self.well_formed());
+
+        if self.sign() < 0 {
+            return None
         }
 
-        self.size == 1 && *self.limbs() == *other
-    }
-}
+        // the floor of a (correctly rounded) f64 sqrt gives the right
+        // answer, until this number (it is 67108865**2 - 1, but
+        // f64::sqrt is rounded *up* to 67108865 precisely).
+        if self < 4_503_599_761_588_224_u64 {
+            let this = self.to_u64_wrapping();
+            let sqrt = sqrt_u64(this);
+            let rem = this - sqrt * sqrt;
 
-impl PartialEq<Int> for Limb {
-    #[inline]
-    fn eq(&self, other: &Int) -> bool {
-        other.eq(self)
-    }
-}
+            // reuse the memory
+            self.size = 0;
+            self.push(Limb(sqrt as BaseInt));
+            self.normalize();
 
-impl Eq for Int { }
+            Some((self,
+                  Int::from(rem)))
+        } else {
+            let n = self.bit_length();
+            let l = (n - 1) / 4;
+            assert!(l > 0);
 
-impl Ord for Int {
-    #[inline]
-    fn cmp(&self, other: &Int) -> Ordering {
-        if self.size < other.size {
-            Ordering::Less
-        } else if self.size > other.size {
-            Ordering::Greater
-        } else { // Same number of digits and same sign
-            // Check for zero
-            if self.size == 0 {
-                return Ordering::Equal;
-            }
-            unsafe {
-                // If both are positive, do `self cmp other`, if both are
-                // negative, do `other cmp self`
-                if self.sign() == 1 {
-                    ll::cmp(self.limbs(), other.limbs(), self.abs_size())
-                } else {
-                    ll::cmp(other.limbs(), self.limbs(), self.abs_size())
-                }
+            let mask = (Int::from(1) << l) - 1;
+            let low = &self & &mask;
+            self >>= l;
+            let mut middle = &self & mask;
+            self >>= l;
+            let (high_sqrt, mut high_rem) = self.sqrt_rem().unwrap();
+
+            high_rem <<= l;
+            middle |= high_rem;
+            let (q, u) = middle.divmod(&(&high_sqrt << 1));
+
+            let mut s = (high_sqrt << l) + &q;
+            let mut r = (u << l) + low - q.dsquare();
+
+            if r < 0 {
+                r += &s << 1;
+                r -= 1;
+                s -= 1;
             }
+            debug_assert!(r >= 0);
+            Some((s, r))
         }
     }
-}
 
-impl PartialOrd<Int> for Int {
-    #[inline]
-    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
-        Some(self.cmp(other))
+    /**
+     * Negates `self` in-place
+     */
+    pub fn negate(&mut self) {
+        self.size *= -1;
     }
-}
 
-impl PartialOrd<Limb> for Int {
+    /**
+     * Returns whether or not this number is even.
+     *
+     * Returns 0 if `self == 0`
+     */
     #[inline]
-    fn partial_cmp(&self, other: &Limb) -> Option<Ordering> {
-        if self.eq(other) {
-            return Some(Ordering::Equal);
-        }
-
-        if self.size < 1 {
-            Some(Ordering::Less)
-        } else if self.size > 1 {
-            Some(Ordering::Greater)
-        } else {
-            (*self.limbs()).partial_cmp(other)
-        }
+    pub fn is_even(&self) -> bool {
+        debug_assert!(self.well_formed());
+        (self.to_single_limb().0 & 1) == 0
     }
-}
 
-impl PartialOrd<Int> for Limb {
+    /**
+     * Returns the number of trailing zero bits in this number
+     *
+     * Returns 0 if `self == 0`
+     */
     #[inline]
-    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
-        other.partial_cmp(self).map(|o| o.reverse())
-    }
-}
-
-impl hash::Hash for Int {
-    fn hash<H>(&self, state: &mut H) where H: hash::Hasher {
+    pub fn trailing_zeros(&self) -> usize {
         debug_assert!(self.well_formed());
-        self.sign().hash(state);
-        self.abs_hash(state);
+        if self.sign() == 0 {
+            0
+        } else {
+            unsafe {
+                ll::scan_1(self.limbs(), self.abs_size())
+            }
+        }
     }
-}
 
-impl AddAssign<Limb> for Int {
-    fn add_assign(&mut self, other: Limb) {
+    /**
+     * Returns the number of ones (the population count) in this number
+     *
+     * If this number is negative, it has infinitely many ones (in
+     * two's complement), so this returns usize::MAX.
+     */
+    pub fn count_ones(&self) -> usize {
         debug_assert!(self.well_formed());
-        if other == 0 { return; }
-
-        // No capacity means `self` is zero. Just push `other` into it
-        if self.cap == 0 {
-            self.push(other);
-            return;
+        if self.sign() < 0 {
+            usize::MAX
+        } else {
+            let bytes = unsafe {
+                std::slice::from_raw_parts(self.ptr() as *const u8,
+                                           self.abs_size() as usize * std::mem::size_of::<Limb>())
+            };
+            weight(bytes) as usize
         }
-        // This is zero, but has allocated space, so just store `other`
-        if self.size == 0 {
+    }
+
+    /**
+     * Returns the number of bits required to represent (the absolute
+     * value of) this number, that is, `floor(log2(abs(self))) + 1`.
+     *
+     * Returns 1 if `self == 0`.
+     */
+    #[inline]
+    pub fn bit_length(&self) -> usize {
+        if *self == 0 {
+            1
+        } else {
             unsafe {
-                *self.limbs_uninit() = other;
-                self.size = 1;
-                return
+                ll::base::num_base_digits(self.limbs(), self.abs_size(), 2)
             }
         }
-        // `self` is non-zero, reuse the storage for the result.
-        unsafe {
-            let sign = self.sign();
-            let size = self.abs_size();
-            let mut ptr = self.limbs_mut();
+    }
 
-            // Self is positive, just add `other`
-            if sign == 1 {
-                let carry = ll::add_1(ptr, ptr.as_const(), size, other);
-                if carry != 0 {
-                    self.push(carry);
-                }
+    /**
+     * Returns the value of the `bit`th bit in this number, as if it
+     * were represented in two's complement.
+     */
+    #[inline]
+    pub fn bit(&self, bit: usize) -> bool {
+        let word = bit / Limb::BITS;
+        let subbit = bit % Limb::BITS;
+        if word < self.abs_size() as usize {
+            let b = unsafe {
+                let w: Limb = *self.limbs().offset(word as isize);
+                w.0 & (1 << subbit) != 0
+            };
+            if self.sign() >= 0 {
+                b
             } else {
-                // Self is negative, "subtract" other from self, basically doing:
-                // -(-self - other) == self + other
-                let borrow = ll::sub_1(ptr, ptr.as_const(), size, other);
-                if borrow != 0 {
-                    // There was a borrow, ignore it but flip the sign on self
-                    self.size = self.abs_size();
-                    *ptr = -*ptr;
-                }
-                self.normalize();
+                let first_one = self.trailing_zeros();
+                // the number is negative, so, in two's complement,
+                // bits up to and including the first one are the same
+                // as their sign-magnitude values (... ^ false), while
+                // bits beyond that are complemented (... ^ true)
+                b ^ (bit > first_one)
             }
+        } else {
+            // we're beyond the in-memory limbs, so the bits are
+            // either all zeros (positive) or all ones (negative)
+            self.sign() < 0
         }
     }
-}
-
-impl Add<Limb> for Int {
-    type Output = Int;
-
-    #[inline]
-    fn add(mut self, other: Limb) -> Int {
-        self += other;
-        self
-    }
-}
 
-impl<'a> AddAssign<&'a Int> for Int {
-    fn add_assign(&mut self, other: &'a Int) {
+    /**
+     * Set the `bit`th bit of this number to `bit_val`, treating
+     * negative numbers as if they're stored in two's complement.
+     */
+    pub fn set_bit(&mut self, bit: usize, bit_val: bool) {
         debug_assert!(self.well_formed());
-        debug_assert!(other.well_formed());
-
-        if self.sign() == 0 {
-            // Try to reuse the allocation from `self`
-            self.clone_from(other);
-            return;
-        }
-        if other.sign() == 0 {
-            return;
-        }
+        let word = bit / Limb::BITS;
+        let subbit = bit % Limb::BITS;
+        let flag = Limb(1 << subbit);
 
+        let sign = self.size.signum();
 
-        if self.sign() == other.sign() {
-            // Signs are the same, add the two numbers together and re-apply
-            // the sign after.
-            let sign = self.sign();
+        unsafe {
 
-            unsafe {
-                // There's a restriction that x-size >= y-size, we can swap the operands
-                // no problem, but we'd like to re-use `self`s memory if possible, so
-                // if `self` is the smaller of the two we make sure it has enough space
-                // for the result
-                let (xp, xs, yp, ys) = if self.abs_size() >= other.abs_size() {
-                    (self.limbs(), self.abs_size(), other.limbs(), other.abs_size())
-                } else {
-                    self.ensure_capacity(other.abs_size() as u32);
-                    (other.limbs(), other.abs_size(), self.limbs(), self.abs_size())
-                };
+            if word >= self.abs_size() as usize {
+                // the bit is beyond the end, so more space is needed,
+                // and we need to be careful to ensure it's all zero
+                // because they'll all be part of the number itself
+                // used once the bit is set
+                self.ensure_capacity(word + 1);
 
-                // Fetch the pointer first to make completely sure the compiler
-                // won't make bogus claims about nonaliasing due to the &mut
-                let ptr = self.limbs_uninit();
+                let size = self.abs_size();
+                ll::zero(self.limbs_uninit().offset(size), word as isize - size + 1);
 
-                let carry = ll::add(ptr,
-                                    xp, xs,
-                                    yp, ys);
-                self.size = xs * sign;
-                if carry != 0 {
-                    self.push(carry);
+                self.size = word as isize + 1;
+                if sign < 0 {
+                    self.size = -self.size
                 }
-                self.normalize();
             }
-        } else {
-            // Signs are different, use the sign from the bigger (absolute value)
-            // of the two numbers and subtract the smaller one.
-
-            unsafe {
-                let (xp, xs, yp, ys) = if self.abs_size() > other.abs_size() {
-                    (self.limbs(), self.size, other.limbs(), other.size)
-                } else if self.abs_size() < other.abs_size() {
-                    self.ensure_capacity(other.abs_size() as u32);
-                    (other.limbs(), other.size, self.limbs(), self.size)
-                } else {
-                    match self.abs_cmp(other) {
-                        Ordering::Equal => {
-                            // They're equal, but opposite signs, so the result
-                            // will be zero, clear `self` and return
-                            self.size = 0;
-                            return;
-                        }
-                        Ordering::Greater =>
-                            (self.limbs(), self.size, other.limbs(), other.size),
-                        Ordering::Less =>
-                            (other.limbs(), other.size, self.limbs(), self.size)
-                    }
-                };
 
-                // Fetch the pointer first to make completely sure the compiler
-                // won't make bogus claims about nonaliasing due to the &mut
-                let ptr = self.limbs_uninit();
+            if sign < 0 {
+                // this could probably be replaced by something
+                // similar to what `bit` does
+                self.negate_twos_complement();
+            }
 
-                let _borrow = ll::sub(ptr,
-                                      xp, xs.abs(),
-                                      yp, ys.abs());
-                // There shouldn't be any borrow
-                debug_assert!(_borrow == 0);
+            let mut ptr = self.limbs_mut().offset(word as isize);
+            let val = if bit_val {
+                *ptr | flag
+            } else {
+                *ptr & !flag
+            };
+            *ptr = val;
 
-                self.size = xs;
-                self.normalize();
-                debug_assert!(self.abs_size() > 0);
+            if sign < 0 {
+                // put self back to normal
+                self.negate_twos_complement();
             }
         }
+        self.normalize()
     }
-}
 
-impl<'a> Add<&'a Int> for Int {
-    type Output = Int;
-
-    #[inline]
-    fn add(mut self, other: &'a Int) -> Int {
-        self += other;
-        self
+    // get a Limbs to all limbs currently initialised/in use
+    fn limbs(&self) -> Limbs {
+        unsafe {
+            Limbs::new(self.ptr(), 0, self.abs_size())
+        }
     }
-}
-
-impl<'a> Add<Int> for &'a Int {
-    type Output = Int;
-
-    #[inline]
-    fn add(self, other: Int) -> Int {
-        // Forward to other + self
-        other.add(self)
+    // get a LimbsMut to all limbs currently initialised/in use
+    fn limbs_mut(&mut self) -> LimbsMut {
+        unsafe {
+            LimbsMut::new(self.ptr_mut(), 0, self.abs_size())
+        }
+    }
+    // get a LimbsMut to all allocated limbs
+    unsafe fn limbs_uninit(&mut self) -> LimbsMut {
+        LimbsMut::new(self.ptr_mut(), 0, self.cap as isize)
     }
-}
-
-impl Add<Int> for Int {
-    type Output = Int;
 
-    #[inline]
-    fn add(self, other: Int) -> Int {
-        // Check for self or other being zero.
-        if self.sign() == 0 {
-            return other;
-        }
-        if other.sign() == 0 {
-            return self;
+    fn ensure_capacity(&mut self, cap: usize) {
+        if cap > self.cap {
+            self.set_capacity(cap)
         }
+    }
 
-        let (x, y) = if self.abs_size() >= other.abs_size() {
-            (self, &other)
-        } else {
-            (other, &self)
-        };
-
-        return x.add(y);
+    fn push(&mut self, limb: Limb) {
+        let new_size = (self.abs_size() + 1) as usize;
+        self.ensure_capacity(new_size);
+        unsafe {
+            let pos = self.abs_size();
+            *self.limbs_uninit().offset(pos) = limb;
+            // If it was previously empty, then just make it positive,
+            // otherwise maintain the signedness
+            if self.size == 0 {
+                self.size = 1;
+            } else {
+                self.size += self.size.signum();
+            }
+        }
     }
-}
 
-impl AddAssign<Int> for Int {
-    #[inline]
-    fn add_assign(&mut self, mut other: Int) {
-        // Use the allocation of the larger of the two inputs.
-        // Doing the obvious and simply swapping self and other
-        // when other.size > self.size results in poor codegen.
-        if other.abs_size() > self.abs_size() {
-            // Instead we doing the addition in-place, then overwrite
-            // self with other. This results in better codegen and better
-            // memory allocation behaviour.
-            other += &*self;
-            *self = other;
-        } else {
-            *self += &other;
+    /**
+     * Adjust the size field so the most significant limb is non-zero
+     */
+    fn normalize(&mut self) {
+        if self.size == 0 { return }
+        let sign = self.size.signum();
+        unsafe {
+            while self.size != 0 &&
+                *self.ptr().offset(self.abs_size() - 1) == 0 {
+
+                self.size -= sign;
+            }
         }
+        debug_assert!(self.well_formed());
     }
-}
 
-impl<'a, 'b> Add<&'a Int> for &'b Int {
-    type Output = Int;
+    /**
+     * Make sure the Int is "well-formed", i.e. that the size doesn't exceed the
+     * the capacity and that the most significant limb is non-zero
+     */
+    fn well_formed(&self) -> bool {
+        if self.size == 0 { return true; }
 
-    #[inline]
-    fn add(self, other: &'a Int) -> Int {
-        if self.sign() == 0 {
-            return other.clone();
-        }
-        if other.sign() == 0 {
-            return self.clone();
+        if (self.abs_size() as usize) > self.cap {
+            return false;
         }
 
-        // Clone the bigger of the two
-        if self.abs_size() >= other.abs_size() {
-            self.clone().add(other)
-        } else {
-            other.clone().add(self)
-        }
+        let high_limb = unsafe {
+            *self.ptr().offset(self.abs_size() - 1)
+        };
+
+        high_limb != 0
     }
-}
 
-impl SubAssign<Limb> for Int {
-    fn sub_assign(&mut self, other: Limb) {
-        debug_assert!(self.well_formed());
-        if other == 0 { return; }
+    /**
+     * convert self into two's complement format (i.e. *self =
+     * (!*self) + 1)
+     */
+    fn negate_twos_complement(&mut self) {
+        unsafe {
+            let self_ptr = self.limbs_mut();
+            let carry = ll::twos_complement(self_ptr, self_ptr.as_const(), self.abs_size());
+            if carry != 0 {
+                self.push(carry)
+            }
+        }
+        self.size = -self.size;
+    }
 
-        // No capacity means `self` is zero. Just push the limb.
-        if self.cap == 0 {
-            self.push(other);
-            self.size = -1;
+    /**
+     * Adds the integer `{yp, |ys|}` to `self`, where the sign of `ys` gives
+     * the sign of the integer. `yp` must not point into `self`.
+     */
+    unsafe fn add_assign_limbs(&mut self, yp: Limbs, ys: isize) {
+        if self.sign() == 0 {
+            // Try to reuse the allocation from `self`
+            self.ensure_capacity(ys.unsigned_abs());
+            ll::copy_incr(yp, self.limbs_uninit(), ys.abs());
+            self.size = ys;
             return;
         }
-        // This is zero, but has allocated space, so just store `other`
-        if self.size == 0 {
-            unsafe {
-                *self.limbs_uninit() = other;
-                self.size = -1;
-            }
+        if ys == 0 {
             return;
         }
-        // `self` is non-zero, reuse the storage for the result.
-        unsafe {
-            let sign = self.sign();
-            let size = self.abs_size();
-            let ptr = self.limbs_mut();
 
-            // Self is negative, just "add" `other`
-            if sign == -1 {
-                let carry = ll::add_1(ptr, ptr.as_const(), size, other);
-                if carry != 0 {
-                    self.push(carry);
-                }
+        if self.size.signum() == ys.signum() {
+            // Signs are the same, add the two numbers together and re-apply
+            // the sign after.
+            let sign = self.size.signum();
+
+            // There's a restriction that x-size >= y-size, we can swap the operands
+            // no problem, but we'd like to re-use `self`s memory if possible, so
+            // if `self` is the smaller of the two we make sure it has enough space
+            // for the result
+            let (xp, xs, yp, ys) = if self.abs_size() >= ys.abs() {
+                (self.limbs(), self.abs_size(), yp, ys.abs())
             } else {
-                // Self is positive, subtract other from self
-                let carry = ll::sub_1(ptr, ptr.as_const(), size, other);
-                self.normalize();
-                if carry != 0 {
-                    // There was a carry, and the native operations
-                    // work with two's complement, so we need to get
-                    // everything back into sign-magnitude form
-                    self.negate_twos_complement()
-                }
+                self.ensure_capacity(ys.unsigned_abs());
+                (yp, ys.abs(), self.limbs(), self.abs_size())
+            };
+
+            // Fetch the pointer first to make completely sure the compiler
+            // won't make bogus claims about nonaliasing due to the &mut
+            let ptr = self.limbs_uninit();
+
+            let carry = ll::add(ptr,
+                                xp, xs,
+                                yp, ys);
+            self.size = xs * sign;
+            if carry != 0 {
+                self.push(carry);
             }
-        }
+            self.normalize();
+        } else {
+            // Signs are different, use the sign from the bigger (absolute value)
+            // of the two numbers and subtract the smaller one.
+            let ord = if self.abs_size() == ys.abs() {
+                ll::cmp(self.limbs(), yp, ys.abs())
+            } else {
+                self.abs_size().cmp(&ys.abs())
+            };
 
-        debug_assert!(self.well_formed());
-    }
-}
+            let (xp, xs, yp, ys) = match ord {
+                Ordering::Equal => {
+                    // They're equal, but opposite signs, so the result
+                    // will be zero, clear `self` and return
+                    self.size = 0;
+                    return;
+                }
+                Ordering::Greater =>
+                    (self.limbs(), self.size, yp, ys),
+                Ordering::Less => {
+                    self.ensure_capacity(ys.unsigned_abs());
+                    (yp, ys, self.limbs(), self.size)
+                }
+            };
 
-impl Sub<Limb> for Int {
-    type Output = Int;
+            // Fetch the pointer first to make completely sure the compiler
+            // won't make bogus claims about nonaliasing due to the &mut
+            let ptr = self.limbs_uninit();
 
-    #[inline]
-    fn sub(mut self, other: Limb) -> Int {
-        self -= other;
-        self
-    }
-}
+            let _borrow = ll::sub(ptr,
+                                  xp, xs.abs(),
+                                  yp, ys.abs());
+            // There shouldn't be any borrow
+            debug_assert!(_borrow == 0);
 
-impl<'a> SubAssign<&'a Int> for Int {
-    fn sub_assign(&mut self, other: &'a Int) {
-        debug_assert!(self.well_formed());
-        debug_assert!(other.well_formed());
+            self.size = xs;
+            self.normalize();
+            debug_assert!(self.abs_size() > 0);
+        }
+    }
 
-        // LHS is zero, set self to the negation of the RHS
-        if self.sign() == 0 {
-            self.clone_from(other);
-            self.size *= -1;
+    /**
+     * Adds `{ap, |asize|} * b` to `self`, where the sign of `asize` gives the
+     * sign of the product. `ap` must not point into `self`.
+     */
+    unsafe fn addmul_1_signed(&mut self, ap: Limbs, asize: isize, b: Limb) {
+        if asize == 0 || b == 0 {
             return;
         }
-        // RHS is zero, do nothing
-        if other.sign() == 0 {
-            return;
+
+        let an = asize.abs();
+        let sn = self.abs_size();
+        let n = std::cmp::max(an, sn);
+        let sign = if sn == 0 { asize.signum() } else { self.size.signum() };
+
+        // Make room for `n` limbs plus a carry, zero-extending `self` if
+        // the product is longer
+        self.ensure_capacity((n + 1) as usize);
+        if sn < n {
+            ll::zero(self.limbs_uninit().offset(sn), n - sn);
         }
 
-        if self.sign() == other.sign() {
-            unsafe {
-                // Signs are the same, subtract the smaller one from
-                // the bigger one and adjust the sign as appropriate
-                let (xp, xs, yp, ys, flip) = match self.abs_cmp(other) {
-                    Ordering::Equal => {
-                        // x - x, just return zero
-                        self.size = 0;
-                        return;
-                    }
-                    Ordering::Less => {
-                        self.ensure_capacity(other.abs_size() as u32);
-                        (other.limbs(), other.size, self.limbs(), self.size, true)
-                    }
-                    Ordering::Greater =>
-                        (self.limbs(), self.size, other.limbs(), other.size, false)
-                };
+        let wp = self.limbs_uninit();
+        if sign == asize.signum() {
+            let mut carry = ll::addmul_1(wp, ap, an, b);
+            if n > an {
+                let hp = wp.offset(an);
+                carry = ll::add_1(hp, hp.as_const(), n - an, carry);
+            }
+            *wp.offset(n as isize) = carry;
+            self.size = (n + 1) * sign;
+        } else {
+            let mut borrow = ll::submul_1(wp, ap, an, b);
+            if n > an {
+                let hp = wp.offset(an);
+                borrow = ll::sub_1(hp, hp.as_const(), n - an, borrow);
+            }
+            if borrow == 0 {
+                self.size = n * sign;
+            } else {
+                // The product was bigger than `self`, so we're left with
+                // `{wp, n} - borrow * B^n`. Negate that to get back to
+                // sign-magnitude form, flipping the sign.
+                let carry = ll::twos_complement(wp, wp.as_const(), n);
+                *wp.offset(n as isize) = borrow - 1 + carry;
+                self.size = -(n + 1) * sign;
+            }
+        }
+        self.normalize();
+    }
 
-                // Fetch the pointer first to make completely sure the compiler
-                // won't make bogus claims about nonaliasing due to the &mut
-                let ptr = self.limbs_uninit();
+    fn add_mul_signed(&mut self, a: &Int, b: &Int, sign: isize) {
+        debug_assert!(self.well_formed());
+        debug_assert!(a.well_formed());
+        debug_assert!(b.well_formed());
 
-                let _borrow = ll::sub(ptr, xp, xs.abs(), yp, ys.abs());
-                debug_assert!(_borrow == 0);
-                self.size = if flip {
-                    xs * -1
-                } else {
-                    xs
-                };
+        if a.sign() == 0 || b.sign() == 0 {
+            return;
+        }
+        let sign = sign * a.size.signum() * b.size.signum();
+
+        unsafe {
+            if a.abs_size() == 1 {
+                return self.addmul_1_signed(b.limbs(), b.abs_size() * sign, *a.limbs());
+            }
+            if b.abs_size() == 1 {
+                return self.addmul_1_signed(a.limbs(), a.abs_size() * sign, *b.limbs());
             }
 
-            self.normalize();
-        } else { // Different signs
-            if self.sign() == -1 {
-                // self is negative, use addition and negation
-                self.size *= -1;
-                *self += other;
-                self.size *= -1;
+            let (xp, xs, yp, ys) = if a.abs_size() >= b.abs_size() {
+                (a.limbs(), a.abs_size(), b.limbs(), b.abs_size())
             } else {
-                unsafe {
-                    // Other is negative, handle as addition
-                    let (xp, xs, yp, ys) = if self.abs_size() >= other.abs_size() {
-                        (self.limbs(), self.abs_size(), other.limbs(), other.abs_size())
-                    } else {
-                        self.ensure_capacity(other.abs_size() as u32);
-                        (other.limbs(), other.abs_size(), self.limbs(), self.abs_size())
-                    };
+                (b.limbs(), b.abs_size(), a.limbs(), a.abs_size())
+            };
 
-                    // Fetch the pointer first to make completely sure the compiler
-                    // won't make bogus claims about nonaliasing due to the &mut
-                    let ptr = self.limbs_uninit();
+            // The product only lives in scratch space for as long as it
+            // takes to add it to `self`.
+            let mut tmp = ::mem::TmpAllocator::new();
+            let prod = tmp.allocate((xs + ys) as usize);
+            ll::mul(prod, xp, xs, yp, ys);
+            let size = ll::normalize(prod.as_const(), xs + ys);
 
-                    let carry = ll::add(ptr, xp, xs, yp, ys);
-                    self.size = xs;
-                    if carry != 0 {
-                        self.push(carry);
-                    }
-                    self.normalize();
-                }
-            }
+            self.add_assign_limbs(prod.as_const(), size * sign);
         }
     }
-}
-
-impl<'a> Sub<&'a Int> for Int {
-    type Output = Int;
 
+    /// Calculates the Greatest Common Divisor (GCD) of the number and `other`.
+    ///
+    /// The result is always positive.
     #[inline]
-    fn sub(mut self, other: &'a Int) -> Int {
-        self -= other;
-        self
-    }
-}
+    pub fn gcd(&self, other: &Int) -> Int {
+        debug_assert!(self.well_formed());
+        debug_assert!(other.well_formed());
 
-impl<'a> Sub<Int> for &'a Int {
-    type Output = Int;
+        let (mut a, mut b) = if self.abs_size() >= other.abs_size() {
+            ((*self).clone(), (*other).clone())
+        } else {
+            ((*other).clone(), (*self).clone())
+        };
 
-    #[inline]
-    fn sub(self, mut other: Int) -> Int {
-        if self.sign() == 0 {
-            return -other;
+        if a == Int::zero() {
+            return b;
         }
-        if other.sign() == 0 {
-            other.clone_from(self);
-            return other;
+
+        if b == Int::zero() {
+            return a;
         }
 
-        -(other.sub(self))
+        let out_size = a.abs_size();
+        let mut r = Int::with_capacity(out_size as usize);
+        r.size = out_size;
+
+        unsafe {
+            ll::gcd(r.limbs_mut(), a.limbs_mut(), a.abs_size(), b.limbs_mut(), b.abs_size());
+            r.normalize();
+            r
+        }
     }
-}
-
-impl Sub<Int> for Int {
-    type Output = Int;
 
+    /// Calculates the Lowest Common Multiple (LCM) of the number and `other`.
     #[inline]
-    fn sub(self, other: Int) -> Int {
-        if self.sign() == 0 {
-            return -other;
-        }
-        if other.sign() == 0 {
-            return self;
-        }
-
-        self.sub(&other)
+    pub fn lcm(&self, other: &Int) -> Int {
+        (self * other).abs() / self.gcd(other)
     }
-}
 
-impl SubAssign<Int> for Int {
-    #[inline]
-    fn sub_assign(&mut self, other: Int) {
-        if other.sign() == 0 {
-            return;
-        }
-        if self.sign() == 0 {
-            *self = -other;
-            return;
-        }
-        *self -= &other;
+    /**
+     * Converts to the nearest `f64`, rounding ties to even. Values too large to represent
+     * become infinity.
+     */
+    pub fn to_f64(&self) -> f64 {
+        f64::from_bits(self.to_float_bits(float::F64))
     }
-}
 
-impl<'a, 'b> Sub<&'a Int> for &'b Int {
-    type Output = Int;
+    /**
+     * Converts to the nearest `f32`, rounding ties to even. Values too large to represent
+     * become infinity.
+     */
+    pub fn to_f32(&self) -> f32 {
+        f32::from_bits(self.to_float_bits(float::F32) as u32)
+    }
 
-    #[inline]
-    fn sub(self, other: &'a Int) -> Int {
-        if self.sign() == 0 {
-            return -other;
-        }
-        if other.sign() == 0 {
-            return self.clone();
+    fn to_float_bits(&self, fmt: float::Format) -> u64 {
+        if self.sign() < 0 {
+            float::to_bits(&-self, 0, false, true, fmt)
+        } else {
+            float::to_bits(self, 0, false, false, fmt)
         }
+    }
 
-        self.clone().sub(other)
+    /**
+     * Converts a float to an `Int`, discarding any fractional part like an `as` cast
+     * does. Returns `None` for infinities and NaN.
+     */
+    pub fn from_f64(val: f64) -> Option<Int> {
+        Int::from_f64_impl(val, false)
     }
-}
 
-impl MulAssign<Limb> for Int {
-    fn mul_assign(&mut self, other: Limb) {
-        debug_assert!(self.well_formed());
-        if other == 0 || self.sign() == 0 {
-            self.size = 0;
-            return;
-        }
+    /**
+     * Converts a float to the nearest `Int`, rounding ties to even. Returns `None` for
+     * infinities and NaN.
+     */
+    pub fn from_f64_rounded(val: f64) -> Option<Int> {
+        Int::from_f64_impl(val, true)
+    }
 
-        if other == 1 {
-            return;
+    fn from_f64_impl(val: f64, round: bool) -> Option<Int> {
+        let bits = val.to_bits();
+        let neg = bits >> 63 == 1;
+        let biased = ((bits >> 52) & 0x7ff) as isize;
+        let frac = bits & ((1 << 52) - 1);
+        if biased == 0x7ff {
+            return None;
         }
 
-        unsafe {
-            // Fetch the pointer first to make completely sure the compiler
-            // won't make bogus claims about nonaliasing due to the &mut
+        // val = m * 2^exp
+        let (m, exp) = if biased == 0 {
+            (frac, -1074)
+        } else {
+            (frac | (1 << 52), biased - 1075)
+        };
 
-            let carry = ll::mul_1(self.limbs_mut(), self.limbs(), self.abs_size(), other);
-            if carry != 0 {
-                self.push(carry);
+        let mut i = if exp >= 0 {
+            Int::from(m) << exp as usize
+        } else if exp < -53 {
+            // |val| < 1/2
+            Int::zero()
+        } else {
+            let shift = -exp as u32;
+            let mut q = m >> shift;
+            let r = m & ((1 << shift) - 1);
+            let half = 1 << (shift - 1);
+            if round && (r > half || (r == half && q & 1 == 1)) {
+                q += 1;
             }
-        }
-    }
-}
-
-impl Mul<Limb> for Int {
-    type Output = Int;
+            Int::from(q)
+        };
 
-    #[inline]
-    fn mul(mut self, other: Limb) -> Int {
-        self *= other;
-        self
+        if neg {
+            i.negate();
+        }
+        Some(i)
     }
-}
-
-impl<'a, 'b> Mul<&'a Int> for &'b Int {
-    type Output = Int;
 
-    fn mul(self, other: &'a Int) -> Int {
-        debug_assert!(self.well_formed());
-        debug_assert!(other.well_formed());
-        // This is the main function, since in the general case
-        // we need to allocate space for the return. Special cases
-        // where this isn't necessary are handled in the other impls
+    /// Computes `self` to the power of `exp` modulus `modulus`.
+    ///
+    /// # Panic
+    ///
+    /// * Panics if modulus is negative.
+    /// * Panics if self is negative.
+    /// * Panics if exp is negative.
+    pub fn modpow(&self, exp:&Int, modulus:&Int) -> Int {
+        assert!(self.sign() >= 0);
+        assert!(exp.sign() >= 0);
+        assert!(modulus.sign() >= 0);
 
-        // 0 * x = 0
-        if self.sign() == 0 || other.sign() == 0 {
-            return Int::zero();
+        if exp.is_zero() || self == &Int::one() {
+            return if modulus == &Int::one() { Int::zero() } else { Int::one() }
         }
 
-        let out_sign = self.sign() * other.sign();
+        if self.is_zero() && exp.sign() > 1 {
+            return Self::zero();
+        }
 
-        if self.abs_size() == 1 {
-            let mut ret = other.clone() * *self.limbs();
-            let size = ret.abs_size();
-            ret.size = size * out_sign;
-            return ret;
+        if !modulus.is_even() {
+            return self.odd_modpow(exp, modulus);
         }
-        if other.abs_size() == 1 {
-            let mut ret = self.clone() * *other.limbs();
-            let size = ret.abs_size();
-            ret.size = size * out_sign;
-            return ret;
+
+        let j = modulus.trailing_zeros();
+        if j+1 == modulus.bit_length() {
+            return self.modpow2(exp, j);
         }
 
-        let out_size = self.abs_size() + other.abs_size();
+        let q = modulus >> j;
 
-        let mut out = Int::with_capacity(out_size as u32);
-        out.size = out_size * out_sign;
+        let x1 = self.odd_modpow(exp, &q);
+        let x2 = self.modpow2(exp, j);
 
-        unsafe {
-            let (xp, xs, yp, ys) = if self.abs_size() >= other.abs_size() {
-                (self.limbs(), self.abs_size(), other.limbs(), other.abs_size())
-            } else {
-                (other.limbs(), other.abs_size(), self.limbs(), self.abs_size())
-            };
-            ll::mul(out.limbs_mut(), xp, xs, yp, ys);
+        let y = ((&x2-&x1) * q.inverse_for_powof2(j)) & ((Int::one()<<j) - 1);
 
-            // Top limb may be zero
-            out.normalize();
-            return out;
-        }
+        x1 + q*y
     }
-}
 
-impl<'a> Mul<&'a Int> for Int {
-    type Output = Int;
+    /**
+     * Computes `self += a * b`, accumulating directly into `self`'s storage.
+     *
+     * This avoids allocating a temporary `Int` for the product, as would be
+     * the case with `acc += &a * &b`.
+     */
+    pub fn add_mul(&mut self, a: &Int, b: &Int) {
+        self.add_mul_signed(a, b, 1);
+    }
 
-    #[inline]
-    fn mul(mut self, other: &'a Int) -> Int {
-        // `other` is zero
-        if other.sign() == 0 {
-            self.size = 0;
-            return self;
-        }
+    /**
+     * Computes `self -= a * b`, accumulating directly into `self`'s storage.
+     */
+    pub fn sub_mul(&mut self, a: &Int, b: &Int) {
+        self.add_mul_signed(a, b, -1);
+    }
 
-        // `other` is a single limb, reuse the allocation of self
-        if other.abs_size() == 1 {
-            let mut out = self * *other.limbs();
-            out.size *= other.sign();
-            return out;
+    /**
+     * Computes `self += a * b` for a single-limb `b`, using `ll::addmul_1`
+     * (or `ll::submul_1` if the signs differ) on `self`'s storage.
+     */
+    pub fn add_mul_limb(&mut self, a: &Int, b: Limb) {
+        debug_assert!(self.well_formed());
+        debug_assert!(a.well_formed());
+        unsafe {
+            self.addmul_1_signed(a.limbs(), a.size, b);
         }
+    }
 
-        // Forward to the by-reference impl
-        (&self) * other
+    /**
+     * Computes `self -= a * b` for a single-limb `b`.
+     */
+    pub fn sub_mul_limb(&mut self, a: &Int, b: Limb) {
+        debug_assert!(self.well_formed());
+        debug_assert!(a.well_formed());
+        unsafe {
+            self.addmul_1_signed(a.limbs(), -a.size, b);
+        }
     }
-}
 
-impl<'a> Mul<Int> for &'a Int {
-    type Output = Int;
+    /**
+     * Stores `a + b` in `dst`, reusing the allocation of `dst`.
+     */
+    pub fn add_into(dst: &mut Int, a: &Int, b: &Int) {
+        dst.clone_from(a);
+        *dst += b;
+    }
 
-    #[inline]
-    fn mul(self, other: Int) -> Int {
-        // Swap arguments
-        other * self
+    /**
+     * Stores `a - b` in `dst`, reusing the allocation of `dst`.
+     */
+    pub fn sub_into(dst: &mut Int, a: &Int, b: &Int) {
+        dst.clone_from(a);
+        *dst -= b;
     }
-}
 
-impl Mul<Int> for Int {
-    type Output = Int;
+    /**
+     * Stores `a * b` in `dst`, reusing the allocation of `dst`. The
+     * allocation only grows if it is too small to hold the product.
+     */
+    pub fn mul_into(dst: &mut Int, a: &Int, b: &Int) {
+        debug_assert!(a.well_formed());
+        debug_assert!(b.well_formed());
 
-    fn mul(mut self, other: Int) -> Int {
-        if self.sign() == 0 || other.sign() == 0 {
-            self.size = 0;
-            return self;
+        if a.sign() == 0 || b.sign() == 0 {
+            dst.size = 0;
+            return;
         }
 
-        // One of them is a single limb big, so we can re-use the
-        // allocation of the other
-        if self.abs_size() == 1 {
-            let val = *self.limbs();
-            let mut out = other * val;
-            out.size *= self.sign();
-            return out;
+        let out_sign = a.size.signum() * b.size.signum();
+
+        // Single-limb operands can be multiplied in-place
+        if a.abs_size() == 1 || b.abs_size() == 1 {
+            let (x, y) = if a.abs_size() == 1 { (b, a) } else { (a, b) };
+            dst.clone_from(x);
+            *dst *= y.to_single_limb();
+            dst.size = dst.abs_size() * out_sign;
+            return;
         }
-        if other.abs_size() == 1 {
-            let val = *other.limbs();
-            let mut out = self * val;
-            out.size *= other.sign();
-            return out;
+
+        let (xp, xs, yp, ys) = if a.abs_size() >= b.abs_size() {
+            (a.limbs(), a.abs_size(), b.limbs(), b.abs_size())
+        } else {
+            (b.limbs(), b.abs_size(), a.limbs(), a.abs_size())
+        };
+
+        // The old value is about to be overwritten, so there's no
+        // point in preserving it if the allocation needs to grow
+        dst.size = 0;
+        dst.ensure_capacity((xs + ys) as usize);
+
+        unsafe {
+            ll::mul(dst.limbs_uninit(), xp, xs, yp, ys);
         }
+        dst.size = (xs + ys) * out_sign;
+        dst.normalize();
+    }
 
-        // Still need to allocate for the result, just forward to
-        // the by-reference impl
-        (&self) * (&other)
+    /**
+     * Makes sure `self` can hold at least `additional` more limbs than it currently uses,
+     * returning an error if that is more than an `Int` can hold or the memory can't be
+     * allocated.
+     */
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        match (self.abs_size() as usize).checked_add(additional) {
+            Some(cap) if cap > self.cap => self.try_set_capacity(cap),
+            Some(_) => Ok(()),
+            None => Err(AllocError { kind: AllocErrorKind::CapacityOverflow }),
+        }
     }
-}
 
-impl<'a> MulAssign<&'a Int> for Int {
-    #[inline]
-    fn mul_assign(&mut self, other: &'a Int) {
-        if self.sign() == 0 {
-            return;
+    /**
+     * Multiplies `self` by `other`, returning an error instead of panicking if
+     * the memory for the product can't be allocated.
+     *
+     * Large multiplications also need temporary scratch space, a few times the size of the
+     * larger operand. That is allocated fallibly too, and only ever counts against the memory
+     * available, not the limit set by `set_limb_limit`.
+     */
+    pub fn try_mul(&self, other: &Int) -> Result<Int, AllocError> {
+        let mut ret = Int::zero();
+        if self.sign() == 0 || other.sign() == 0 {
+            return Ok(ret);
         }
-        if other.sign() == 0 {
-            self.size = 0;
-            return;
+        ret.try_reserve(self.abs_size() as usize + other.abs_size() as usize)?;
+        if self.abs_size() == 1 || other.abs_size() == 1 {
+            Int::mul_into(&mut ret, self, other);
+            return Ok(ret);
         }
-        let res = &*self * other;
-        *self = res;
+
+        let (xp, xs, yp, ys) = if self.abs_size() >= other.abs_size() {
+            (self.limbs(), self.abs_size(), other.limbs(), other.abs_size())
+        } else {
+            (other.limbs(), other.abs_size(), self.limbs(), self.abs_size())
+        };
+        let mut tmp = ::mem::TmpAllocator::new();
+        let scratch = try_scratch(&mut tmp, ll::mul_scratch_limbs(xs, ys))?;
+        unsafe {
+            ll::mul_with_scratch(ret.limbs_uninit(), xp, xs, yp, ys, scratch);
+        }
+        ret.size = (xs + ys) * self.size.signum() * other.size.signum();
+        ret.normalize();
+        Ok(ret)
     }
-}
 
-impl MulAssign<Int> for Int {
-    #[inline]
-    fn mul_assign(&mut self, other: Int) {
+    /**
+     * Shifts `self` left by `cnt` bits, returning an error instead of panicking
+     * if the result is too large to represent or its memory can't be allocated.
+     */
+    pub fn try_shl(&self, cnt: usize) -> Result<Int, AllocError> {
+        let mut ret = Int::zero();
         if self.sign() == 0 {
-            return;
+            return Ok(ret);
         }
-        if other.sign() == 0 {
-            self.size = 0;
-            return;
+        // Same as the space `<<=` asks for
+        match (cnt / Limb::BITS).checked_add(self.abs_size() as usize + 1) {
+            Some(cap) => ret.try_reserve(cap)?,
+            None => return Err(AllocError { kind: AllocErrorKind::CapacityOverflow }),
         }
-        let res = &*self * other;
-        *self = res;
+        ret.clone_from(self);
+        ret <<= cnt;
+        Ok(ret)
     }
-}
 
-impl DivAssign<Limb> for Int {
-    fn div_assign(&mut self, other: Limb) {
-        debug_assert!(self.well_formed());
-        if other == 0 {
-            ll::divide_by_zero();
-        }
-        if other == 1 || self.sign() == 0 {
-            return;
-        }
+    /**
+     * Limits the number of limbs any `Int` created or grown on the current thread may use.
+     * Operations whose result would need more limbs than this panic, while the `try_*`
+     * methods and `from_str_radix` return an error instead. `None` removes the limit.
+     *
+     * Since the limit applies to allocations, an operation may be refused if it would need a
+     * limb or so more space than its final result actually occupies. Values that fit in the
+     * inline storage of an `Int` are never affected.
+     *
+     * Without the `std` feature there are no thread-locals to keep the limit in, so it applies
+     * to every thread.
+     */
+    pub fn set_limb_limit(limit: Option<usize>) {
+        set_limb_limit(limit.unwrap_or(usize::MAX));
+    }
 
-        unsafe {
-            // Ignore the remainder
-            ll::divrem_1(self.limbs_mut(), 0, self.limbs(), self.abs_size(), other);
-            // Adjust the size if necessary
-            self.normalize();
+    /**
+     * Returns the limit set by `set_limb_limit` for the current thread, if any.
+     */
+    pub fn limb_limit() -> Option<usize> {
+        match get_limb_limit() {
+            usize::MAX => None,
+            limit => Some(limit),
         }
     }
 }
 
-impl Div<Limb> for Int {
-    type Output = Int;
+/// The ASN.1 tag for an INTEGER.
+const DER_INTEGER_TAG: u8 = 0x02;
 
-    #[inline]
-    fn div(mut self, other: Limb) -> Int {
-        self /= other;
-        self
-    }
+#[cfg(feature = "std")]
+thread_local!(static LIMB_LIMIT: Cell<usize> = const { Cell::new(usize::MAX) });
+
+#[cfg(not(feature = "std"))]
+static LIMB_LIMIT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(usize::MAX);
+
+#[cfg(feature = "std")]
+fn get_limb_limit() -> usize {
+    LIMB_LIMIT.with(|l| l.get())
 }
 
-impl<'a, 'b> Div<&'a Int> for &'b Int {
-    type Output = Int;
+#[cfg(feature = "std")]
+fn set_limb_limit(limit: usize) {
+    LIMB_LIMIT.with(|l| l.set(limit))
+}
 
-    fn div(self, other: &'a Int) -> Int {
-        debug_assert!(self.well_formed());
-        debug_assert!(other.well_formed());
-        if other.sign() == 0 {
-            ll::divide_by_zero();
-        }
-        if other.abs_size() == 1 {
-            let l = *other.limbs();
-            let out_sign = self.sign() * other.sign();
-            let mut out = self.clone() / l;
-            out.size = out.abs_size() * out_sign;
-            return out;
-        }
+#[cfg(not(feature = "std"))]
+fn get_limb_limit() -> usize {
+    LIMB_LIMIT.load(std::sync::atomic::Ordering::Relaxed)
+}
 
-        self.divmod(other).0
-    }
+#[cfg(not(feature = "std"))]
+fn set_limb_limit(limit: usize) {
+    LIMB_LIMIT.store(limit, std::sync::atomic::Ordering::Relaxed)
 }
 
-impl<'a> Div<&'a Int> for Int {
-    type Output = Int;
+#[cfg(feature = "std")]
+fn sqrt_u64(n: u64) -> u64 {
+    (n as f64).sqrt().floor() as u64
+}
 
-    #[inline]
-    fn div(self, other: &'a Int) -> Int {
-        (&self) / other
+#[cfg(not(feature = "std"))]
+fn sqrt_u64(n: u64) -> u64 {
+    sqrt_u64_newton(n)
+}
+
+/// Newton's method on integers, for when there's no `f64::sqrt`.
+#[cfg(any(not(feature = "std"), test))]
+fn sqrt_u64_newton(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    // Start at a power of two that's at least the root, and work down
+    let mut x = 1u64 << (64 - n.leading_zeros()).div_ceil(2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
     }
 }
 
-impl<'a> Div<Int> for &'a Int {
-    type Output = Int;
+#[cfg(not(feature = "std"))]
+fn weight(bytes: &[u8]) -> u64 {
+    weight_portable(bytes)
+}
 
-    #[inline]
-    fn div(self, other: Int) -> Int {
-        self / (&other)
-    }
+/// Counts the set bits in `bytes`, standing in for `hamming::weight`.
+#[cfg(any(not(feature = "std"), test))]
+fn weight_portable(bytes: &[u8]) -> u64 {
+    bytes.iter().map(|b| b.count_ones() as u64).sum()
 }
 
-impl Div<Int> for Int {
-    type Output = Int;
+/// An iterator over the digits of an `Int`, returned by `Int::digits`.
+#[derive(Clone, Debug)]
+pub struct Digits {
+    inner: std::vec::IntoIter<u32>,
+}
+
+impl Iterator for Digits {
+    type Item = u32;
 
     #[inline]
-    fn div(self, other: Int) -> Int {
-        (&self) / (&other)
+    fn next(&mut self) -> Option<u32> {
+        self.inner.next()
     }
-}
 
-impl<'a> DivAssign<&'a Int> for Int {
     #[inline]
-    fn div_assign(&mut self, other: &'a Int) {
-        let res = &*self / other;
-        *self = res;
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
     }
 }
 
-impl DivAssign<Int> for Int {
+impl DoubleEndedIterator for Digits {
     #[inline]
-    fn div_assign(&mut self, other: Int) {
-        let res = &*self / other;
-        *self = res;
+    fn next_back(&mut self) -> Option<u32> {
+        self.inner.next_back()
     }
 }
 
-impl Rem<Limb> for Int {
-    type Output = Int;
-
-    #[inline]
-    fn rem(mut self, other: Limb) -> Int {
-        self %= other;
-        self
-    }
+impl ExactSizeIterator for Digits { }
+
+/// Builds up the value of a stream of digits, most significant first, one limb
+/// at a time.
+#[cfg(feature = "std")]
+struct DigitAccumulator {
+    int: Int,
+    base: u8,
+    digits: usize,
+    // Digits not yet added to `int`
+    cur: Limb,
+    cur_digits: u32,
 }
 
-impl RemAssign<Limb> for Int {
-    fn rem_assign(&mut self, other: Limb) {
-        debug_assert!(self.well_formed());
-        if other == 0 {
-            ll::divide_by_zero();
-        }
-        // x % 1 == 0, 0 % n == 0
-        if other == 1 || self.sign() == 0 {
-            self.size = 0;
-            return;
+#[cfg(feature = "std")]
+impl DigitAccumulator {
+    fn new(base: u8) -> DigitAccumulator {
+        DigitAccumulator {
+            int: Int::zero(),
+            base,
+            digits: 0,
+            cur: Limb(0),
+            cur_digits: 0,
+        }
+    }
+
+    fn push(&mut self, digit: u8) -> Result<(), AllocError> {
+        self.digits += 1;
+        let base = self.base as u32;
+
+        if base.is_power_of_two() {
+            // Pack the bits into limbs in the order they arrive. The limbs are put
+            // into least-significant-first order by `finish`.
+            let bits = base.trailing_zeros();
+            let room = Limb::BITS as u32 - self.cur_digits;
+            let digit = Limb(digit as BaseInt);
+            if bits < room {
+                self.cur = (self.cur << bits as usize) | digit;
+                self.cur_digits += bits;
+            } else {
+                let rest = bits - room;
+                let full = (self.cur << room as usize) | (digit >> rest as usize);
+                self.reserve()?;
+                self.int.push(full);
+                self.cur = digit & ((Limb(1) << rest as usize) - 1);
+                self.cur_digits = rest;
+            }
+        } else {
+            let (digits_per_limb, big_base) = ll::base::big_base(base as u64);
+            self.cur = self.cur * (base as BaseInt) + (digit as BaseInt);
+            self.cur_digits += 1;
+            if self.cur_digits == digits_per_limb {
+                let cur = self.cur;
+                self.add_chunk(big_base, cur)?;
+                self.cur = Limb(0);
+                self.cur_digits = 0;
+            }
         }
 
-        unsafe {
-            let rem = ll::divrem_1(self.limbs_mut(), 0, self.limbs(), self.abs_size(), other);
-            // Reuse the space from `self`, taking the sign from the numerator
-            // Since `rem` has to satisfy `N = QD + R` and D is always positive,
-            // `R` will always be the same sign as the numerator.
-            *self.limbs_mut() = rem;
-            let sign = self.sign();
-            self.size = sign;
+        Ok(())
+    }
 
-            self.normalize();
+    fn finish(mut self) -> Result<Int, AllocError> {
+        let base = self.base as u32;
 
-            if self.cap > 8 {
-                // Shrink self, since it's at least 8 times bigger than necessary
-                self.shrink_to_fit();
+        if base.is_power_of_two() {
+            let n = self.int.abs_size();
+            if n > 0 {
+                unsafe {
+                    std::slice::from_raw_parts_mut(self.int.ptr_mut(), n as usize).reverse();
+                }
+                self.int.normalize();
             }
-
+            if self.cur_digits > 0 {
+                self.int.try_reserve(1)?;
+                self.int <<= self.cur_digits as usize;
+                self.int += self.cur;
+            }
+            Ok(self.int)
+        } else {
+            if self.cur_digits > 0 {
+                let mult = Limb((base as BaseInt).pow(self.cur_digits));
+                let cur = self.cur;
+                self.add_chunk(mult, cur)?;
+            }
+            Ok(self.int)
         }
     }
-}
 
-impl DivRem<Limb> for Int {
-    type Output = (Int, Limb);
+    // Sets the value to `value * mult + chunk`
+    fn add_chunk(&mut self, mult: Limb, chunk: Limb) -> Result<(), AllocError> {
+        self.reserve()?;
+        self.int *= mult;
+        self.int += chunk;
+        Ok(())
+    }
 
-    fn divrem(mut self, other: Limb) -> Self::Output {
-        debug_assert!(self.well_formed());
-        if other == 0 {
-            ll::divide_by_zero();
-        }
-        // x % 1 == 0, 0 % n == 0
-        if other == 1 || self.sign() == 0 {
-            self.size = 0;
-            return (self, Limb(0));
+    // Makes room for another limb, growing geometrically to keep the number of
+    // reallocations down
+    fn reserve(&mut self) -> Result<(), AllocError> {
+        let size = self.int.abs_size() as usize;
+        if size + 1 > self.int.cap {
+            self.int.try_reserve(std::cmp::max(size, 1))?;
         }
-
-        let rem = unsafe { ll::divrem_1(self.limbs_mut(), 0, self.limbs(), self.abs_size(), other) };
-        self.normalize();
-        return (self, rem);
+        Ok(())
     }
 }
 
-// TODO: There's probably too much cloning happening here, need to figure out
-// the best way of avoiding over-copying.
-
-impl<'a, 'b> Rem<&'a Int> for &'b Int {
-    type Output = Int;
-
-    fn rem(self, other: &'a Int) -> Int {
-        debug_assert!(self.well_formed());
-        debug_assert!(other.well_formed());
-        if other.sign() == 0 {
-            ll::divide_by_zero();
-        }
-        if other.abs_size() == 1 {
-            let l = *other.limbs();
-            return self.clone() % l;
-        }
-
-        self.divmod(other).1
-    }
+#[cfg(feature = "std")]
+fn alloc_io_error(err: AllocError) -> io::Error {
+    io::Error::other(err)
 }
 
-impl<'a> Rem<&'a Int> for Int {
-    type Output = Int;
+#[cfg(feature = "std")]
+fn be_u32(v: u32) -> [u8; 4] {
+    [(v >> 24) as u8, (v >> 16) as u8, (v >> 8) as u8, v as u8]
+}
 
-    #[inline]
-    fn rem(self, other: &'a Int) -> Int {
-        (&self) % other
-    }
+#[cfg(feature = "std")]
+fn read_be_u32<R: io::Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(buf.iter().fold(0, |v, &b| (v << 8) | b as u32))
 }
 
-impl<'a> Rem<Int> for &'a Int {
-    type Output = Int;
+/// Reads exactly `len` bytes. The buffer grows as data actually arrives, so a
+/// corrupt length prefix can't make us allocate a huge amount up front.
+#[cfg(feature = "std")]
+fn read_len<R: io::Read>(r: &mut R, len: usize) -> io::Result<Vec<u8>> {
+    use std::io::Read;
 
-    #[inline]
-    fn rem(self, other: Int) -> Int {
-        self % (&other)
+    let mut bytes = Vec::new();
+    r.by_ref().take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "integer data is truncated"));
     }
+    Ok(bytes)
 }
 
-impl Rem<Int> for Int {
-    type Output = Int;
+/// Splits the little-endian `bytes`, extended with `fill`, into `groups` LEB128
+/// bytes. Working from bytes rather than limbs keeps the output the same for
+/// every `Limb` size.
+#[cfg(feature = "std")]
+fn pack_7bit_groups(bytes: &[u8], fill: u8, groups: usize) -> Vec<u8> {
+    debug_assert!(groups > 0);
+    let mut src = bytes.iter().cloned().chain(std::iter::repeat(fill));
+    let mut out = Vec::with_capacity(groups);
+
+    let mut acc = 0u32;
+    let mut nbits = 0;
+    while out.len() < groups {
+        if nbits < 7 {
+            acc |= (src.next().unwrap() as u32) << nbits;
+            nbits += 8;
+        }
+        out.push(acc as u8 | 0x80);
+        acc >>= 7;
+        nbits -= 7;
+    }
+
+    out[groups - 1] &= 0x7f;
+    out
+}
 
-    #[inline]
-    fn rem(self, other: Int) -> Int {
-        (&self) % (&other)
+/// Reads LEB128 bytes up to and including the one without the continuation
+/// bit. Returns the 7-bit groups packed into little-endian bytes, the number of
+/// groups and the last group.
+#[cfg(feature = "std")]
+fn read_7bit_groups<R: io::Read>(r: &mut R) -> io::Result<(Vec<u8>, usize, u8)> {
+    let mut bytes = Vec::new();
+    let mut groups = 0;
+
+    let mut acc = 0u32;
+    let mut nbits = 0;
+    loop {
+        let mut b = [0];
+        r.read_exact(&mut b)?;
+        groups += 1;
+
+        acc |= ((b[0] & 0x7f) as u32) << nbits;
+        nbits += 7;
+        if nbits >= 8 {
+            bytes.push(acc as u8);
+            acc >>= 8;
+            nbits -= 8;
+        }
+
+        if b[0] & 0x80 == 0 {
+            if nbits > 0 {
+                bytes.push(acc as u8);
+            }
+            return Ok((bytes, groups, b[0]));
+        }
     }
 }
 
-impl<'a, 'b> DivRem<&'a Int> for &'b Int {
-    type Output = (Int, Int);
+#[cfg(feature = "std")]
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
 
-    #[inline]
-    fn divrem(self, other: &'a Int) -> (Int, Int) {
-        self.divmod(other)
-    }
+#[cfg(feature = "std")]
+fn invalid_input(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
 }
 
-impl RemAssign<Int> for Int {
-    #[inline]
-    fn rem_assign(&mut self, other: Int) {
-        let res = &*self % other;
-        *self = res;
+/// Replaces little-endian `bytes` with their two's complement negation, in place.
+fn twos_complement_le(bytes: &mut [u8]) {
+    let mut carry = true;
+    for b in bytes.iter_mut() {
+        *b = !*b;
+        if carry {
+            let (v, c) = b.overflowing_add(1);
+            *b = v;
+            carry = c;
+        }
     }
 }
-impl<'a> RemAssign<&'a Int> for Int {
-    #[inline]
-    fn rem_assign(&mut self, other: &'a Int) {
-        let res = &*self % other;
-        *self = res;
-    }
+
+/// Panics for results that need more limbs than an `Int` can hold.
+#[cold]
+#[inline(never)]
+fn capacity_overflow() -> ! {
+    panic!("capacity overflow")
 }
 
-impl Neg for Int {
-    type Output = Int;
+/// Panics for results whose memory the allocator refused. Sizes on the order of the address
+/// space pass the `isize` checks but can never be allocated, so this panics rather than
+/// aborting the process.
+#[cold]
+#[inline(never)]
+fn alloc_failed(size: usize) -> ! {
+    panic!("allocation failed: couldn't allocate {} bytes", size)
+}
 
-    #[inline]
-    fn neg(mut self) -> Int {
-        debug_assert!(self.well_formed());
-        self.size *= -1;
-        self
+/// Allocates `n` limbs of scratch space from `tmp` for the `try_*` methods, returning an
+/// error instead of aborting if that isn't possible.
+fn try_scratch(tmp: &mut ::mem::TmpAllocator, n: usize) -> Result<LimbsMut, AllocError> {
+    match unsafe { tmp.try_allocate(n) } {
+        Some(scratch) => Ok(scratch),
+        None => Err(match n.checked_mul(std::mem::size_of::<Limb>()) {
+            Some(bytes) if bytes <= isize::MAX as usize =>
+                AllocError { kind: AllocErrorKind::AllocFailed(bytes) },
+            _ => AllocError { kind: AllocErrorKind::CapacityOverflow },
+        }),
     }
 }
 
-impl<'a> Neg for &'a Int {
-    type Output = Int;
-
-    #[inline]
-    fn neg(self) -> Int {
-        self.clone().neg()
+/// Unwraps the result of a fallible allocation, using the same failure
+/// behaviour as the rest of the library.
+fn unwrap_alloc<T>(res: Result<T, AllocError>) -> T {
+    match res {
+        Ok(v) => v,
+        Err(AllocError { kind: AllocErrorKind::CapacityOverflow }) => capacity_overflow(),
+        Err(AllocError { kind: AllocErrorKind::LimitExceeded }) => panic!("limb limit exceeded"),
+        Err(AllocError { kind: AllocErrorKind::AllocFailed(size) }) => alloc_failed(size),
     }
 }
 
-impl ShlAssign<usize> for Int {
-    #[inline]
-    fn shl_assign(&mut self, mut cnt: usize) {
+impl Clone for Int {
+    fn clone(&self) -> Int {
         debug_assert!(self.well_formed());
-        if self.sign() == 0 { return; }
-
-        if cnt >= Limb::BITS as usize {
-            let extra_limbs = (cnt / Limb::BITS as usize) as u32;
-            debug_assert!(extra_limbs >= 1);
-            cnt = cnt % Limb::BITS as usize;
-
-            let size = self.abs_size() as u32;
-            // Extend for the extra limbs, then another one for any potential extra limbs
-            self.ensure_capacity(extra_limbs + size + 1);
-
-            unsafe {
-                let ptr = self.limbs_uninit();
-                let shift = ptr.offset(extra_limbs as isize);
-                ll::copy_decr(ptr.as_const(), shift, self.abs_size());
-                ll::zero(ptr, extra_limbs as i32);
-            }
 
-            self.size += (extra_limbs as i32) * self.sign();
+        if self.sign() == 0 {
+            return Int::zero();
         }
 
-        debug_assert!(cnt < Limb::BITS as usize);
-
-        if cnt == 0 { return; }
+        let mut new = Int::with_capacity(self.abs_size() as usize);
+        unsafe {
+            ll::copy_incr(self.limbs(), new.limbs_uninit(), self.abs_size());
+        }
+        new.size = self.size;
+        new
+    }
 
-        let size = self.abs_size();
+    fn clone_from(&mut self, other: &Int) {
+        debug_assert!(self.well_formed());
+        debug_assert!(other.well_formed());
 
+        if other.sign() == 0 {
+            self.size = 0;
+            return;
+        }
+        self.ensure_capacity(other.abs_size() as usize);
         unsafe {
-            let ptr = self.limbs_mut();
-            let c = ll::shl(ptr, ptr.as_const(), size, cnt as u32);
-            if c > 0 {
-                self.push(c);
-            }
+            ll::copy_incr(other.limbs(), self.limbs_uninit(), other.abs_size());
+            self.size = other.size;
         }
     }
 }
 
-impl<'a> Shl<usize> for &'a Int {
-    type Output = Int;
-
+impl std::default::Default for Int {
     #[inline]
-    fn shl(self, cnt: usize) -> Int {
-        let mut new = self.clone();
-        new <<= cnt;
-        new
+    fn default() -> Int {
+        Int::zero()
     }
 }
 
-impl Shl<usize> for Int {
-    type Output = Int;
+impl Drop for Int {
+    fn drop(&mut self) {
+        if !self.is_inline() {
+            unsafe {
+                ::mem::deallocate_bytes(self.data.heap as *mut u8,
+                                        self.cap * std::mem::size_of::<Limb>());
+            }
+            self.cap = INLINE_LIMBS;
+            self.size = 0;
+        }
+    }
+}
 
+impl PartialEq<Int> for Int {
     #[inline]
-    fn shl(mut self, other: usize) -> Int {
-        self <<= other;
-        self
+    fn eq(&self, other: &Int) -> bool {
+        debug_assert!(self.well_formed());
+        debug_assert!(other.well_formed());
+        if self.size == other.size {
+            unsafe {
+                ll::cmp(self.limbs(), other.limbs(), self.abs_size()) == Ordering::Equal
+            }
+        } else {
+            false
+        }
     }
 }
 
-impl ShrAssign<usize> for Int {
+impl PartialEq<Limb> for Int {
     #[inline]
-    fn shr_assign(&mut self, mut cnt: usize) {
-        debug_assert!(self.well_formed());
-        if self.sign() == 0 { return; }
+    fn eq(&self, other: &Limb) -> bool {
+        if *other == 0 && self.size == 0 {
+            return true;
+        }
 
-        if cnt >= Limb::BITS as usize {
-            let removed_limbs = (cnt / Limb::BITS as usize) as u32;
-            let size = self.abs_size();
-            if removed_limbs as i32 >= size {
-                *self = Int::zero();
-                return;
-            }
-            debug_assert!(removed_limbs > 0);
-            cnt = cnt % Limb::BITS as usize;
+        self.size == 1 && *self.limbs() == *other
+    }
+}
 
-            unsafe {
-                let ptr = self.limbs_mut();
-                let shift = ptr.offset(removed_limbs as isize);
-                let new_size = size - removed_limbs as i32;
+impl PartialEq<Int> for Limb {
+    #[inline]
+    fn eq(&self, other: &Int) -> bool {
+        other.eq(self)
+    }
+}
 
-                // Shift down a whole number of limbs
-                ll::copy_incr(shift.as_const(), ptr, new_size);
-                // Zero out the high limbs
-                ll::zero(ptr.offset(new_size as isize),
-                         removed_limbs as i32);
+impl Eq for Int { }
 
-                self.size = new_size * self.sign();
+impl Ord for Int {
+    #[inline]
+    fn cmp(&self, other: &Int) -> Ordering {
+        if self.size < other.size {
+            Ordering::Less
+        } else if self.size > other.size {
+            Ordering::Greater
+        } else { // Same number of digits and same sign
+            // Check for zero
+            if self.size == 0 {
+                return Ordering::Equal;
+            }
+            unsafe {
+                // If both are positive, do `self cmp other`, if both are
+                // negative, do `other cmp self`
+                if self.sign() == 1 {
+                    ll::cmp(self.limbs(), other.limbs(), self.abs_size())
+                } else {
+                    ll::cmp(other.limbs(), self.limbs(), self.abs_size())
+                }
             }
         }
+    }
+}
 
-        debug_assert!(cnt < Limb::BITS as usize);
-        if cnt == 0 { return; }
+impl PartialOrd<Int> for Int {
+    #[inline]
+    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-        let size = self.abs_size();
+impl PartialOrd<Limb> for Int {
+    #[inline]
+    fn partial_cmp(&self, other: &Limb) -> Option<Ordering> {
+        if self.eq(other) {
+            return Some(Ordering::Equal);
+        }
 
-        unsafe {
-            let ptr = self.limbs_mut();
-            ll::shr(ptr, ptr.as_const(), size, cnt as u32);
-            self.normalize();
+        if self.size < 1 {
+            Some(Ordering::Less)
+        } else if self.size > 1 {
+            Some(Ordering::Greater)
+        } else {
+            (*self.limbs()).partial_cmp(other)
         }
     }
 }
 
-impl<'a> Shr<usize> for &'a Int {
-    type Output = Int;
-
+impl PartialOrd<Int> for Limb {
     #[inline]
-    fn shr(self, other: usize) -> Int {
-        let mut new = self.clone();
-        new >>= other;
-        new
+    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
+        other.partial_cmp(self).map(|o| o.reverse())
     }
 }
 
-impl Shr<usize> for Int {
-    type Output = Int;
-
-    #[inline]
-    fn shr(mut self, other: usize) -> Int {
-        self >>= other;
-        self
+impl hash::Hash for Int {
+    fn hash<H>(&self, state: &mut H) where H: hash::Hasher {
+        debug_assert!(self.well_formed());
+        state.write(&[self.sign() as u8]);
+        self.abs_hash(state);
     }
 }
 
-#[derive(Copy, Clone)]
-enum BitOp { And, Or, Xor }
+/**
+ * A hasher with a fixed, documented output: 64-bit FNV-1a over the bytes written to it.
+ *
+ * The `Hash` implementations of `Int` and `Rational` only ever pass bytes to `write`, in a
+ * way that doesn't depend on the platform, so hashing them with this gives values that
+ * can be persisted and checked elsewhere. Other integer `write_*` methods use the native
+ * byte order, so values of other types hashed with it may not be portable.
+ *
+ * FNV-1a is not resistant to collision attacks, so prefer the standard hasher for hash
+ * tables holding untrusted keys.
+ */
+#[derive(Clone, Debug)]
+pub struct StableHasher(u64);
 
-fn bitop_ref(this: &mut Int, other: &Int, op: BitOp) -> Result<(), ()> {
-    let this_sign = this.sign();
-    let other_sign = other.sign();
+impl StableHasher {
+    pub fn new() -> StableHasher {
+        StableHasher(0xcbf29ce484222325)
+    }
+}
 
-    // if other is small, we can fall back to something that'll be
-    // more efficient (especially if other is negative)
-    if other.abs_size() <= 1 {
-        // the magnitude of the limb
-        let mut limb = other.to_single_limb();
-        if other_sign < 0 {
-            if limb.high_bit_set() {
-                // the limb is too large to be put into two's
-                // complement form (NB. that if other is positive, we
-                // don't need to worry about two's complement, since
-                // bitop_limb can handle unsigned Limbs)
-                return Err(())
-            } else {
-                limb = -limb;
-            }
-        }
+impl std::default::Default for StableHasher {
+    fn default() -> StableHasher {
+        StableHasher::new()
+    }
+}
 
-        // as mentioned above, we only have to say that `limb` is
-        // signed when it is actually negative
-        bitop_limb(this, limb, other_sign < 0, op);
-        return Ok(());
+impl hash::Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ b as u64).wrapping_mul(0x100000001b3);
+        }
     }
 
-    if this_sign < 0 || other_sign < 0 {
-        return Err(())
+    fn finish(&self) -> u64 {
+        self.0
     }
+}
 
-    unsafe {
-        let other_ptr = other.limbs();
-        let min_size = std::cmp::min(this.abs_size(), other.abs_size());
-        let max_size = std::cmp::max(this.abs_size(), other.abs_size());
-        match op {
-            BitOp::And => {
-                let this_ptr = this.limbs_mut();
-                ll::and_n(this_ptr, this_ptr.as_const(), other_ptr, min_size);
-                this.size = min_size;
+impl AddAssign<Limb> for Int {
+    fn add_assign(&mut self, other: Limb) {
+        debug_assert!(self.well_formed());
+        if other == 0 { return; }
+
+        // This is zero, so just store `other`
+        if self.size == 0 {
+            unsafe {
+                *self.limbs_uninit() = other;
+                self.size = 1;
+                return
             }
-            BitOp::Or => {
-                this.ensure_capacity(max_size as u32);
-                let this_ptr = this.limbs_uninit();
-                ll::or_n(this_ptr, this_ptr.as_const(), other_ptr, min_size);
-                if this.abs_size() < max_size {
-                    ll::copy_rest(other_ptr, this_ptr, max_size, min_size);
+        }
+        // `self` is non-zero, reuse the storage for the result.
+        unsafe {
+            let sign = self.size.signum();
+            let size = self.abs_size();
+            let mut ptr = self.limbs_mut();
+
+            // Self is positive, just add `other`
+            if sign == 1 {
+                let carry = ll::add_1(ptr, ptr.as_const(), size, other);
+                if carry != 0 {
+                    self.push(carry);
                 }
-                this.size = max_size;
-            }
-            BitOp::Xor => {
-                this.ensure_capacity(max_size as u32);
-                let this_ptr = this.limbs_uninit();
-                ll::xor_n(this_ptr, this_ptr.as_const(), other_ptr, min_size);
-                if this.abs_size() < max_size {
-                    ll::copy_rest(other_ptr, this_ptr, max_size, min_size);
+            } else {
+                // Self is negative, "subtract" other from self, basically doing:
+                // -(-self - other) == self + other
+                let borrow = ll::sub_1(ptr, ptr.as_const(), size, other);
+                if borrow != 0 {
+                    // There was a borrow, ignore it but flip the sign on self
+                    self.size = self.abs_size();
+                    *ptr = -*ptr;
                 }
-                this.size = max_size;
+                self.normalize();
             }
         }
     }
-    this.normalize();
-    Ok(())
 }
 
-// one of the inputs is negative. The answer is as if `Int` was stored
-// in two's complement (in infinite precision), which means converting
-// to that format, doing the operation, and then converting back out
-// of it, if necessary.
-fn bitop_neg(mut a: Int, mut b: Int, op: BitOp) -> Int {
-    debug_assert!(a.sign() < 0 || b.sign() < 0);
-    let a_sign = a.sign();
-    let b_sign = b.sign();
+impl Add<Limb> for Int {
+    type Output = Int;
 
-    if a_sign < 0 {
-        a.negate_twos_complement();
-    }
-    if b_sign < 0 {
-        b.negate_twos_complement();
+    #[inline]
+    fn add(mut self, other: Limb) -> Int {
+        self += other;
+        self
     }
-    let (mut a, b, a_sign, b_sign) = if a.abs_size() < b.abs_size() {
-        (b, a, b_sign, a_sign)
-    } else {
-        (a, b, a_sign, b_sign)
-    };
+}
 
-    unsafe {
-        let a_ptr = a.limbs_mut();
-        let b_ptr = b.limbs();
-        let min_size = b.abs_size();
-        let max_size = a.abs_size();
+impl<'a> AddAssign<&'a Int> for Int {
+    fn add_assign(&mut self, other: &'a Int) {
+        debug_assert!(self.well_formed());
+        debug_assert!(other.well_formed());
 
-        let (neg_result, use_max_size) = match op {
-            BitOp::And => {
-                ll::and_n(a_ptr, a_ptr.as_const(), b_ptr, min_size);
+        unsafe {
+            self.add_assign_limbs(other.limbs(), other.size);
+        }
+    }
+}
 
-                (a_sign < 0 && b_sign < 0,
-                 b_sign < 0)
-            }
-            BitOp::Or => {
-                ll::or_n(a_ptr, a_ptr.as_const(), b_ptr, min_size);
-                // (no need to copy trailing, a is longer than b)
-
-                (a_sign < 0 || b_sign < 0,
-                 b_sign >= 0)
-            }
-            BitOp::Xor => {
-                ll::xor_n(a_ptr, a_ptr.as_const(), b_ptr, min_size);
-                if b_sign < 0 {
-                    let ptr = a_ptr.offset(min_size as isize);
-                    ll::not(ptr, ptr.as_const(), max_size - min_size);
-                }
-                ((a_sign < 0) ^ (b_sign < 0),
-                 true)
-            }
-        };
-
-        a.size = if use_max_size {
-            max_size
-        } else {
-            min_size
-        };
-        if neg_result {
-            a.negate_twos_complement();
-        }
-    }
-    a.normalize();
-    return a;
-}
-
-// do a bit operation on `a` and `b`.
-//
-// If `signed` only indicates whether to interpret `b` as two's
-// complement or not (i.e. if it is true, then `1` is still `1`, and
-// `-1` is `!0`)
-fn bitop_limb(a: &mut Int, b: Limb, signed: bool, op: BitOp) {
-    let a_sign = a.sign();
-    let b_negative = signed && b.high_bit_set();
-    let b_sign = if b_negative { -1 } else if b == 0 { 0 } else { 1 };
-
-    if a_sign < 0 {
-        a.negate_twos_complement();
-    }
-    // b is already in two's complement if it is negative
-
-    if a_sign == 0 {
-        match op {
-            // 0 ^ x == 0 | x == x
-            BitOp::Or | BitOp::Xor => {
-                if b_sign < 0 {
-                    a.push(-b);
-                    a.negate();
-                } else {
-                    a.push(b)
-                }
-            }
-            // 0 & x == 0
-            BitOp::And => {}
-        }
-    } else {
-        unsafe {
-            let mut a_ptr = a.limbs_mut();
-            let min_size = if b == 0 { 0 } else { 1 };
-            let max_size = a.abs_size();
-            // we've got to have space to write data to this pointer
-            debug_assert!(max_size >= 1);
-
-            let (neg_result, use_max_size) = match op {
-                BitOp::And => {
-                    *a_ptr = *a_ptr & b;
-                    (a_sign < 0 && b_sign < 0,
-                     b_sign < 0)
-                }
-                BitOp::Or => {
-                    *a_ptr = *a_ptr | b;
-                    (a_sign < 0 || b_sign < 0,
-                     b_sign >= 0)
-                }
-                BitOp::Xor => {
-                    *a_ptr = *a_ptr ^ b;
-                    if b_sign < 0 {
-                        let ptr = a_ptr.offset(min_size as isize);
-                        ll::not(ptr, ptr.as_const(), max_size - min_size);
-                    }
-                    ((a_sign < 0) ^ (b_sign < 0),
-                     true)
-                }
-            };
-            a.size = if use_max_size {
-                max_size
-            } else {
-                min_size
-            };
-            if neg_result {
-                a.negate_twos_complement();
-            }
-        }
-    }
-    a.normalize();
-}
-
-impl<'a> BitAnd<Limb> for Int {
+impl<'a> Add<&'a Int> for Int {
     type Output = Int;
 
-    fn bitand(mut self, other: Limb) -> Int {
-        self &= other;
+    #[inline]
+    fn add(mut self, other: &'a Int) -> Int {
+        self += other;
         self
     }
 }
 
-impl BitAndAssign<Limb> for Int {
-    fn bitand_assign(&mut self, other: Limb) {
-        bitop_limb(self, other, false, BitOp::And)
-    }
-}
-
-impl<'a> BitAnd<&'a Int> for Int {
-    type Output = Int;
-
-    fn bitand(mut self, other: &'a Int) -> Int {
-        if let Ok(_) = bitop_ref(&mut self, other, BitOp::And) {
-            self
-        } else {
-            bitop_neg(self, other.clone(), BitOp::And)
-        }
-    }
-}
-
-impl<'a> BitAnd<Int> for &'a Int {
+impl Add<Int> for &Int {
     type Output = Int;
 
     #[inline]
-    fn bitand(self, other: Int) -> Int {
-        other.bitand(self)
+    fn add(self, other: Int) -> Int {
+        // Forward to other + self
+        other.add(self)
     }
 }
 
-impl<'a, 'b> BitAnd<&'a Int> for &'b Int {
+impl Add<Int> for Int {
     type Output = Int;
 
     #[inline]
-    fn bitand(self, other: &'a Int) -> Int {
-        self.clone().bitand(other)
-    }
-}
-
-impl BitAnd<Int> for Int {
-    type Output = Int;
+    fn add(self, other: Int) -> Int {
+        // Check for self or other being zero.
+        if self.sign() == 0 {
+            return other;
+        }
+        if other.sign() == 0 {
+            return self;
+        }
 
-    fn bitand(mut self, other: Int) -> Int {
-        if let Ok(_) = bitop_ref(&mut self, &other, BitOp::And) {
-            self
+        let (x, y) = if self.abs_size() >= other.abs_size() {
+            (self, &other)
         } else {
-            bitop_neg(self, other, BitOp::And)
-        }
-    }
-}
+            (other, &self)
+        };
 
-impl BitAndAssign<Int> for Int {
-    #[inline]
-    fn bitand_assign(&mut self, other: Int) {
-        if let Err(_) = bitop_ref(self, &other, BitOp::And) {
-            let res = &*self & other;
-            *self = res;
-        }
+        x.add(y)
     }
 }
-impl<'a> BitAndAssign<&'a Int> for Int {
+
+impl AddAssign<Int> for Int {
     #[inline]
-    fn bitand_assign(&mut self, other: &'a Int) {
-        if let Err(_) = bitop_ref(self, other, BitOp::And) {
-            let res = &*self & other;
-            *self = res;
+    fn add_assign(&mut self, mut other: Int) {
+        // Use the allocation of the larger of the two inputs.
+        // Doing the obvious and simply swapping self and other
+        // when other.size > self.size results in poor codegen.
+        if other.abs_size() > self.abs_size() {
+            // Instead we doing the addition in-place, then overwrite
+            // self with other. This results in better codegen and better
+            // memory allocation behaviour.
+            other += &*self;
+            *self = other;
+        } else {
+            *self += &other;
         }
     }
 }
 
-impl BitOr<Limb> for Int {
+impl<'a> Add<&'a Int> for &Int {
     type Output = Int;
 
-    fn bitor(mut self, other: Limb) -> Int {
-        self |= other;
-        self
-    }
-}
+    #[inline]
+    fn add(self, other: &'a Int) -> Int {
+        if self.sign() == 0 {
+            return other.clone();
+        }
+        if other.sign() == 0 {
+            return self.clone();
+        }
 
-impl BitOrAssign<Limb> for Int {
-    fn bitor_assign(&mut self, other: Limb) {
-        bitop_limb(self, other, false, BitOp::Or)
+        // Clone the bigger of the two
+        if self.abs_size() >= other.abs_size() {
+            self.clone().add(other)
+        } else {
+            other.clone().add(self)
+        }
     }
 }
 
-impl<'a> BitOr<&'a Int> for Int {
-    type Output = Int;
+impl SubAssign<Limb> for Int {
+    fn sub_assign(&mut self, other: Limb) {
+        debug_assert!(self.well_formed());
+        if other == 0 { return; }
 
-    fn bitor(mut self, other: &'a Int) -> Int {
-        if let Ok(_) = bitop_ref(&mut self, other, BitOp::Or) {
-            self
-        } else {
-            bitop_neg(self, other.clone(), BitOp::Or)
+        // This is zero, so just store `other`
+        if self.size == 0 {
+            unsafe {
+                *self.limbs_uninit() = other;
+                self.size = -1;
+            }
+            return;
         }
-    }
-}
+        // `self` is non-zero, reuse the storage for the result.
+        unsafe {
+            let sign = self.size.signum();
+            let size = self.abs_size();
+            let ptr = self.limbs_mut();
 
-impl<'a> BitOr<Int> for &'a Int {
-    type Output = Int;
+            // Self is negative, just "add" `other`
+            if sign == -1 {
+                let carry = ll::add_1(ptr, ptr.as_const(), size, other);
+                if carry != 0 {
+                    self.push(carry);
+                }
+            } else {
+                // Self is positive, subtract other from self
+                let carry = ll::sub_1(ptr, ptr.as_const(), size, other);
+                self.normalize();
+                if carry != 0 {
+                    // There was a carry, and the native operations
+                    // work with two's complement, so we need to get
+                    // everything back into sign-magnitude form
+                    self.negate_twos_complement()
+                }
+            }
+        }
 
-    #[inline]
-    fn bitor(self, other: Int) -> Int {
-        other.bitor(self)
+        debug_assert!(self.well_formed());
     }
 }
 
-impl<'a, 'b> BitOr<&'a Int> for &'b Int {
+impl Sub<Limb> for Int {
     type Output = Int;
 
     #[inline]
-    fn bitor(self, other: &'a Int) -> Int {
-        self.clone().bitor(other)
+    fn sub(mut self, other: Limb) -> Int {
+        self -= other;
+        self
     }
 }
 
-impl BitOr<Int> for Int {
-    type Output = Int;
+impl<'a> SubAssign<&'a Int> for Int {
+    fn sub_assign(&mut self, other: &'a Int) {
+        debug_assert!(self.well_formed());
+        debug_assert!(other.well_formed());
 
-    #[inline]
-    fn bitor(mut self, other: Int) -> Int {
-        if let Ok(_) = bitop_ref(&mut self, &other, BitOp::Or) {
-            self
-        } else {
-            bitop_neg(self, other, BitOp::Or)
-        }
-    }
-}
-
-impl BitOrAssign<Int> for Int {
-    #[inline]
-    fn bitor_assign(&mut self, other: Int) {
-        if let Err(_) = bitop_ref(self, &other, BitOp::Or) {
-            let res = &*self | other;
-            *self = res;
+        // LHS is zero, set self to the negation of the RHS
+        if self.sign() == 0 {
+            self.clone_from(other);
+            self.size *= -1;
+            return;
         }
-    }
-}
-impl<'a> BitOrAssign<&'a Int> for Int {
-    #[inline]
-    fn bitor_assign(&mut self, other: &'a Int) {
-        if let Err(_) = bitop_ref(self, &other, BitOp::Or) {
-            let res = &*self | other;
-            *self = res;
+        // RHS is zero, do nothing
+        if other.sign() == 0 {
+            return;
         }
-    }
-}
 
-impl<'a> BitXor<Limb> for Int {
-    type Output = Int;
+        if self.sign() == other.sign() {
+            unsafe {
+                // Signs are the same, subtract the smaller one from
+                // the bigger one and adjust the sign as appropriate
+                let (xp, xs, yp, ys, flip) = match self.abs_cmp(other) {
+                    Ordering::Equal => {
+                        // x - x, just return zero
+                        self.size = 0;
+                        return;
+                    }
+                    Ordering::Less => {
+                        self.ensure_capacity(other.abs_size() as usize);
+                        (other.limbs(), other.size, self.limbs(), self.size, true)
+                    }
+                    Ordering::Greater =>
+                        (self.limbs(), self.size, other.limbs(), other.size, false)
+                };
 
-    fn bitxor(mut self, other: Limb) -> Int {
-        self ^= other;
-        self
-    }
-}
+                // Fetch the pointer first to make completely sure the compiler
+                // won't make bogus claims about nonaliasing due to the &mut
+                let ptr = self.limbs_uninit();
 
-impl BitXorAssign<Limb> for Int {
-    fn bitxor_assign(&mut self, other: Limb) {
-        bitop_limb(self, other, false, BitOp::Xor)
-    }
-}
+                let _borrow = ll::sub(ptr, xp, xs.abs(), yp, ys.abs());
+                debug_assert!(_borrow == 0);
+                self.size = if flip {
+                    -xs
+                } else {
+                    xs
+                };
+            }
 
-impl<'a> BitXor<&'a Int> for Int {
-    type Output = Int;
+            self.normalize();
+        } else { // Different signs
+            if self.sign() == -1 {
+                // self is negative, use addition and negation
+                self.size *= -1;
+                *self += other;
+                self.size *= -1;
+            } else {
+                unsafe {
+                    // Other is negative, handle as addition
+                    let (xp, xs, yp, ys) = if self.abs_size() >= other.abs_size() {
+                        (self.limbs(), self.abs_size(), other.limbs(), other.abs_size())
+                    } else {
+                        self.ensure_capacity(other.abs_size() as usize);
+                        (other.limbs(), other.abs_size(), self.limbs(), self.abs_size())
+                    };
 
-    fn bitxor(mut self, other: &'a Int) -> Int {
-        if let Ok(_) = bitop_ref(&mut self, other, BitOp::Xor) {
-            self
-        } else {
-            bitop_neg(self, other.clone(), BitOp::Xor)
+                    // Fetch the pointer first to make completely sure the compiler
+                    // won't make bogus claims about nonaliasing due to the &mut
+                    let ptr = self.limbs_uninit();
+
+                    let carry = ll::add(ptr, xp, xs, yp, ys);
+                    self.size = xs;
+                    if carry != 0 {
+                        self.push(carry);
+                    }
+                    self.normalize();
+                }
+            }
         }
     }
 }
 
-impl<'a> BitXor<Int> for &'a Int {
+impl<'a> Sub<&'a Int> for Int {
     type Output = Int;
 
     #[inline]
-    fn bitxor(self, other: Int) -> Int {
-        other.bitxor(self)
+    fn sub(mut self, other: &'a Int) -> Int {
+        self -= other;
+        self
     }
 }
 
-impl<'a, 'b> BitXor<&'a Int> for &'b Int {
+impl Sub<Int> for &Int {
     type Output = Int;
 
     #[inline]
-    fn bitxor(self, other: &'a Int) -> Int {
-        self.clone().bitxor(other)
+    fn sub(self, mut other: Int) -> Int {
+        if self.sign() == 0 {
+            return -other;
+        }
+        if other.sign() == 0 {
+            other.clone_from(self);
+            return other;
+        }
+
+        -(other.sub(self))
     }
 }
 
-impl BitXor<Int> for Int {
+impl Sub<Int> for Int {
     type Output = Int;
 
     #[inline]
-    fn bitxor(mut self, other: Int) -> Int {
-        if let Ok(_) = bitop_ref(&mut self, &other, BitOp::Xor) {
-            self
-        } else {
-            bitop_neg(self, other, BitOp::Xor)
+    fn sub(self, other: Int) -> Int {
+        if self.sign() == 0 {
+            return -other;
+        }
+        if other.sign() == 0 {
+            return self;
         }
+
+        self.sub(&other)
     }
 }
 
-impl BitXorAssign<Int> for Int {
+impl SubAssign<Int> for Int {
     #[inline]
-    fn bitxor_assign(&mut self, other: Int) {
-        if let Err(_) = bitop_ref(self, &other, BitOp::Xor) {
-            let res = &*self ^ other;
-            *self = res;
+    fn sub_assign(&mut self, other: Int) {
+        if other.sign() == 0 {
+            return;
+        }
+        if self.sign() == 0 {
+            *self = -other;
+            return;
         }
+        *self -= &other;
     }
 }
-impl<'a> BitXorAssign<&'a Int> for Int {
+
+impl<'a> Sub<&'a Int> for &Int {
+    type Output = Int;
+
     #[inline]
-    fn bitxor_assign(&mut self, other: &'a Int) {
-        if let Err(_) = bitop_ref(self, &other, BitOp::Xor) {
-            let res = &*self ^ other;
-            *self = res;
+    fn sub(self, other: &'a Int) -> Int {
+        if self.sign() == 0 {
+            return -other;
+        }
+        if other.sign() == 0 {
+            return self.clone();
         }
+
+        self.clone().sub(other)
     }
 }
 
-macro_rules! impl_arith_prim (
-    (signed $t:ty) => (
-        // Limbs are unsigned, so make sure we account for the sign
-        // when $t is signed
-        impl Add<$t> for Int {
-            type Output = Int;
-
-            #[inline]
-            fn add(self, other: $t) -> Int {
-                if other == 0 {
-                    return self;
-                }
-                if other < 0 {
-                    return self - Limb(other.abs() as BaseInt);
-                }
-                return self + Limb(other as BaseInt);
-            }
+impl MulAssign<Limb> for Int {
+    fn mul_assign(&mut self, other: Limb) {
+        debug_assert!(self.well_formed());
+        if other == 0 || self.sign() == 0 {
+            self.size = 0;
+            return;
         }
 
-        impl AddAssign<$t> for Int {
-            #[inline]
-            fn add_assign(&mut self, other: $t) {
-                if other < 0 {
-                    *self -= Limb(other.abs() as BaseInt);
-                } else if other > 0 {
-                    *self += Limb(other as BaseInt);
-                }
-            }
+        if other == 1 {
+            return;
         }
 
-        impl Sub<$t> for Int {
-            type Output = Int;
+        unsafe {
+            // Fetch the pointer first to make completely sure the compiler
+            // won't make bogus claims about nonaliasing due to the &mut
 
-            #[inline]
-            fn sub(self, other: $t) -> Int {
-                if other == 0 {
-                    return self;
-                }
-                if other < 0 {
-                    return self + Limb(other.abs() as BaseInt);
-                }
-                return self - Limb(other as BaseInt);
+            let carry = ll::mul_1(self.limbs_mut(), self.limbs(), self.abs_size(), other);
+            if carry != 0 {
+                self.push(carry);
             }
         }
+    }
+}
 
-        impl SubAssign<$t> for Int {
-            #[inline]
-            fn sub_assign(&mut self, other: $t) {
-                if other < 0 {
-                    *self += Limb(other.abs() as BaseInt);
-                } else if other > 0 {
-                    *self -= Limb(other as BaseInt);
-                }
-            }
-        }
+impl Mul<Limb> for Int {
+    type Output = Int;
 
-        impl Mul<$t> for Int {
-            type Output = Int;
+    #[inline]
+    fn mul(mut self, other: Limb) -> Int {
+        self *= other;
+        self
+    }
+}
 
-            #[inline]
-            fn mul(mut self, other: $t) -> Int {
-                self *= other;
-                self
-            }
-        }
+impl<'a> Mul<&'a Int> for &Int {
+    type Output = Int;
 
-        impl MulAssign<$t> for Int {
-            #[inline]
-            fn mul_assign(&mut self, other: $t) {
-                if other == 0 {
-                    self.size = 0;
-                } else if other == -1 {
-                    self.negate();
-                } else if other < 0 {
-                    self.negate();
-                    *self *= Limb(other.abs() as BaseInt);
-                } else {
-                    *self *= Limb(other as BaseInt);
-                }
-            }
-        }
+    fn mul(self, other: &'a Int) -> Int {
+        debug_assert!(self.well_formed());
+        debug_assert!(other.well_formed());
+        // This is the main function, since in the general case
+        // we need to allocate space for the return. Special cases
+        // where this isn't necessary are handled in the other impls
+
+        // 0 * x = 0
+        if self.sign() == 0 || other.sign() == 0 {
+            return Int::zero();
+        }
+
+        let out_sign = self.size.signum() * other.size.signum();
+
+        if self.abs_size() == 1 {
+            let mut ret = other.clone() * *self.limbs();
+            let size = ret.abs_size();
+            ret.size = size * out_sign;
+            return ret;
+        }
+        if other.abs_size() == 1 {
+            let mut ret = self.clone() * *other.limbs();
+            let size = ret.abs_size();
+            ret.size = size * out_sign;
+            return ret;
+        }
+
+        let out_size = self.abs_size() + other.abs_size();
+
+        let mut out = Int::with_capacity(out_size as usize);
+        out.size = out_size * out_sign;
+
+        unsafe {
+            let (xp, xs, yp, ys) = if self.abs_size() >= other.abs_size() {
+                (self.limbs(), self.abs_size(), other.limbs(), other.abs_size())
+            } else {
+                (other.limbs(), other.abs_size(), self.limbs(), self.abs_size())
+            };
+            ll::mul(out.limbs_mut(), xp, xs, yp, ys);
+
+            // Top limb may be zero
+            out.normalize();
+            out
+        }
+    }
+}
+
+impl<'a> Mul<&'a Int> for Int {
+    type Output = Int;
+
+    #[inline]
+    fn mul(mut self, other: &'a Int) -> Int {
+        // `other` is zero
+        if other.sign() == 0 {
+            self.size = 0;
+            return self;
+        }
+
+        // `other` is a single limb, reuse the allocation of self
+        if other.abs_size() == 1 {
+            let mut out = self * *other.limbs();
+            out.size *= other.size.signum();
+            return out;
+        }
+
+        // Forward to the by-reference impl
+        (&self) * other
+    }
+}
+
+impl Mul<Int> for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn mul(self, other: Int) -> Int {
+        // Swap arguments
+        other * self
+    }
+}
+
+impl Mul<Int> for Int {
+    type Output = Int;
+
+    fn mul(mut self, other: Int) -> Int {
+        if self.sign() == 0 || other.sign() == 0 {
+            self.size = 0;
+            return self;
+        }
+
+        // One of them is a single limb big, so we can re-use the
+        // allocation of the other
+        if self.abs_size() == 1 {
+            let val = *self.limbs();
+            let mut out = other * val;
+            out.size *= self.size.signum();
+            return out;
+        }
+        if other.abs_size() == 1 {
+            let val = *other.limbs();
+            let mut out = self * val;
+            out.size *= other.size.signum();
+            return out;
+        }
+
+        // Still need to allocate for the result, just forward to
+        // the by-reference impl
+        (&self) * (&other)
+    }
+}
+
+impl<'a> MulAssign<&'a Int> for Int {
+    #[inline]
+    fn mul_assign(&mut self, other: &'a Int) {
+        if self.sign() == 0 {
+            return;
+        }
+        if other.sign() == 0 {
+            self.size = 0;
+            return;
+        }
+        let res = &*self * other;
+        *self = res;
+    }
+}
+
+impl MulAssign<Int> for Int {
+    #[inline]
+    fn mul_assign(&mut self, other: Int) {
+        if self.sign() == 0 {
+            return;
+        }
+        if other.sign() == 0 {
+            self.size = 0;
+            return;
+        }
+        let res = &*self * other;
+        *self = res;
+    }
+}
+
+impl DivAssign<Limb> for Int {
+    fn div_assign(&mut self, other: Limb) {
+        debug_assert!(self.well_formed());
+        if other == 0 {
+            ll::divide_by_zero();
+        }
+        if other == 1 || self.sign() == 0 {
+            return;
+        }
+
+        unsafe {
+            // Ignore the remainder
+            ll::divrem_1(self.limbs_mut(), 0, self.limbs(), self.abs_size(), other);
+            // Adjust the size if necessary
+            self.normalize();
+        }
+    }
+}
+
+impl Div<Limb> for Int {
+    type Output = Int;
+
+    #[inline]
+    fn div(mut self, other: Limb) -> Int {
+        self /= other;
+        self
+    }
+}
+
+impl<'a> Div<&'a Int> for &Int {
+    type Output = Int;
+
+    fn div(self, other: &'a Int) -> Int {
+        debug_assert!(self.well_formed());
+        debug_assert!(other.well_formed());
+        if other.sign() == 0 {
+            ll::divide_by_zero();
+        }
+        if other.abs_size() == 1 {
+            let l = *other.limbs();
+            let out_sign = self.size.signum() * other.size.signum();
+            let mut out = self.clone() / l;
+            out.size = out.abs_size() * out_sign;
+            return out;
+        }
+
+        self.divmod(other).0
+    }
+}
+
+impl<'a> Div<&'a Int> for Int {
+    type Output = Int;
+
+    #[inline]
+    fn div(self, other: &'a Int) -> Int {
+        (&self) / other
+    }
+}
+
+impl Div<Int> for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn div(self, other: Int) -> Int {
+        self / (&other)
+    }
+}
+
+impl Div<Int> for Int {
+    type Output = Int;
+
+    #[inline]
+    fn div(self, other: Int) -> Int {
+        (&self) / (&other)
+    }
+}
+
+impl<'a> DivAssign<&'a Int> for Int {
+    #[inline]
+    fn div_assign(&mut self, other: &'a Int) {
+        let res = &*self / other;
+        *self = res;
+    }
+}
+
+impl DivAssign<Int> for Int {
+    #[inline]
+    fn div_assign(&mut self, other: Int) {
+        let res = &*self / other;
+        *self = res;
+    }
+}
+
+impl Rem<Limb> for Int {
+    type Output = Int;
+
+    #[inline]
+    fn rem(mut self, other: Limb) -> Int {
+        self %= other;
+        self
+    }
+}
+
+impl RemAssign<Limb> for Int {
+    fn rem_assign(&mut self, other: Limb) {
+        debug_assert!(self.well_formed());
+        if other == 0 {
+            ll::divide_by_zero();
+        }
+        // x % 1 == 0, 0 % n == 0
+        if other == 1 || self.sign() == 0 {
+            self.size = 0;
+            return;
+        }
+
+        unsafe {
+            let rem = ll::divrem_1(self.limbs_mut(), 0, self.limbs(), self.abs_size(), other);
+            // Reuse the space from `self`, taking the sign from the numerator
+            // Since `rem` has to satisfy `N = QD + R` and D is always positive,
+            // `R` will always be the same sign as the numerator.
+            *self.limbs_mut() = rem;
+            let sign = self.size.signum();
+            self.size = sign;
+
+            self.normalize();
+
+            if self.cap > 8 {
+                // Shrink self, since it's at least 8 times bigger than necessary
+                self.shrink_to_fit();
+            }
+
+        }
+    }
+}
+
+impl DivRem<Limb> for Int {
+    type Output = (Int, Limb);
+
+    fn divrem(mut self, other: Limb) -> Self::Output {
+        debug_assert!(self.well_formed());
+        if other == 0 {
+            ll::divide_by_zero();
+        }
+        // x % 1 == 0, 0 % n == 0
+        if other == 1 || self.sign() == 0 {
+            self.size = 0;
+            return (self, Limb(0));
+        }
+
+        let rem = unsafe { ll::divrem_1(self.limbs_mut(), 0, self.limbs(), self.abs_size(), other) };
+        self.normalize();
+        (self, rem)
+    }
+}
+
+// TODO: There's probably too much cloning happening here, need to figure out
+// the best way of avoiding over-copying.
+
+impl<'a> Rem<&'a Int> for &Int {
+    type Output = Int;
+
+    fn rem(self, other: &'a Int) -> Int {
+        debug_assert!(self.well_formed());
+        debug_assert!(other.well_formed());
+        if other.sign() == 0 {
+            ll::divide_by_zero();
+        }
+        if other.abs_size() == 1 {
+            let l = *other.limbs();
+            return self.clone() % l;
+        }
+
+        self.divmod(other).1
+    }
+}
+
+impl<'a> Rem<&'a Int> for Int {
+    type Output = Int;
+
+    #[inline]
+    fn rem(self, other: &'a Int) -> Int {
+        (&self) % other
+    }
+}
+
+impl Rem<Int> for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn rem(self, other: Int) -> Int {
+        self % (&other)
+    }
+}
+
+impl Rem<Int> for Int {
+    type Output = Int;
+
+    #[inline]
+    fn rem(self, other: Int) -> Int {
+        (&self) % (&other)
+    }
+}
+
+impl<'a> DivRem<&'a Int> for &Int {
+    type Output = (Int, Int);
+
+    #[inline]
+    fn divrem(self, other: &'a Int) -> (Int, Int) {
+        self.divmod(other)
+    }
+}
+
+impl RemAssign<Int> for Int {
+    #[inline]
+    fn rem_assign(&mut self, other: Int) {
+        let res = &*self % other;
+        *self = res;
+    }
+}
+impl<'a> RemAssign<&'a Int> for Int {
+    #[inline]
+    fn rem_assign(&mut self, other: &'a Int) {
+        let res = &*self % other;
+        *self = res;
+    }
+}
+
+impl Neg for Int {
+    type Output = Int;
+
+    #[inline]
+    fn neg(mut self) -> Int {
+        debug_assert!(self.well_formed());
+        self.size *= -1;
+        self
+    }
+}
+
+impl Neg for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn neg(self) -> Int {
+        self.clone().neg()
+    }
+}
+
+impl ShlAssign<usize> for Int {
+    #[inline]
+    fn shl_assign(&mut self, mut cnt: usize) {
+        debug_assert!(self.well_formed());
+        if self.sign() == 0 { return; }
+
+        if cnt >= Limb::BITS {
+            let extra_limbs = cnt / Limb::BITS;
+            debug_assert!(extra_limbs >= 1);
+            cnt %= Limb::BITS;
+
+            let size = self.abs_size() as usize;
+            // Extend for the extra limbs, then another one for any potential extra limbs
+            match extra_limbs.checked_add(size + 1) {
+                Some(cap) => self.ensure_capacity(cap),
+                None => capacity_overflow(),
+            }
+
+            unsafe {
+                let ptr = self.limbs_uninit();
+                let shift = ptr.offset(extra_limbs as isize);
+                ll::copy_decr(ptr.as_const(), shift, self.abs_size());
+                ll::zero(ptr, extra_limbs as isize);
+            }
+
+            self.size += (extra_limbs as isize) * self.size.signum();
+        }
+
+        debug_assert!(cnt < Limb::BITS);
+
+        if cnt == 0 { return; }
+
+        let size = self.abs_size();
+
+        unsafe {
+            let ptr = self.limbs_mut();
+            let c = ll::shl(ptr, ptr.as_const(), size, cnt as u32);
+            if c > 0 {
+                self.push(c);
+            }
+        }
+    }
+}
+
+impl Shl<usize> for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn shl(self, cnt: usize) -> Int {
+        let mut new = self.clone();
+        new <<= cnt;
+        new
+    }
+}
+
+impl Shl<usize> for Int {
+    type Output = Int;
+
+    #[inline]
+    fn shl(mut self, other: usize) -> Int {
+        self <<= other;
+        self
+    }
+}
+
+impl ShrAssign<usize> for Int {
+    #[inline]
+    fn shr_assign(&mut self, mut cnt: usize) {
+        debug_assert!(self.well_formed());
+        if self.sign() == 0 { return; }
+
+        if cnt >= Limb::BITS {
+            let removed_limbs = cnt / Limb::BITS;
+            let size = self.abs_size();
+            if removed_limbs >= size as usize {
+                *self = Int::zero();
+                return;
+            }
+            debug_assert!(removed_limbs > 0);
+            cnt %= Limb::BITS;
+
+            unsafe {
+                let ptr = self.limbs_mut();
+                let shift = ptr.offset(removed_limbs as isize);
+                let new_size = size - removed_limbs as isize;
+
+                // Shift down a whole number of limbs
+                ll::copy_incr(shift.as_const(), ptr, new_size);
+                // Zero out the high limbs
+                ll::zero(ptr.offset(new_size),
+                         removed_limbs as isize);
+
+                self.size = new_size * self.size.signum();
+            }
+        }
+
+        debug_assert!(cnt < Limb::BITS);
+        if cnt == 0 { return; }
+
+        let size = self.abs_size();
+
+        unsafe {
+            let ptr = self.limbs_mut();
+            ll::shr(ptr, ptr.as_const(), size, cnt as u32);
+            self.normalize();
+        }
+    }
+}
+
+impl Shr<usize> for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn shr(self, other: usize) -> Int {
+        let mut new = self.clone();
+        new >>= other;
+        new
+    }
+}
+
+impl Shr<usize> for Int {
+    type Output = Int;
+
+    #[inline]
+    fn shr(mut self, other: usize) -> Int {
+        self >>= other;
+        self
+    }
+}
+
+#[derive(Copy, Clone)]
+enum BitOp { And, Or, Xor }
+
+fn bitop_ref(this: &mut Int, other: &Int, op: BitOp) -> Result<(), ()> {
+    let this_sign = this.sign();
+    let other_sign = other.sign();
+
+    // if other is small, we can fall back to something that'll be
+    // more efficient (especially if other is negative)
+    if other.abs_size() <= 1 {
+        // the magnitude of the limb
+        let mut limb = other.to_single_limb();
+        if other_sign < 0 {
+            if limb.high_bit_set() {
+                // the limb is too large to be put into two's
+                // complement form (NB. that if other is positive, we
+                // don't need to worry about two's complement, since
+                // bitop_limb can handle unsigned Limbs)
+                return Err(())
+            } else {
+                limb = -limb;
+            }
+        }
+
+        // as mentioned above, we only have to say that `limb` is
+        // signed when it is actually negative
+        bitop_limb(this, limb, other_sign < 0, op);
+        return Ok(());
+    }
+
+    if this_sign < 0 || other_sign < 0 {
+        return Err(())
+    }
+
+    unsafe {
+        let other_ptr = other.limbs();
+        let min_size = std::cmp::min(this.abs_size(), other.abs_size());
+        let max_size = std::cmp::max(this.abs_size(), other.abs_size());
+        match op {
+            BitOp::And => {
+                let this_ptr = this.limbs_mut();
+                ll::and_n(this_ptr, this_ptr.as_const(), other_ptr, min_size);
+                this.size = min_size;
+            }
+            BitOp::Or => {
+                this.ensure_capacity(max_size as usize);
+                let this_ptr = this.limbs_uninit();
+                ll::or_n(this_ptr, this_ptr.as_const(), other_ptr, min_size);
+                if this.abs_size() < max_size {
+                    ll::copy_rest(other_ptr, this_ptr, max_size, min_size);
+                }
+                this.size = max_size;
+            }
+            BitOp::Xor => {
+                this.ensure_capacity(max_size as usize);
+                let this_ptr = this.limbs_uninit();
+                ll::xor_n(this_ptr, this_ptr.as_const(), other_ptr, min_size);
+                if this.abs_size() < max_size {
+                    ll::copy_rest(other_ptr, this_ptr, max_size, min_size);
+                }
+                this.size = max_size;
+            }
+        }
+    }
+    this.normalize();
+    Ok(())
+}
+
+// one of the inputs is negative. The answer is as if `Int` was stored
+// in two's complement (in infinite precision), which means converting
+// to that format, doing the operation, and then converting back out
+// of it, if necessary.
+fn bitop_neg(mut a: Int, mut b: Int, op: BitOp) -> Int {
+    debug_assert!(a.sign() < 0 || b.sign() < 0);
+    let a_sign = a.sign();
+    let b_sign = b.sign();
+
+    if a_sign < 0 {
+        a.negate_twos_complement();
+    }
+    if b_sign < 0 {
+        b.negate_twos_complement();
+    }
+    let (mut a, b, a_sign, b_sign) = if a.abs_size() < b.abs_size() {
+        (b, a, b_sign, a_sign)
+    } else {
+        (a, b, a_sign, b_sign)
+    };
+
+    unsafe {
+        let a_ptr = a.limbs_mut();
+        let b_ptr = b.limbs();
+        let min_size = b.abs_size();
+        let max_size = a.abs_size();
+
+        let (neg_result, use_max_size) = match op {
+            BitOp::And => {
+                ll::and_n(a_ptr, a_ptr.as_const(), b_ptr, min_size);
+
+                (a_sign < 0 && b_sign < 0,
+                 b_sign < 0)
+            }
+            BitOp::Or => {
+                ll::or_n(a_ptr, a_ptr.as_const(), b_ptr, min_size);
+                // (no need to copy trailing, a is longer than b)
+
+                (a_sign < 0 || b_sign < 0,
+                 b_sign >= 0)
+            }
+            BitOp::Xor => {
+                ll::xor_n(a_ptr, a_ptr.as_const(), b_ptr, min_size);
+                if b_sign < 0 {
+                    let ptr = a_ptr.offset(min_size);
+                    ll::not(ptr, ptr.as_const(), max_size - min_size);
+                }
+                ((a_sign < 0) ^ (b_sign < 0),
+                 true)
+            }
+        };
+
+        a.size = if use_max_size {
+            max_size
+        } else {
+            min_size
+        };
+        if neg_result {
+            a.negate_twos_complement();
+        }
+    }
+    a.normalize();
+    a
+}
+
+// do a bit operation on `a` and `b`.
+//
+// If `signed` only indicates whether to interpret `b` as two's
+// complement or not (i.e. if it is true, then `1` is still `1`, and
+// `-1` is `!0`)
+fn bitop_limb(a: &mut Int, b: Limb, signed: bool, op: BitOp) {
+    let a_sign = a.sign();
+    let b_negative = signed && b.high_bit_set();
+    let b_sign = if b_negative { -1 } else if b == 0 { 0 } else { 1 };
+
+    if a_sign < 0 {
+        a.negate_twos_complement();
+    }
+    // b is already in two's complement if it is negative
+
+    if a_sign == 0 {
+        match op {
+            // 0 ^ x == 0 | x == x
+            BitOp::Or | BitOp::Xor => {
+                if b_sign < 0 {
+                    a.push(-b);
+                    a.negate();
+                } else {
+                    a.push(b)
+                }
+            }
+            // 0 & x == 0
+            BitOp::And => {}
+        }
+    } else {
+        unsafe {
+            let mut a_ptr = a.limbs_mut();
+            let min_size = if b == 0 { 0 } else { 1 };
+            let max_size = a.abs_size();
+            // we've got to have space to write data to this pointer
+            debug_assert!(max_size >= 1);
+
+            let (neg_result, use_max_size) = match op {
+                BitOp::And => {
+                    *a_ptr = *a_ptr & b;
+                    (a_sign < 0 && b_sign < 0,
+                     b_sign < 0)
+                }
+                BitOp::Or => {
+                    *a_ptr = *a_ptr | b;
+                    (a_sign < 0 || b_sign < 0,
+                     b_sign >= 0)
+                }
+                BitOp::Xor => {
+                    *a_ptr = *a_ptr ^ b;
+                    if b_sign < 0 {
+                        let ptr = a_ptr.offset(min_size);
+                        ll::not(ptr, ptr.as_const(), max_size - min_size);
+                    }
+                    ((a_sign < 0) ^ (b_sign < 0),
+                     true)
+                }
+            };
+            a.size = if use_max_size {
+                max_size
+            } else {
+                min_size
+            };
+            if neg_result {
+                a.negate_twos_complement();
+            }
+        }
+    }
+    a.normalize();
+}
+
+impl BitAnd<Limb> for Int {
+    type Output = Int;
+
+    fn bitand(mut self, other: Limb) -> Int {
+        self &= other;
+        self
+    }
+}
+
+impl BitAndAssign<Limb> for Int {
+    fn bitand_assign(&mut self, other: Limb) {
+        bitop_limb(self, other, false, BitOp::And)
+    }
+}
+
+impl<'a> BitAnd<&'a Int> for Int {
+    type Output = Int;
+
+    fn bitand(mut self, other: &'a Int) -> Int {
+        if bitop_ref(&mut self, other, BitOp::And).is_ok() {
+            self
+        } else {
+            bitop_neg(self, other.clone(), BitOp::And)
+        }
+    }
+}
+
+impl BitAnd<Int> for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn bitand(self, other: Int) -> Int {
+        other.bitand(self)
+    }
+}
+
+impl<'a> BitAnd<&'a Int> for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn bitand(self, other: &'a Int) -> Int {
+        self.clone().bitand(other)
+    }
+}
+
+impl BitAnd<Int> for Int {
+    type Output = Int;
+
+    fn bitand(mut self, other: Int) -> Int {
+        if bitop_ref(&mut self, &other, BitOp::And).is_ok() {
+            self
+        } else {
+            bitop_neg(self, other, BitOp::And)
+        }
+    }
+}
+
+impl BitAndAssign<Int> for Int {
+    #[inline]
+    fn bitand_assign(&mut self, other: Int) {
+        if bitop_ref(self, &other, BitOp::And).is_err() {
+            let res = &*self & other;
+            *self = res;
+        }
+    }
+}
+impl<'a> BitAndAssign<&'a Int> for Int {
+    #[inline]
+    fn bitand_assign(&mut self, other: &'a Int) {
+        if bitop_ref(self, other, BitOp::And).is_err() {
+            let res = &*self & other;
+            *self = res;
+        }
+    }
+}
+
+impl BitOr<Limb> for Int {
+    type Output = Int;
+
+    fn bitor(mut self, other: Limb) -> Int {
+        self |= other;
+        self
+    }
+}
+
+impl BitOrAssign<Limb> for Int {
+    fn bitor_assign(&mut self, other: Limb) {
+        bitop_limb(self, other, false, BitOp::Or)
+    }
+}
+
+impl<'a> BitOr<&'a Int> for Int {
+    type Output = Int;
+
+    fn bitor(mut self, other: &'a Int) -> Int {
+        if bitop_ref(&mut self, other, BitOp::Or).is_ok() {
+            self
+        } else {
+            bitop_neg(self, other.clone(), BitOp::Or)
+        }
+    }
+}
+
+impl BitOr<Int> for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn bitor(self, other: Int) -> Int {
+        other.bitor(self)
+    }
+}
+
+impl<'a> BitOr<&'a Int> for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn bitor(self, other: &'a Int) -> Int {
+        self.clone().bitor(other)
+    }
+}
+
+impl BitOr<Int> for Int {
+    type Output = Int;
+
+    #[inline]
+    fn bitor(mut self, other: Int) -> Int {
+        if bitop_ref(&mut self, &other, BitOp::Or).is_ok() {
+            self
+        } else {
+            bitop_neg(self, other, BitOp::Or)
+        }
+    }
+}
+
+impl BitOrAssign<Int> for Int {
+    #[inline]
+    fn bitor_assign(&mut self, other: Int) {
+        if bitop_ref(self, &other, BitOp::Or).is_err() {
+            let res = &*self | other;
+            *self = res;
+        }
+    }
+}
+impl<'a> BitOrAssign<&'a Int> for Int {
+    #[inline]
+    fn bitor_assign(&mut self, other: &'a Int) {
+        if bitop_ref(self, other, BitOp::Or).is_err() {
+            let res = &*self | other;
+            *self = res;
+        }
+    }
+}
+
+impl BitXor<Limb> for Int {
+    type Output = Int;
+
+    fn bitxor(mut self, other: Limb) -> Int {
+        self ^= other;
+        self
+    }
+}
+
+impl BitXorAssign<Limb> for Int {
+    fn bitxor_assign(&mut self, other: Limb) {
+        bitop_limb(self, other, false, BitOp::Xor)
+    }
+}
+
+impl<'a> BitXor<&'a Int> for Int {
+    type Output = Int;
+
+    fn bitxor(mut self, other: &'a Int) -> Int {
+        if bitop_ref(&mut self, other, BitOp::Xor).is_ok() {
+            self
+        } else {
+            bitop_neg(self, other.clone(), BitOp::Xor)
+        }
+    }
+}
+
+impl BitXor<Int> for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn bitxor(self, other: Int) -> Int {
+        other.bitxor(self)
+    }
+}
+
+impl<'a> BitXor<&'a Int> for &Int {
+    type Output = Int;
+
+    #[inline]
+    fn bitxor(self, other: &'a Int) -> Int {
+        self.clone().bitxor(other)
+    }
+}
+
+impl BitXor<Int> for Int {
+    type Output = Int;
+
+    #[inline]
+    fn bitxor(mut self, other: Int) -> Int {
+        if bitop_ref(&mut self, &other, BitOp::Xor).is_ok() {
+            self
+        } else {
+            bitop_neg(self, other, BitOp::Xor)
+        }
+    }
+}
+
+impl BitXorAssign<Int> for Int {
+    #[inline]
+    fn bitxor_assign(&mut self, other: Int) {
+        if bitop_ref(self, &other, BitOp::Xor).is_err() {
+            let res = &*self ^ other;
+            *self = res;
+        }
+    }
+}
+impl<'a> BitXorAssign<&'a Int> for Int {
+    #[inline]
+    fn bitxor_assign(&mut self, other: &'a Int) {
+        if bitop_ref(self, other, BitOp::Xor).is_err() {
+            let res = &*self ^ other;
+            *self = res;
+        }
+    }
+}
+
+macro_rules! impl_arith_prim (
+    (signed $t:ty) => (
+        // Limbs are unsigned, so make sure we account for the sign
+        // when $t is signed
+        impl Add<$t> for Int {
+            type Output = Int;
+
+            #[inline]
+            fn add(self, other: $t) -> Int {
+                if other == 0 {
+                    return self;
+                }
+                if other < 0 {
+                    return self - Limb(other.unsigned_abs() as BaseInt);
+                }
+                return self + Limb(other as BaseInt);
+            }
+        }
+
+        impl AddAssign<$t> for Int {
+            #[inline]
+            fn add_assign(&mut self, other: $t) {
+                if other < 0 {
+                    *self -= Limb(other.unsigned_abs() as BaseInt);
+                } else if other > 0 {
+                    *self += Limb(other as BaseInt);
+                }
+            }
+        }
+
+        impl Sub<$t> for Int {
+            type Output = Int;
+
+            #[inline]
+            fn sub(self, other: $t) -> Int {
+                if other == 0 {
+                    return self;
+                }
+                if other < 0 {
+                    return self + Limb(other.unsigned_abs() as BaseInt);
+                }
+                return self - Limb(other as BaseInt);
+            }
+        }
+
+        impl SubAssign<$t> for Int {
+            #[inline]
+            fn sub_assign(&mut self, other: $t) {
+                if other < 0 {
+                    *self += Limb(other.unsigned_abs() as BaseInt);
+                } else if other > 0 {
+                    *self -= Limb(other as BaseInt);
+                }
+            }
+        }
+
+        impl Mul<$t> for Int {
+            type Output = Int;
+
+            #[inline]
+            fn mul(mut self, other: $t) -> Int {
+                self *= other;
+                self
+            }
+        }
+
+        impl MulAssign<$t> for Int {
+            #[inline]
+            fn mul_assign(&mut self, other: $t) {
+                if other == 0 {
+                    self.size = 0;
+                } else if other == -1 {
+                    self.negate();
+                } else if other < 0 {
+                    self.negate();
+                    *self *= Limb(other.unsigned_abs() as BaseInt);
+                } else {
+                    *self *= Limb(other as BaseInt);
+                }
+            }
+        }
 
         impl DivAssign<$t> for Int {
             #[inline]
@@ -2600,7 +4055,7 @@ macro_rules! impl_arith_prim (
                     self.negate();
                 } else if other < 0 {
                     self.negate();
-                    *self /= Limb(other.abs() as BaseInt);
+                    *self /= Limb(other.unsigned_abs() as BaseInt);
                 } else {
                     *self /= Limb(other as BaseInt);
                 }
@@ -2639,7 +4094,7 @@ macro_rules! impl_arith_prim (
                     return self;
                 }
 
-                return self % Limb(other.abs() as BaseInt);
+                return self % Limb(other.unsigned_abs() as BaseInt);
             }
         }
 
@@ -2660,7 +4115,7 @@ macro_rules! impl_arith_prim (
                         return (self, 0);
                     } else if other < 0 {
                         self.negate();
-                        self.divrem(Limb(other.abs() as BaseInt))
+                        self.divrem(Limb(other.unsigned_abs() as BaseInt))
                     } else {
                         self.divrem(Limb(other as BaseInt))
                     }
@@ -2676,45 +4131,198 @@ macro_rules! impl_arith_prim (
         impl BitAndAssign<$t> for Int {
             #[inline]
             fn bitand_assign(&mut self, other: $t) {
-                bitop_limb(self, Limb(other as BaseInt), true, BitOp::And)
+                bitop_limb(self, Limb(other as BaseInt), true, BitOp::And)
+            }
+        }
+
+        impl BitOrAssign<$t> for Int {
+            #[inline]
+            fn bitor_assign(&mut self, other: $t) {
+                bitop_limb(self, Limb(other as BaseInt), true, BitOp::Or)
+            }
+        }
+
+        impl BitXorAssign<$t> for Int {
+            #[inline]
+            fn bitxor_assign(&mut self, other: $t) {
+                bitop_limb(self, Limb(other as BaseInt), true, BitOp::Xor)
+            }
+        }
+
+        impl_arith_prim!(common $t);
+    );
+    (unsigned $t:ty) => (
+        impl Add<$t> for Int {
+            type Output = Int;
+
+            #[inline]
+            fn add(self, other: $t) -> Int {
+                if other == 0 {
+                    return self;
+                }
+                return self + Limb(other as BaseInt);
+            }
+        }
+
+        impl AddAssign<$t> for Int {
+            #[inline]
+            fn add_assign(&mut self, other: $t) {
+                if other != 0 {
+                    *self += Limb(other as BaseInt);
+                }
+            }
+        }
+
+        impl Sub<$t> for Int {
+            type Output = Int;
+
+            #[inline]
+            fn sub(self, other: $t) -> Int {
+                if other == 0 {
+                    return self;
+                }
+                return self - Limb(other as BaseInt);
+            }
+        }
+
+        impl SubAssign<$t> for Int {
+            #[inline]
+            fn sub_assign(&mut self, other: $t) {
+                if other != 0 {
+                    *self -= Limb(other as BaseInt);
+                }
+            }
+        }
+
+        impl Mul<$t> for Int {
+            type Output = Int;
+
+            #[inline]
+            fn mul(mut self, other: $t) -> Int {
+                if other == 0 {
+                    self.size = 0;
+                    return self;
+                }
+                if other == 1 || self.sign() == 0 {
+                    return self;
+                }
+                return self * Limb(other as BaseInt);
+            }
+        }
+
+        impl MulAssign<$t> for Int {
+            #[inline]
+            fn mul_assign(&mut self, other: $t) {
+                if other == 0 {
+                    self.size = 0;
+                } else if other > 1 && self.sign() != 0 {
+                    *self *= Limb(other as BaseInt);
+                }
+            }
+        }
+
+        impl Div<$t> for Int {
+            type Output = Int;
+
+            #[inline]
+            fn div(self, other: $t) -> Int {
+                if other == 0 {
+                    ll::divide_by_zero();
+                }
+                if other == 1 || self.sign() == 0 {
+                    return self;
+                }
+                return self / Limb(other as BaseInt);
+            }
+        }
+
+        impl DivAssign<$t> for Int {
+            #[inline]
+            fn div_assign(&mut self, other: $t) {
+                if other == 0 {
+                    ll::divide_by_zero();
+                } else if other > 1 && self.sign() != 0 {
+                    *self /= Limb(other as BaseInt);
+                }
+            }
+        }
+
+        impl Rem<$t> for Int {
+            type Output = Int;
+
+            #[inline]
+            fn rem(mut self, other: $t) -> Int {
+                if other == 0 {
+                    ll::divide_by_zero();
+                }
+
+                if other == 1 || self.sign() == 0 {
+                    self.size = 0;
+                    return self;
+                }
+
+                return self % Limb(other as BaseInt);
+            }
+        }
+        impl RemAssign<$t> for Int {
+            #[inline]
+            fn rem_assign(&mut self, other: $t) {
+                *self %= Limb(other as BaseInt);
+            }
+        }
+
+        impl DivRem<$t> for Int {
+            type Output = (Int, $t);
+
+            #[inline]
+            fn divrem(self, other: $t) -> Self::Output {
+                let other = other as BaseInt;
+                let (q, r) = self.divrem(Limb(other));
+                debug_assert!(r < other);
+                (q, r.0 as $t)
+            }
+        }
+
+        impl BitAndAssign<$t> for Int {
+            #[inline]
+            fn bitand_assign(&mut self, other: $t) {
+                bitop_limb(self, Limb(other as BaseInt), false, BitOp::And)
             }
         }
 
         impl BitOrAssign<$t> for Int {
             #[inline]
             fn bitor_assign(&mut self, other: $t) {
-                bitop_limb(self, Limb(other as BaseInt), true, BitOp::Or)
+                bitop_limb(self, Limb(other as BaseInt), false, BitOp::Or)
             }
         }
 
         impl BitXorAssign<$t> for Int {
             #[inline]
             fn bitxor_assign(&mut self, other: $t) {
-                bitop_limb(self, Limb(other as BaseInt), true, BitOp::Xor)
+                bitop_limb(self, Limb(other as BaseInt), false, BitOp::Xor)
             }
         }
 
         impl_arith_prim!(common $t);
     );
-    (unsigned $t:ty) => (
+    (wide $t:ty) => (
+        // For unsigned types that can be wider than a limb, like `usize` with
+        // the `force-32-bit-limbs` feature on a 64-bit target. These go
+        // through a full `Int`, since the value might not fit in a `Limb`.
         impl Add<$t> for Int {
             type Output = Int;
 
             #[inline]
             fn add(self, other: $t) -> Int {
-                if other == 0 {
-                    return self;
-                }
-                return self + Limb(other as BaseInt);
+                self + Int::from(other)
             }
         }
 
         impl AddAssign<$t> for Int {
             #[inline]
             fn add_assign(&mut self, other: $t) {
-                if other != 0 {
-                    *self += Limb(other as BaseInt);
-                }
+                *self += Int::from(other);
             }
         }
 
@@ -2723,19 +4331,14 @@ macro_rules! impl_arith_prim (
 
             #[inline]
             fn sub(self, other: $t) -> Int {
-                if other == 0 {
-                    return self;
-                }
-                return self - Limb(other as BaseInt);
+                self - Int::from(other)
             }
         }
 
         impl SubAssign<$t> for Int {
             #[inline]
             fn sub_assign(&mut self, other: $t) {
-                if other != 0 {
-                    *self -= Limb(other as BaseInt);
-                }
+                *self -= Int::from(other);
             }
         }
 
@@ -2743,26 +4346,15 @@ macro_rules! impl_arith_prim (
             type Output = Int;
 
             #[inline]
-            fn mul(mut self, other: $t) -> Int {
-                if other == 0 {
-                    self.size = 0;
-                    return self;
-                }
-                if other == 1 || self.sign() == 0 {
-                    return self;
-                }
-                return self * Limb(other as BaseInt);
+            fn mul(self, other: $t) -> Int {
+                self * Int::from(other)
             }
         }
 
         impl MulAssign<$t> for Int {
             #[inline]
             fn mul_assign(&mut self, other: $t) {
-                if other == 0 {
-                    self.size = 0;
-                } else if other > 1 && self.sign() != 0 {
-                    *self *= Limb(other as BaseInt);
-                }
+                *self *= Int::from(other);
             }
         }
 
@@ -2771,24 +4363,14 @@ macro_rules! impl_arith_prim (
 
             #[inline]
             fn div(self, other: $t) -> Int {
-                if other == 0 {
-                    ll::divide_by_zero();
-                }
-                if other == 1 || self.sign() == 0 {
-                    return self;
-                }
-                return self / Limb(other as BaseInt);
+                self / Int::from(other)
             }
         }
 
         impl DivAssign<$t> for Int {
             #[inline]
             fn div_assign(&mut self, other: $t) {
-                if other == 0 {
-                    ll::divide_by_zero();
-                } else if other > 1 && self.sign() != 0 {
-                    *self /= Limb(other as BaseInt);
-                }
+                *self /= Int::from(other);
             }
         }
 
@@ -2796,23 +4378,15 @@ macro_rules! impl_arith_prim (
             type Output = Int;
 
             #[inline]
-            fn rem(mut self, other: $t) -> Int {
-                if other == 0 {
-                    ll::divide_by_zero();
-                }
-
-                if other == 1 || self.sign() == 0 {
-                    self.size = 0;
-                    return self;
-                }
-
-                return self % Limb(other as BaseInt);
+            fn rem(self, other: $t) -> Int {
+                self % Int::from(other)
             }
         }
+
         impl RemAssign<$t> for Int {
             #[inline]
             fn rem_assign(&mut self, other: $t) {
-                *self %= Limb(other as BaseInt);
+                *self %= Int::from(other);
             }
         }
 
@@ -2821,31 +4395,29 @@ macro_rules! impl_arith_prim (
 
             #[inline]
             fn divrem(self, other: $t) -> Self::Output {
-                let other = other as BaseInt;
-                let (q, r) = self.divrem(Limb(other));
-                debug_assert!(r < other);
-                (q, r.0 as $t)
+                let (q, r) = (&self).divrem(&Int::from(other));
+                (q, r.abs().low_u128() as $t)
             }
         }
 
         impl BitAndAssign<$t> for Int {
             #[inline]
             fn bitand_assign(&mut self, other: $t) {
-                bitop_limb(self, Limb(other as BaseInt), false, BitOp::And)
+                *self &= Int::from(other);
             }
         }
 
         impl BitOrAssign<$t> for Int {
             #[inline]
             fn bitor_assign(&mut self, other: $t) {
-                bitop_limb(self, Limb(other as BaseInt), false, BitOp::Or)
+                *self |= Int::from(other);
             }
         }
 
         impl BitXorAssign<$t> for Int {
             #[inline]
             fn bitxor_assign(&mut self, other: $t) {
-                bitop_limb(self, Limb(other as BaseInt), false, BitOp::Xor)
+                *self ^= Int::from(other);
             }
         }
 
@@ -2881,739 +4453,1357 @@ macro_rules! impl_arith_prim (
             }
         }
 
-        impl<'a> Sub<$t> for &'a Int {
+        impl<'a> Sub<$t> for &'a Int {
+            type Output = Int;
+
+            #[inline]
+            fn sub(self, other: $t) -> Int {
+                self.clone() - other
+            }
+        }
+
+        impl Sub<Int> for $t {
+            type Output = Int;
+
+            #[inline]
+            fn sub(self, other: Int) -> Int {
+                -other + self
+            }
+        }
+
+        impl<'a> Sub<&'a Int> for $t {
+            type Output = Int;
+
+            #[inline]
+            fn sub(self, other: &'a Int) -> Int {
+                -(other - self)
+            }
+        }
+
+        impl<'a> Mul<$t> for &'a Int {
+            type Output = Int;
+
+            #[inline]
+            fn mul(self, other: $t) -> Int {
+                return self.clone() * other;
+            }
+        }
+
+        impl Mul<Int> for $t {
+            type Output = Int;
+
+            #[inline]
+            fn mul(self, other: Int) -> Int {
+                other * self
+            }
+        }
+
+        impl<'a> Mul<&'a Int> for $t {
+            type Output = Int;
+
+            #[inline]
+            fn mul(self, other: &'a Int) -> Int {
+                // Check for zero here to avoid cloning unnecessarily
+                if self == 0 { return Int::zero() };
+                other.clone() * self
+            }
+        }
+
+        impl<'a> Div<$t> for &'a Int {
+            type Output = Int;
+
+            #[inline]
+            fn div(self, other: $t) -> Int {
+                if other == 0 {
+                    ll::divide_by_zero();
+                }
+                return self.clone() / other;
+            }
+        }
+
+        impl Div<Int> for $t {
+            type Output = Int;
+
+            #[inline]
+            fn div(self, mut other: Int) -> Int {
+                if self == 0 {
+                    other.size = 0;
+                    return other;
+                }
+                if other.sign() == 0 {
+                    ll::divide_by_zero();
+                }
+                // There's probably a better way of doing this, but
+                // I don't see n / <bigint> being common in code
+                Int::from(self) / other
+            }
+        }
+
+        impl<'a> Div<&'a Int> for $t {
+            type Output = Int;
+
+            #[inline]
+            fn div(self, other: &'a Int) -> Int {
+                if self == 0 { return Int::zero() };
+                if other.sign() == 0 {
+                    ll::divide_by_zero();
+                }
+
+                self / other.clone()
+            }
+        }
+
+        impl<'a> Rem<$t> for &'a Int {
+            type Output = Int;
+
+            #[inline]
+            fn rem(self, other: $t) -> Int {
+                if other == 0 {
+                    ll::divide_by_zero();
+                }
+                if self.sign() == 0 || other == 1 {
+                    return Int::zero()
+                };
+                return self.clone() % other;
+            }
+        }
+
+        impl Rem<Int> for $t {
+            type Output = Int;
+
+            #[inline]
+            fn rem(self, mut other: Int) -> Int {
+                if self == 0 || other == 1 {
+                    other.size = 0;
+                    return other;
+                }
+                if other.sign() == 0 {
+                    ll::divide_by_zero();
+                }
+                // There's probably a better way of doing this, but
+                // I don't see n % <bigint> being common in code
+                Int::from(self) % other
+            }
+        }
+
+        impl<'a> Rem<&'a Int> for $t {
+            type Output = Int;
+
+            #[inline]
+            fn rem(self, other: &'a Int) -> Int {
+                if self == 0 { return Int::zero() };
+                if other.sign() == 0 {
+                    ll::divide_by_zero();
+                }
+
+                self % other.clone()
+            }
+        }
+
+        impl BitAnd<$t> for Int {
+            type Output = Int;
+
+            #[inline]
+            fn bitand(mut self, other: $t) -> Int {
+                self &= other;
+                self
+            }
+        }
+
+        impl<'a> BitAnd<$t> for &'a Int {
+            type Output = Int;
+
+            #[inline]
+            fn bitand(self, other: $t) -> Int {
+                self.clone() & other
+            }
+        }
+
+        impl BitAnd<Int> for $t {
+            type Output = Int;
+
+            #[inline]
+            fn bitand(self, other: Int) -> Int {
+                other & self
+            }
+        }
+
+        impl<'a> BitAnd<&'a Int> for $t {
+            type Output = Int;
+
+            #[inline]
+            fn bitand(self, other: &'a Int) -> Int {
+                other & self
+            }
+        }
+
+        impl BitOr<$t> for Int {
             type Output = Int;
 
             #[inline]
-            fn sub(self, other: $t) -> Int {
-                self.clone() - other
+            fn bitor(mut self, other: $t) -> Int {
+                self |= other;
+                self
             }
         }
 
-        impl Sub<Int> for $t {
+        impl<'a> BitOr<$t> for &'a Int {
             type Output = Int;
 
             #[inline]
-            fn sub(self, other: Int) -> Int {
-                -other + self
+            fn bitor(self, other: $t) -> Int {
+                self.clone() | other
             }
         }
 
-        impl<'a> Sub<&'a Int> for $t {
+        impl BitOr<Int> for $t {
             type Output = Int;
 
             #[inline]
-            fn sub(self, other: &'a Int) -> Int {
-                -(other - self)
+            fn bitor(self, other: Int) -> Int {
+                other | self
             }
         }
 
-        impl<'a> Mul<$t> for &'a Int {
+        impl<'a> BitOr<&'a Int> for $t {
             type Output = Int;
 
             #[inline]
-            fn mul(self, other: $t) -> Int {
-                return self.clone() * other;
+            fn bitor(self, other: &'a Int) -> Int {
+                other | self
             }
         }
 
-        impl Mul<Int> for $t {
+        impl BitXor<$t> for Int {
             type Output = Int;
 
             #[inline]
-            fn mul(self, other: Int) -> Int {
-                other * self
+            fn bitxor(mut self, other: $t) -> Int {
+                self ^= other;
+                self
             }
         }
 
-        impl<'a> Mul<&'a Int> for $t {
+        impl<'a> BitXor<$t> for &'a Int {
             type Output = Int;
 
             #[inline]
-            fn mul(self, other: &'a Int) -> Int {
-                // Check for zero here to avoid cloning unnecessarily
-                if self == 0 { return Int::zero() };
-                other.clone() * self
+            fn bitxor(self, other: $t) -> Int {
+                self.clone() ^ other
             }
         }
 
-        impl<'a> Div<$t> for &'a Int {
+        impl BitXor<Int> for $t {
             type Output = Int;
 
             #[inline]
-            fn div(self, other: $t) -> Int {
-                if other == 0 {
-                    ll::divide_by_zero();
-                }
-                return self.clone() / other;
+            fn bitxor(self, other: Int) -> Int {
+                other ^ self
             }
         }
 
-        impl Div<Int> for $t {
+        impl<'a> BitXor<&'a Int> for $t {
             type Output = Int;
 
             #[inline]
-            fn div(self, mut other: Int) -> Int {
-                if self == 0 {
-                    other.size = 0;
-                    return other;
-                }
-                if other.sign() == 0 {
-                    ll::divide_by_zero();
-                }
-                // There's probably a better way of doing this, but
-                // I don't see n / <bigint> being common in code
-                Int::from(self) / other
+            fn bitxor(self, other: &'a Int) -> Int {
+                other ^ self
             }
         }
+    )
+);
 
-        impl<'a> Div<&'a Int> for $t {
-            type Output = Int;
+// Implement for `i32` which is the fallback type, usize and the base integer type.
+// No more than this because the rest of Rust doesn't much coercion for integer types,
+// but allocating an entire multiple-precision `Int` to do `+ 1` seems silly.
+impl_arith_prim!(signed i32);
+#[cfg(not(all(limb_width = "32", target_pointer_width = "64")))]
+impl_arith_prim!(unsigned usize);
+#[cfg(all(limb_width = "32", target_pointer_width = "64"))]
+impl_arith_prim!(wide usize);
+impl_arith_prim!(unsigned BaseInt);
 
-            #[inline]
-            fn div(self, other: &'a Int) -> Int {
-                if self == 0 { return Int::zero() };
-                if other.sign() == 0 {
-                    ll::divide_by_zero();
-                }
+impl PartialEq<i32> for Int {
+    #[inline]
+    fn eq(&self, &other: &i32) -> bool {
+        let sign = self.sign();
+        // equals zero
+        if sign == 0 || other == 0 {
+            return other == sign;
+        }
+        // Differing signs
+        if sign < 0 && other > 0 || sign > 0 && other < 0 {
+            return false;
+        }
 
-                self / other.clone()
-            }
+        // We can't fall back to the `== Limb` impl when self is negative
+        // since it'll fail because of signs
+        if sign < 0 {
+            if self.abs_size() > 1 { return false; }
+            return *self.limbs() == (other.unsigned_abs() as BaseInt);
         }
 
-        impl<'a> Rem<$t> for &'a Int {
-            type Output = Int;
+        self.eq(&Limb(other.unsigned_abs() as BaseInt))
+    }
+}
 
-            #[inline]
-            fn rem(self, other: $t) -> Int {
-                if other == 0 {
-                    ll::divide_by_zero();
-                }
-                if self.sign() == 0 || other == 1 {
-                    return Int::zero()
-                };
-                return self.clone() % other;
-            }
+impl PartialEq<Int> for i32 {
+    #[inline]
+    fn eq(&self, other: &Int) -> bool {
+        other.eq(self)
+    }
+}
+
+impl PartialOrd<i32> for Int {
+    #[inline]
+    fn partial_cmp(&self, &other: &i32) -> Option<Ordering> {
+        let self_sign = self.sign();
+        let other_sign = if other < 0 {
+            -1
+        } else if other > 0 {
+            1
+        } else {
+            0
+        };
+
+        // Both are equal
+        if self_sign == 0 && other_sign == 0 {
+            return Some(Ordering::Equal);
         }
 
-        impl Rem<Int> for $t {
-            type Output = Int;
+        let ord = if self_sign > other_sign {
+            Ordering::Greater
+        } else if self_sign < other_sign {
+            Ordering::Less
+        } else { // Now both signs are the same, and non-zero
 
-            #[inline]
-            fn rem(self, mut other: Int) -> Int {
-                if self == 0 || other == 1 {
-                    other.size = 0;
-                    return other;
-                }
-                if other.sign() == 0 {
-                    ll::divide_by_zero();
+            if self_sign < 0 {
+                if self.abs_size() > 1 {
+                    Ordering::Less
+                } else {
+                    self.to_single_limb().cmp(&Limb(other.unsigned_abs() as BaseInt)).reverse()
                 }
-                // There's probably a better way of doing this, but
-                // I don't see n % <bigint> being common in code
-                Int::from(self) % other
+            } else {
+                return self.partial_cmp(&Limb(other.unsigned_abs() as BaseInt));
+            }
+        };
+
+        Some(ord)
+    }
+}
+
+impl PartialOrd<Int> for i32 {
+    #[inline]
+    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
+        other.partial_cmp(self).map(|o| o.reverse())
+    }
+}
+
+impl PartialEq<usize> for Int {
+    #[inline]
+    fn eq(&self, &other: &usize) -> bool {
+        eq_64(self, other as u64, false)
+    }
+}
+
+impl PartialEq<Int> for usize {
+    #[inline]
+    fn eq(&self, other: &Int) -> bool {
+        other.eq(self)
+    }
+}
+
+impl PartialOrd<usize> for Int {
+    #[inline]
+    fn partial_cmp(&self, &other: &usize) -> Option<Ordering> {
+        Some(cmp_64(self, other as u64, false))
+    }
+}
+
+impl PartialOrd<Int> for usize {
+    #[inline]
+    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
+        Some(cmp_64(other, *self as u64, false).reverse())
+    }
+}
+
+impl Int {
+    fn modpow2(&self, exp:&Int, pow2:usize) -> Int {
+        let mask = (Int::one() << pow2) - 1;
+        let mut result = Int::one();
+        let mut base_to_pow_of_2:Int = self & &mask;
+        for i in 0..exp.bit_length() {
+            if exp.bit(i) {
+                result *= &base_to_pow_of_2;
+                result &= &mask;
+            }
+            base_to_pow_of_2 = base_to_pow_of_2.dsquare();
+            base_to_pow_of_2 &= &mask;
+        }
+        result
+    }
+
+    fn inverse_for_powof2(&self, pow2:usize) -> Int {
+        let mut y = Int::one();
+        for i in 1..(pow2+1) {
+            if (Int::one() << (i-1)) < (self*&y % (Int::one() << i)) {
+                y.set_bit(i-1, true);
             }
         }
+        y
+    }
+
+
+    fn odd_modpow(&self, exp:&Int, modulus:&Int) -> Int {
+        let mont = mtgy::MtgyModulus::new(modulus);
+        let base = mont.to_mtgy(self);
+        let result = mont.pow(&base, exp);
+        mont.to_int(&result)
+    }
+}
+
+
+// do a sign-magnitude comparison. With 64-bit limbs `BaseInt` is `u64`, so
+// the casts checking whether `mag` fits in a limb are no-ops there.
+#[allow(clippy::unnecessary_cast)]
+fn eq_64(x: &Int, mag: u64, neg: bool) -> bool {
+    let sign = if mag == 0 { 0 } else if neg { -1 } else { 1 };
+    if x.sign() != sign {
+        return false
+    } else if mag == 0 {
+        // we're guaranteed to have x == 0 since the signs match
+        return true
+    }
+
+
+    let abs_size = x.abs_size();
+    debug_assert!(abs_size >= 1);
+    let ptr = x.limbs();
+    let lo_limb = *ptr;
+
+    if mag as BaseInt as u64 == mag {
+        abs_size == 1 && lo_limb.0 == mag as BaseInt
+    } else {
+        // we can only get here when Limbs are small, and the Int
+        // is large
+        assert_eq!(Limb::BITS, 32);
+
+        if abs_size == 2 {
+            let hi_limb = unsafe {*ptr.offset(1)};
+            hi_limb.0 == (mag >> 32) as BaseInt
+                && lo_limb.0 == mag as BaseInt
+        } else {
+            false
+        }
+    }
+}
 
-        impl<'a> Rem<&'a Int> for $t {
-            type Output = Int;
+#[allow(clippy::unnecessary_cast)]
+fn cmp_64(x: &Int, mag: u64, neg: bool) -> Ordering {
+    if mag == 0 {
+        return x.sign().cmp(&0)
+    }
 
-            #[inline]
-            fn rem(self, other: &'a Int) -> Int {
-                if self == 0 { return Int::zero() };
-                if other.sign() == 0 {
-                    ll::divide_by_zero();
-                }
+    let size = x.size;
+    if (size < 0) != neg || size == 0 {
+        // they have different signs
+        return size.cmp(&if neg {-1} else {1})
+    }
+    let ptr = x.limbs();
+    let lo_limb = *ptr;
 
-                self % other.clone()
-            }
-        }
+    let mag_ord = if mag as BaseInt as u64 == mag {
+        (size.abs(), lo_limb.0).cmp(&(1, mag as BaseInt))
+    } else {
+        assert_eq!(Limb::BITS, 32);
+        debug_assert!(size.abs() >= 1);
+        let hi_limb = if size.abs() == 1 {
+            Limb(0)
+        } else {
+            unsafe {*ptr.offset(1)}
+        };
 
-        impl BitAnd<$t> for Int {
-            type Output = Int;
+        (size.abs(), hi_limb.0, lo_limb.0)
+            .cmp(&(2, (mag >> 32) as BaseInt, mag as BaseInt))
+    };
+    if size < 0 && neg {
+        // both negative, so the magnitude orderings need to be
+        // flipped
+        mag_ord.reverse()
+    } else {
+        mag_ord
+    }
+}
 
-            #[inline]
-            fn bitand(mut self, other: $t) -> Int {
-                self &= other;
-                self
-            }
-        }
+impl PartialEq<u64> for Int {
+    fn eq(&self, &other: &u64) -> bool {
+        eq_64(self, other, false)
+    }
+}
 
-        impl<'a> BitAnd<$t> for &'a Int {
-            type Output = Int;
+impl PartialEq<Int> for u64 {
+    fn eq(&self, other: &Int) -> bool {
+        eq_64(other, *self, false)
+    }
+}
 
-            #[inline]
-            fn bitand(self, other: $t) -> Int {
-                self.clone() & other
-            }
-        }
+impl PartialOrd<u64> for Int {
+    fn partial_cmp(&self, &other: &u64) -> Option<Ordering> {
+        Some(cmp_64(self, other, false))
+    }
+}
 
-        impl BitAnd<Int> for $t {
-            type Output = Int;
+impl PartialOrd<Int> for u64 {
+    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
+        Some(cmp_64(other, *self, false).reverse())
+    }
+}
 
-            #[inline]
-            fn bitand(self, other: Int) -> Int {
-                other & self
-            }
-        }
+impl PartialEq<i64> for Int {
+    fn eq(&self, &other: &i64) -> bool {
+        eq_64(self, other.unsigned_abs(), other < 0)
+    }
+}
 
-        impl<'a> BitAnd<&'a Int> for $t {
-            type Output = Int;
+impl PartialEq<Int> for i64 {
+    fn eq(&self, other: &Int) -> bool {
+        eq_64(other, self.unsigned_abs(), *self < 0)
+    }
+}
 
-            #[inline]
-            fn bitand(self, other: &'a Int) -> Int {
-                other & self
-            }
-        }
+impl PartialOrd<i64> for Int {
+    fn partial_cmp(&self, &other: &i64) -> Option<Ordering> {
+        Some(cmp_64(self, other.unsigned_abs(), other < 0))
+    }
+}
 
-        impl BitOr<$t> for Int {
-            type Output = Int;
+impl PartialOrd<Int> for i64 {
+    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
+        Some(cmp_64(other, self.unsigned_abs(), *self < 0).reverse())
+    }
+}
 
-            #[inline]
-            fn bitor(mut self, other: $t) -> Int {
-                self |= other;
-                self
+macro_rules! impl_from_prim (
+    (signed $($t:ty => $u:ty),*) => {
+        $(impl ::std::convert::From<$t> for Int {
+            fn from(val: $t) -> Int {
+                // `wrapping_abs` leaves MIN as it is, but read as unsigned its bits
+                // are the right magnitude.
+                let mut i = Int::from(val.wrapping_abs() as $u);
+                if val < 0 {
+                    i.negate();
+                }
+                i
             }
-        }
+        })*
+    };
+    (unsigned $($t:ty),*) => {
+        $(impl ::std::convert::From<$t> for Int {
+            fn from(val: $t) -> Int {
+                let mut val = val;
+                let mut i = Int::zero();
+                // Loop to handle types wider than a limb, like u64 on 32-bit
+                // platforms and u128.
+                while val != 0 {
+                    i.push(Limb(val as BaseInt));
+                    val = val.checked_shr(Limb::BITS as u32).unwrap_or(0);
+                }
+                i
+            }
+        })*
+    }
+);
 
-        impl<'a> BitOr<$t> for &'a Int {
-            type Output = Int;
+impl_from_prim!(signed   i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+impl_from_prim!(unsigned u8, u16, u32, u64, u128, usize);
 
-            #[inline]
-            fn bitor(self, other: $t) -> Int {
-                self.clone() | other
-            }
-        }
+// Number formatting - There's not much difference between the impls,
+// hence the macro
 
-        impl BitOr<Int> for $t {
-            type Output = Int;
+macro_rules! impl_fmt (
+    ($t:path, $radix:expr, $upper:expr, $prefix:expr) => {
+        impl $t for Int {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let mut s : &str = &self.to_str_radix($radix, $upper);
+                let is_positive = self.sign() >= 0;
+                // to_str_radix adds the sign if `self` is negative, but
+                // pad_integral adds it's own sign, so slice the sign off
+                if !is_positive {
+                    s = &s[1..];
+                }
 
-            #[inline]
-            fn bitor(self, other: Int) -> Int {
-                other | self
+                f.pad_integral(is_positive, $prefix, s)
             }
         }
+    };
 
-        impl<'a> BitOr<&'a Int> for $t {
-            type Output = Int;
+    ($t:path, $radix:expr, $prefix:expr) => {
+        impl_fmt!($t, $radix, false, $prefix);
+    }
+);
 
-            #[inline]
-            fn bitor(self, other: &'a Int) -> Int {
-                other | self
-            }
-        }
+impl_fmt!(fmt::Binary,    2, "0b");
+impl_fmt!(fmt::Octal,     8, "0o");
+impl_fmt!(fmt::Display,  10, "");
+impl_fmt!(fmt::Debug,    10, "");
+impl_fmt!(fmt::LowerHex, 16, false, "0x");
+impl_fmt!(fmt::UpperHex, 16, true, "0x");
 
-        impl BitXor<$t> for Int {
-            type Output = Int;
+// Formatting builder
 
-            #[inline]
-            fn bitxor(mut self, other: $t) -> Int {
-                self ^= other;
-                self
-            }
+/**
+ * A value returned by `Int::format` that controls how the `Int` is written out.
+ *
+ * It implements `fmt::Display`, so the usual width, fill, alignment, `+` and `0` flags can
+ * still be given in the format string, and `#` adds a `0b`, `0o` or `0x` prefix for bases
+ * 2, 8 and 16.
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct IntFormat<'a> {
+    int: &'a Int,
+    radix: u8,
+    upper: bool,
+    group: Option<(char, usize)>,
+    sig_digits: Option<usize>,
+}
+
+impl<'a> IntFormat<'a> {
+    /**
+     * Writes the number in base `radix`.
+     *
+     * Panics if `radix` is less than two or greater than 36.
+     */
+    pub fn radix(mut self, radix: u8) -> IntFormat<'a> {
+        if !(2..=36).contains(&radix) {
+            panic!("Invalid base: {}", radix);
         }
+        self.radix = radix;
+        self
+    }
 
-        impl<'a> BitXor<$t> for &'a Int {
-            type Output = Int;
+    /**
+     * Uses upper-case letters for digits above nine.
+     */
+    pub fn upper(mut self, upper: bool) -> IntFormat<'a> {
+        self.upper = upper;
+        self
+    }
 
-            #[inline]
-            fn bitxor(self, other: $t) -> Int {
-                self.clone() ^ other
-            }
-        }
+    /**
+     * Separates the digits into groups of `size`, counting from the least significant
+     * digit, e.g. `group(',', 3)` gives `1,234,567`.
+     *
+     * Panics if `size` is zero.
+     */
+    pub fn group(mut self, sep: char, size: usize) -> IntFormat<'a> {
+        assert!(size > 0, "digit group size is zero");
+        self.group = Some((sep, size));
+        self
+    }
 
-        impl BitXor<Int> for $t {
-            type Output = Int;
+    /**
+     * Writes the number in scientific notation with `digits` significant digits, rounding
+     * halfway cases away from zero, e.g. `1.23e6`. The exponent is written in decimal after
+     * an `e`, or after an `@` for bases above ten where `e` is a digit. Digit grouping is
+     * not applied in this form.
+     *
+     * Panics if `digits` is zero.
+     */
+    pub fn scientific(mut self, digits: usize) -> IntFormat<'a> {
+        assert!(digits > 0, "no significant digits requested");
+        self.sig_digits = Some(digits);
+        self
+    }
 
-            #[inline]
-            fn bitxor(self, other: Int) -> Int {
-                other ^ self
+    // The digits of the magnitude of `self.int` in scientific notation
+    fn scientific_digits(&self, digits: &str, n: usize) -> String {
+        let mut exp = digits.len() - 1;
+        let mut mant = if digits.len() <= n {
+            let mut mant = digits.to_string();
+            mant.extend(std::iter::repeat_n('0', n - digits.len()));
+            mant
+        } else {
+            let p = Int::from(self.radix).pow(digits.len() - n);
+            let (mut q, r) = self.int.clone().abs().divmod(&p);
+            if (r << 1) >= p {
+                q += Limb(1);
             }
+            q.to_str_radix(self.radix, self.upper)
+        };
+        // Rounding up can carry into a new digit
+        if mant.len() > n {
+            mant.truncate(n);
+            exp += 1;
         }
 
-        impl<'a> BitXor<&'a Int> for $t {
-            type Output = Int;
-
-            #[inline]
-            fn bitxor(self, other: &'a Int) -> Int {
-                other ^ self
-            }
+        let mut s = String::with_capacity(n + 24);
+        s.push_str(&mant[..1]);
+        if n > 1 {
+            s.push('.');
+            s.push_str(&mant[1..]);
         }
-    )
-);
+        s.push(if self.radix <= 10 { 'e' } else { '@' });
+        s.push_str(&exp.to_string());
+        s
+    }
+}
 
-// Implement for `i32` which is the fallback type, usize and the base integer type.
-// No more than this because the rest of Rust doesn't much coercion for integer types,
-// but allocating an entire multiple-precision `Int` to do `+ 1` seems silly.
-impl_arith_prim!(signed i32);
-impl_arith_prim!(unsigned usize);
-impl_arith_prim!(unsigned BaseInt);
+impl<'a> fmt::Display for IntFormat<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = self.int.to_str_radix(self.radix, self.upper);
+        let is_positive = self.int.sign() >= 0;
+        let digits = if is_positive { &s[..] } else { &s[1..] };
+
+        let body = match (self.sig_digits, self.group) {
+            (Some(n), _) => self.scientific_digits(digits, n),
+            (None, Some((sep, size))) => {
+                let mut body = String::with_capacity(digits.len() * 2);
+                for (i, c) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i) % size == 0 {
+                        body.push(sep);
+                    }
+                    body.push(c);
+                }
+                body
+            }
+            (None, None) => digits.to_string(),
+        };
 
-impl PartialEq<i32> for Int {
-    #[inline]
-    fn eq(&self, &other: &i32) -> bool {
-        let sign = self.sign();
-        // equals zero
-        if sign == 0 || other == 0 {
-            return other == sign;
-        }
-        // Differing signs
-        if sign < 0 && other > 0 || sign > 0 && other < 0 {
-            return false;
-        }
+        let prefix = match self.radix {
+            2 => "0b",
+            8 => "0o",
+            16 => "0x",
+            _ => "",
+        };
+        f.pad_integral(is_positive, prefix, &body)
+    }
+}
 
-        // We can't fall back to the `== Limb` impl when self is negative
-        // since it'll fail because of signs
-        if sign < 0 {
-            if self.abs_size() > 1 { return false; }
-            return *self.limbs() == (other.abs() as BaseInt);
-        }
+// Allocation errors
+
+/// The error returned by the fallible `try_*` methods of `Int` when the result, or the scratch
+/// space needed to calculate it, can't be stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllocError { kind: AllocErrorKind }
+
+#[derive(Debug, Clone, PartialEq)]
+enum AllocErrorKind {
+    CapacityOverflow,
+    LimitExceeded,
+    AllocFailed(usize)
+}
+
+impl AllocError {
+    /// Whether the error was caused by the limit set with `Int::set_limb_limit`.
+    pub fn is_limit_exceeded(&self) -> bool {
+        self.kind == AllocErrorKind::LimitExceeded
+    }
 
-        self.eq(&Limb(other.abs() as BaseInt))
+    fn desc(&self) -> &'static str {
+        match self.kind {
+            AllocErrorKind::CapacityOverflow => "integer too large to represent",
+            AllocErrorKind::LimitExceeded => "integer exceeds the limb limit",
+            AllocErrorKind::AllocFailed(_) => "memory allocation failed"
+        }
     }
 }
 
-impl PartialEq<Int> for i32 {
-    #[inline]
-    fn eq(&self, other: &Int) -> bool {
-        other.eq(self)
+#[cfg(feature = "std")]
+impl Error for AllocError {
+    fn description(&self) -> &str {
+        self.desc()
     }
 }
 
-impl PartialOrd<i32> for Int {
-    #[inline]
-    fn partial_cmp(&self, &other: &i32) -> Option<Ordering> {
-        let self_sign = self.sign();
-        let other_sign = if other < 0 {
-            -1
-        } else if other > 0 {
-            1
-        } else {
-            0
-        };
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.desc().fmt(f)
+    }
+}
 
-        // Both are equal
-        if self_sign == 0 && other_sign == 0 {
-            return Some(Ordering::Equal);
-        }
+// DER decoding errors
 
-        let ord = if self_sign > other_sign {
-            Ordering::Greater
-        } else if self_sign < other_sign {
-            Ordering::Less
-        } else { // Now both signs are the same, and non-zero
+/// The error returned by `Int::from_der` for input that isn't a valid DER INTEGER.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerError { kind: DerErrorKind }
 
-            if self_sign < 0 {
-                if self.abs_size() > 1 {
-                    Ordering::Less
-                } else {
-                    self.to_single_limb().cmp(&Limb(other.abs() as BaseInt)).reverse()
-                }
-            } else {
-                return self.partial_cmp(&Limb(other.abs() as BaseInt));
-            }
-        };
+#[derive(Debug, Clone, PartialEq)]
+enum DerErrorKind {
+    Truncated,
+    UnexpectedTag(u8),
+    InvalidLength,
+    EmptyContent,
+    NonMinimal,
+    TrailingData
+}
 
-        Some(ord)
+impl DerError {
+    fn desc(&self) -> &'static str {
+        match self.kind {
+            DerErrorKind::Truncated => "DER input is truncated",
+            DerErrorKind::UnexpectedTag(_) => "DER value is not an INTEGER",
+            DerErrorKind::InvalidLength => "invalid DER length encoding",
+            DerErrorKind::EmptyContent => "DER INTEGER has no content",
+            DerErrorKind::NonMinimal => "DER INTEGER content is not minimal",
+            DerErrorKind::TrailingData => "unexpected data after DER INTEGER"
+        }
     }
 }
 
-impl PartialOrd<Int> for i32 {
-    #[inline]
-    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
-        other.partial_cmp(self).map(|o| o.reverse())
+#[cfg(feature = "std")]
+impl Error for DerError {
+    fn description(&self) -> &str {
+        self.desc()
     }
 }
 
-impl PartialEq<usize> for Int {
-    #[inline]
-    fn eq(&self, &other: &usize) -> bool {
-        return self.eq(&Limb(other as BaseInt));
+impl fmt::Display for DerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.desc().fmt(f)
     }
 }
 
-impl PartialEq<Int> for usize {
-    #[inline]
-    fn eq(&self, other: &Int) -> bool {
-        other.eq(self)
+// String parsing
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseIntError { kind: ErrorKind }
+
+#[derive(Debug, Clone, PartialEq)]
+enum ErrorKind {
+    Empty,
+    InvalidDigit(usize),
+    LimitExceeded
+}
+
+impl ParseIntError {
+    /// The byte offset in the input of the character that couldn't be parsed, if the
+    /// error was caused by one.
+    pub fn offset(&self) -> Option<usize> {
+        match self.kind {
+            ErrorKind::InvalidDigit(offset) => Some(offset),
+            _ => None
+        }
+    }
+
+    fn desc(&self) -> &'static str {
+        match self.kind {
+            ErrorKind::Empty => "cannot parse empty string",
+            ErrorKind::InvalidDigit(_) => "invalid digit found in string",
+            ErrorKind::LimitExceeded => "number exceeds the limb limit"
+        }
     }
 }
 
-impl PartialOrd<usize> for Int {
-    #[inline]
-    fn partial_cmp(&self, &other: &usize) -> Option<Ordering> {
-        self.partial_cmp(&Limb(other as BaseInt))
+#[cfg(feature = "std")]
+impl Error for ParseIntError {
+    fn description(&self) -> &str {
+        self.desc()
     }
 }
 
-impl PartialOrd<Int> for usize {
-    #[inline]
-    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
-        Limb(*self as BaseInt).partial_cmp(other)
+impl fmt::Display for ParseIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            ErrorKind::InvalidDigit(offset) => write!(f, "{} at byte {}", self.desc(), offset),
+            _ => self.desc().fmt(f)
+        }
     }
 }
 
-impl Int {
-    fn modpow2(&self, exp:&Int, pow2:usize) -> Int {
-        let mask = (Int::one() << pow2) - 1;
-        let mut result = Int::one();
-        let mut base_to_pow_of_2:Int = self & &mask;
-        for i in 0..exp.bit_length() {
-            if exp.bit(i as u32) {
-                result *= &base_to_pow_of_2;
-                result &= &mask;
-            }
-            base_to_pow_of_2 = base_to_pow_of_2.dsquare();
-            base_to_pow_of_2 &= &mask;
+/**
+ * A configurable parser for `Int`s.
+ *
+ * `IntParser::new()` accepts the same strict form as `Int::from_str_radix`: an optional `-`
+ * followed by digits. Each of the more lenient forms can be enabled on its own, or all of
+ * them at once with `IntParser::lenient()`.
+ *
+ * ```
+ * # extern crate framp as ramp;
+ * # use ramp::int::{Int, IntParser};
+ * # fn main() {
+ * let parser = IntParser::lenient();
+ * assert_eq!(parser.parse(" +1_000_000 ").unwrap(), Int::from(1000000));
+ * assert_eq!(parser.parse("-0xff").unwrap(), Int::from(-255));
+ *
+ * let err = IntParser::new().parse("12_345").unwrap_err();
+ * assert_eq!(err.offset(), Some(2));
+ * # }
+ * ```
+ */
+#[derive(Clone, Copy, Debug)]
+pub struct IntParser {
+    radix: u8,
+    prefixes: bool,
+    underscores: bool,
+    plus_sign: bool,
+    whitespace: bool,
+}
+
+impl IntParser {
+    /**
+     * Creates a strict parser for base 10 numbers.
+     */
+    pub fn new() -> IntParser {
+        IntParser {
+            radix: 10,
+            prefixes: false,
+            underscores: false,
+            plus_sign: false,
+            whitespace: false,
         }
-        result
     }
 
-    fn inverse_for_powof2(&self, pow2:usize) -> Int {
-        let mut y = Int::one();
-        for i in 1..(pow2+1) {
-            if (Int::one() << (i-1)) < (self*&y % (Int::one() << i)) {
-                y.set_bit((i-1) as u32, true);
-            }
+    /**
+     * Creates a base 10 parser that accepts radix prefixes, `_` separators, a leading
+     * `+` and surrounding whitespace.
+     */
+    pub fn lenient() -> IntParser {
+        IntParser {
+            radix: 10,
+            prefixes: true,
+            underscores: true,
+            plus_sign: true,
+            whitespace: true,
         }
-        y
     }
 
+    /**
+     * Sets the base used for numbers without a radix prefix.
+     *
+     * Panics if `radix` is less than two or greater than 36.
+     */
+    pub fn radix(mut self, radix: u8) -> IntParser {
+        if !(2..=36).contains(&radix) {
+            panic!("Invalid base: {}", radix);
+        }
+        self.radix = radix;
+        self
+    }
 
-    fn odd_modpow(&self, exp:&Int, modulus:&Int) -> Int {
-        let mont = mtgy::MtgyModulus::new(modulus);
-        let base = mont.to_mtgy(self);
-        let result = mont.pow(&base, &exp);
-        return mont.to_int(&result)
+    /**
+     * Whether to accept the `0x`, `0o` and `0b` prefixes (in either case) after the sign,
+     * which select base 16, 8 and 2 respectively. A prefix is only recognised when its
+     * letter isn't also a digit in the parser's base, so `0b1` is still a hexadecimal
+     * number in base 16.
+     */
+    pub fn prefixes(mut self, allow: bool) -> IntParser {
+        self.prefixes = allow;
+        self
     }
-}
 
+    /**
+     * Whether to accept `_` between digits, as in Rust literals. An `_` may appear
+     * anywhere after the first digit or the radix prefix.
+     */
+    pub fn underscores(mut self, allow: bool) -> IntParser {
+        self.underscores = allow;
+        self
+    }
 
-const MAX_LIMB: u64 = !0 >> (64 - Limb::BITS);
+    /**
+     * Whether to accept a leading `+`.
+     */
+    pub fn plus_sign(mut self, allow: bool) -> IntParser {
+        self.plus_sign = allow;
+        self
+    }
 
-// do a sign-magnitude comparison
-fn eq_64(x: &Int, mag: u64, neg: bool) -> bool {
-    let sign = if mag == 0 { 0 } else if neg { -1 } else { 1 };
-    if x.sign() != sign {
-        return false
-    } else if mag == 0 {
-        // we're guaranteed to have x == 0 since the signs match
-        return true
+    /**
+     * Whether to ignore leading and trailing whitespace.
+     */
+    pub fn whitespace(mut self, allow: bool) -> IntParser {
+        self.whitespace = allow;
+        self
     }
 
+    /**
+     * Parses `src` into an `Int`. Offsets reported in errors are relative to the start
+     * of `src`, including any whitespace.
+     */
+    pub fn parse(&self, src: &str) -> Result<Int, ParseIntError> {
+        let bytes = src.as_bytes();
+        let (mut pos, end) = if self.whitespace {
+            let trimmed = src.trim_start();
+            let start = src.len() - trimmed.len();
+            (start, start + trimmed.trim_end().len())
+        } else {
+            (0, src.len())
+        };
 
-    let abs_size = x.abs_size();
-    debug_assert!(abs_size >= 1);
-    let ptr = x.limbs();
-    let lo_limb = *ptr;
+        let mut sign = 1;
+        if pos < end && bytes[pos] == b'-' {
+            sign = -1;
+            pos += 1;
+        } else if self.plus_sign && pos < end && bytes[pos] == b'+' {
+            pos += 1;
+        }
+
+        let mut base = self.radix;
+        let mut prefixed = false;
+        if self.prefixes && end - pos >= 2 && bytes[pos] == b'0' &&
+            digit_value(bytes[pos + 1]) >= self.radix {
+            let prefix_base = match bytes[pos + 1] {
+                b'x' | b'X' => 16,
+                b'o' | b'O' => 8,
+                b'b' | b'B' => 2,
+                _ => 0
+            };
+            if prefix_base != 0 {
+                base = prefix_base;
+                prefixed = true;
+                pos += 2;
+            }
+        }
 
-    if mag < MAX_LIMB {
-        abs_size == 1 && lo_limb.0 == mag as BaseInt
-    } else {
-        // we can only get here when Limbs are small, and the Int
-        // is large
-        assert_eq!(Limb::BITS, 32);
+        let mut buf = Vec::with_capacity(end - pos);
+        for (i, &c) in bytes[pos..end].iter().enumerate() {
+            if c == b'_' && self.underscores && (prefixed || !buf.is_empty()) {
+                continue;
+            }
+            let b = digit_value(c);
+            if b >= base {
+                return Err(ParseIntError { kind: ErrorKind::InvalidDigit(pos + i) });
+            }
+            buf.push(b);
+        }
 
-        if abs_size == 2 {
-            let hi_limb = unsafe {*ptr.offset(1)};
-            hi_limb.0 == (mag >> 32) as BaseInt
-                && lo_limb.0 == mag as BaseInt
-        } else {
-            false
+        if buf.is_empty() {
+            return Err(ParseIntError { kind: ErrorKind::Empty });
         }
+
+        let num_digits = ll::base::base_digits_to_len(buf.len(), base as u64);
+
+        let mut i = Int::zero();
+        match i.try_set_capacity(num_digits) {
+            Err(AllocError { kind: AllocErrorKind::LimitExceeded }) =>
+                return Err(ParseIntError { kind: ErrorKind::LimitExceeded }),
+            res => unwrap_alloc(res),
+        }
+
+        unsafe {
+            let size = ll::base::from_base(i.limbs_uninit(), buf.as_ptr(), buf.len() as isize, base as u64);
+            i.size = (size as isize) * sign;
+        }
+
+        Ok(i)
     }
 }
 
-fn cmp_64(x: &Int, mag: u64, neg: bool) -> Ordering {
-    if mag == 0 {
-        return x.sign().cmp(&0)
+impl std::default::Default for IntParser {
+    fn default() -> IntParser {
+        IntParser::new()
     }
+}
 
-    let size = x.size;
-    if (size < 0) != neg || size == 0 {
-        // they have different signs
-        return size.cmp(&if neg {-1} else {1})
+// The value of the digit `c` in bases up to 36, or 255 if it isn't one.
+fn digit_value(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'A'..=b'Z' => (c - b'A') + 10,
+        b'a'..=b'z' => (c - b'a') + 10,
+        _ => 255
     }
-    let ptr = x.limbs();
-    let lo_limb = *ptr;
+}
 
-    let mag_ord = if mag < MAX_LIMB {
-        (size.abs(), lo_limb.0).cmp(&(1, mag as BaseInt))
-    } else {
-        assert_eq!(Limb::BITS, 32);
-        debug_assert!(size.abs() >= 1);
-        let hi_limb = if size.abs() == 1 {
-            Limb(0)
-        } else {
-            unsafe {*ptr.offset(1)}
-        };
+impl FromStr for Int {
+    type Err = ParseIntError;
 
-        (size.abs(), hi_limb.0, lo_limb.0)
-            .cmp(&(2, (mag >> 32) as BaseInt, mag as BaseInt))
-    };
-    if size < 0 && neg {
-        // both negative, so the magnitude orderings need to be
-        // flipped
-        mag_ord.reverse()
-    } else {
-        mag_ord
+    fn from_str(src: &str) -> Result<Int, ParseIntError> {
+        Int::from_str_radix(src, 10)
+    }
+}
+
+// Conversion *to* primitives. `TryFrom` fails if the value doesn't fit, the
+// `to_*_wrapping` methods truncate like an `as` cast.
+
+impl Int {
+    // The value modulo 2^128, in two's complement.
+    fn low_u128(&self) -> u128 {
+        let limbs = std::cmp::min(self.abs_size() as usize, 128 / Limb::BITS);
+        let mut n: u128 = 0;
+        for k in (0..limbs).rev() {
+            let l = unsafe { *self.limbs().offset(k as isize) };
+            n = (n << Limb::BITS) | l.0 as u128;
+        }
+        if self.sign() < 0 {
+            n.wrapping_neg()
+        } else {
+            n
+        }
     }
 }
 
-impl PartialEq<u64> for Int {
-    fn eq(&self, &other: &u64) -> bool {
-        eq_64(self, other, false)
+macro_rules! impl_wrapping_to_prim (
+    ($($name:ident -> $t:ty),*) => (
+        impl Int {
+            $(
+            /**
+             * Converts to the primitive type, keeping only the bits that fit in it.
+             * This gives the same result as an `as` cast between primitive integers.
+             */
+            pub fn $name(&self) -> $t {
+                self.low_u128() as $t
+            }
+            )*
+        }
+    )
+);
+
+impl_wrapping_to_prim!(to_i8_wrapping -> i8, to_i16_wrapping -> i16, to_i32_wrapping -> i32,
+                       to_i64_wrapping -> i64, to_i128_wrapping -> i128,
+                       to_isize_wrapping -> isize,
+                       to_u8_wrapping -> u8, to_u16_wrapping -> u16, to_u32_wrapping -> u32,
+                       to_u64_wrapping -> u64, to_u128_wrapping -> u128,
+                       to_usize_wrapping -> usize);
+
+macro_rules! impl_try_from_for_prim (
+    (signed $($t:ty),*) => (
+        $(impl<'a> ::std::convert::TryFrom<&'a Int> for $t {
+            type Error = TryFromIntError;
+
+            fn try_from(i: &'a Int) -> Result<$t, TryFromIntError> {
+                let bits = ::std::mem::size_of::<$t>() * 8;
+                let len = i.bit_length();
+                // MIN is the only value that needs all the bits for its magnitude
+                if len < bits || (i.sign() < 0 && len == bits && i.trailing_zeros() == bits - 1) {
+                    Ok(i.low_u128() as $t)
+                } else {
+                    Err(TryFromIntError { _priv: () })
+                }
+            }
+        })*
+    );
+    (unsigned $($t:ty),*) => (
+        $(impl<'a> ::std::convert::TryFrom<&'a Int> for $t {
+            type Error = TryFromIntError;
+
+            fn try_from(i: &'a Int) -> Result<$t, TryFromIntError> {
+                let bits = ::std::mem::size_of::<$t>() * 8;
+                if i.sign() >= 0 && i.bit_length() <= bits {
+                    Ok(i.low_u128() as $t)
+                } else {
+                    Err(TryFromIntError { _priv: () })
+                }
+            }
+        })*
+    )
+);
+
+impl_try_from_for_prim!(signed   i8, i16, i32, i64, i128, isize);
+impl_try_from_for_prim!(unsigned u8, u16, u32, u64, u128, usize);
+
+/// The error returned when converting an `Int` to a primitive integer type that can't
+/// hold its value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TryFromIntError { _priv: () }
+
+impl TryFromIntError {
+    fn desc(&self) -> &'static str {
+        "integer out of range for the target type"
     }
 }
 
-impl PartialEq<Int> for u64 {
-    fn eq(&self, other: &Int) -> bool {
-        eq_64(other, *self, false)
+#[cfg(feature = "std")]
+impl Error for TryFromIntError {
+    fn description(&self) -> &str {
+        self.desc()
     }
 }
 
-impl PartialOrd<u64> for Int {
-    fn partial_cmp(&self, &other: &u64) -> Option<Ordering> {
-        Some(cmp_64(self, other, false))
+impl fmt::Display for TryFromIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.desc().fmt(f)
     }
 }
 
-impl PartialOrd<Int> for u64 {
-    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
-        Some(cmp_64(other, *self, false).reverse())
+impl ::num_traits::Signed for Int {
+    fn abs(&self) -> Int {
+        self.clone().abs()
     }
-}
 
-impl PartialEq<i64> for Int {
-    fn eq(&self, &other: &i64) -> bool {
-        eq_64(self, other.abs() as u64, other < 0)
+    fn abs_sub(&self, other: &Int) -> Int {
+        if *self <= *other {
+            Int::zero()
+        } else {
+            self - other
+        }
     }
-}
 
-impl PartialEq<Int> for i64 {
-    fn eq(&self, other: &Int) -> bool {
-        eq_64(other, self.abs() as u64, *self < 0)
+    fn signum(&self) -> Int {
+        Int::from(self.sign())
     }
-}
 
-impl PartialOrd<i64> for Int {
-    fn partial_cmp(&self, &other: &i64) -> Option<Ordering> {
-        Some(cmp_64(self, other.abs() as u64, other < 0))
+    fn is_positive(&self) -> bool {
+        self.sign() > 0
     }
-}
 
-impl PartialOrd<Int> for i64 {
-    fn partial_cmp(&self, other: &Int) -> Option<Ordering> {
-        Some(cmp_64(other, self.abs() as u64, *self < 0).reverse())
+    fn is_negative(&self) -> bool {
+        self.sign() < 0
     }
 }
 
-macro_rules! impl_from_prim (
-    (signed $($t:ty),*) => {
-        $(impl ::std::convert::From<$t> for Int {
-            fn from(val: $t) -> Int {
-                if val == 0 {
-                    return Int::zero();
-                } if val == <$t>::min_value() {
-                    let shift = val.trailing_zeros() as usize;
-                    let mut i = Int::one();
-                    i = i << shift;
-                    return -i;
-                }
-                // Handle i64/u64 on 32-bit platforms.
-                if std::mem::size_of::<$t>() > std::mem::size_of::<BaseInt>() {
-                    let vabs = val.abs();
-                    let mask : BaseInt = !0;
-                    let vlow = vabs & (mask as $t);
-                    let vhigh = vabs.overflowing_shr(Limb::BITS as u32).0;
-
-                    let low_limb = Limb(vlow as BaseInt);
-                    let high_limb = Limb(vhigh as BaseInt);
-                    let mut i = Int::from_single_limb(low_limb);
-                    if high_limb != 0 {
-                        i.push(high_limb);
-                    }
-
-                    if val < 0 {
-                        i.size *= -1;
-                    }
-
-                    return i;
-                } else {
-                    let limb = Limb(val.abs() as BaseInt);
-                    let mut i = Int::from_single_limb(limb);
-                    if val < 0 {
-                        i.size *= -1;
-                    }
+macro_rules! impl_pow (
+    ($($t:ty),*) => {
+        $(impl ::num_traits::Pow<$t> for Int {
+            type Output = Int;
 
-                    return i;
-                }
+            #[inline]
+            fn pow(self, exp: $t) -> Int {
+                Int::pow(&self, exp as usize)
             }
-        })*
-    };
-    (unsigned $($t:ty),*) => {
-        $(impl ::std::convert::From<$t> for Int {
-            fn from(val: $t) -> Int {
-                if val == 0 {
-                    return Int::zero();
-                }
-                // Handle i64/u64 on 32-bit platforms.
-                if std::mem::size_of::<$t>() > std::mem::size_of::<BaseInt>() {
-                    let mask : BaseInt = !0;
-                    let vlow = val & (mask as $t);
-                    let vhigh = val.overflowing_shr(Limb::BITS as u32).0;
-
-                    let low_limb = Limb(vlow as BaseInt);
-                    let high_limb = Limb(vhigh as BaseInt);
-                    let mut i = Int::from_single_limb(low_limb);
-                    if high_limb != 0 {
-                        i.push(high_limb);
-                    }
+        }
 
-                    return i;
-                } else {
-                    let limb = Limb(val as BaseInt);
-                    return Int::from_single_limb(limb);
-                }
+        impl<'a> ::num_traits::Pow<$t> for &'a Int {
+            type Output = Int;
+
+            #[inline]
+            fn pow(self, exp: $t) -> Int {
+                Int::pow(self, exp as usize)
             }
         })*
     }
 );
 
-impl_from_prim!(signed   i8, i16, i32, i64, isize);
-impl_from_prim!(unsigned u8, u16, u32, u64, usize);
+impl_pow!(u8, u16, u32, usize);
 
-// Number formatting - There's not much difference between the impls,
-// hence the macro
+impl ::num_traits::CheckedAdd for Int {
+    #[inline]
+    fn checked_add(&self, other: &Int) -> Option<Int> {
+        Some(self + other)
+    }
+}
 
-macro_rules! impl_fmt (
-    ($t:path, $radix:expr, $upper:expr, $prefix:expr) => {
-        impl $t for Int {
-            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                let mut s : &str = &self.to_str_radix($radix, $upper);
-                let is_positive = self.sign() >= 0;
-                // to_str_radix adds the sign if `self` is negative, but
-                // pad_integral adds it's own sign, so slice the sign off
-                if !is_positive {
-                    s = &s[1..];
-                }
+impl ::num_traits::CheckedSub for Int {
+    #[inline]
+    fn checked_sub(&self, other: &Int) -> Option<Int> {
+        Some(self - other)
+    }
+}
 
-                f.pad_integral(is_positive, $prefix, s)
-            }
-        }
-    };
+impl ::num_traits::CheckedMul for Int {
+    #[inline]
+    fn checked_mul(&self, other: &Int) -> Option<Int> {
+        Some(self * other)
+    }
+}
 
-    ($t:path, $radix:expr, $prefix:expr) => {
-        impl_fmt!($t, $radix, false, $prefix);
+impl ::num_traits::CheckedDiv for Int {
+    #[inline]
+    fn checked_div(&self, other: &Int) -> Option<Int> {
+        if other.sign() == 0 {
+            None
+        } else {
+            Some(self / other)
+        }
     }
-);
+}
 
-impl_fmt!(fmt::Binary,    2, "0b");
-impl_fmt!(fmt::Octal,     8, "0o");
-impl_fmt!(fmt::Display,  10, "");
-impl_fmt!(fmt::Debug,    10, "");
-impl_fmt!(fmt::LowerHex, 16, false, "0x");
-impl_fmt!(fmt::UpperHex, 16, true, "0x");
+impl ::num_traits::ToPrimitive for Int {
+    fn to_i64(&self) -> Option<i64> {
+        i64::try_from(self).ok()
+    }
 
-// String parsing
+    fn to_u64(&self) -> Option<u64> {
+        u64::try_from(self).ok()
+    }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct ParseIntError { kind: ErrorKind }
+    fn to_i128(&self) -> Option<i128> {
+        i128::try_from(self).ok()
+    }
 
-#[derive(Debug, Clone, PartialEq)]
-enum ErrorKind {
-    Empty,
-    InvalidDigit
-}
+    fn to_u128(&self) -> Option<u128> {
+        u128::try_from(self).ok()
+    }
 
-impl Error for ParseIntError {
-    fn description<'a>(&'a self) -> &'a str {
-        match self.kind {
-            ErrorKind::Empty => "cannot parse empty string",
-            ErrorKind::InvalidDigit => "invalid digit found in string"
-        }
+    fn to_f32(&self) -> Option<f32> {
+        Some(Int::to_f32(self))
     }
-}
 
-impl fmt::Display for ParseIntError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.description().fmt(f)
+    fn to_f64(&self) -> Option<f64> {
+        Some(Int::to_f64(self))
     }
 }
 
-impl FromStr for Int {
-    type Err = ParseIntError;
+impl ::num_traits::FromPrimitive for Int {
+    fn from_i64(n: i64) -> Option<Int> {
+        Some(Int::from(n))
+    }
 
-    fn from_str(src: &str) -> Result<Int, ParseIntError> {
-        Int::from_str_radix(src, 10)
+    fn from_u64(n: u64) -> Option<Int> {
+        Some(Int::from(n))
     }
-}
 
-// Conversion *to* primitives via the From trait.
+    fn from_i128(n: i128) -> Option<Int> {
+        Some(Int::from(n))
+    }
 
-macro_rules! impl_from_for_prim (
-    (signed $($t:ty),*) => (
-        $(impl<'a> ::std::convert::From<&'a Int> for $t {
-            fn from(i: &'a Int) -> $t {
-                let sign = i.sign() as $t;
-                if sign == 0 {
-                    return 0;
-                }
-                if ::std::mem::size_of::<BaseInt>() < ::std::mem::size_of::<$t>() {
-                    // Handle conversion where BaseInt = u32 and $t = i64
-                    if i.abs_size() >= 2 { // Fallthrough if there's only one limb
-                        let lower = i.to_single_limb().0 as $t;
-                        let higher = unsafe { (*i.ptr.offset(1)).0 } as $t;
+    fn from_u128(n: u128) -> Option<Int> {
+        Some(Int::from(n))
+    }
 
-                        // Combine the two
-                        let n : $t = lower | higher.overflowing_shl(Limb::BITS as u32).0;
+    fn from_f64(n: f64) -> Option<Int> {
+        Int::from_f64_impl(n, false)
+    }
+}
 
-                        // Apply the sign
-                        return n.wrapping_mul(sign);
-                    }
-                }
-                let n = i.to_single_limb().0;
-                // Using wrapping_mul to account for when the binary
-                // representation of n == $t::MIN
-                return (n as $t).wrapping_mul(sign);
-            }
-        })*
-    );
-    (unsigned $($t:ty),*) => (
-        $(impl<'a> ::std::convert::From<&'a Int> for $t {
-            fn from(i: &'a Int) -> $t {
-                // This does the conversion ignoring the sign
+impl ::num_traits::NumCast for Int {
+    fn from<T: ::num_traits::ToPrimitive>(n: T) -> Option<Int> {
+        // Floats can be out of range of every primitive integer, so they go last
+        n.to_i128().map(From::from)
+            .or_else(|| n.to_u128().map(From::from))
+            .or_else(|| n.to_f64().and_then(|f| Int::from_f64_impl(f, false)))
+    }
+}
 
-                if i.sign() == 0 {
-                    return 0;
-                }
-                if ::std::mem::size_of::<BaseInt>() < ::std::mem::size_of::<$t>() {
-                    // Handle conversion where BaseInt = u32 and $t = u64
-                    if i.abs_size() >= 2 { // Fallthrough if there's only one limb
-                        let lower = i.to_single_limb().0 as $t;
-                        let higher = unsafe { (*i.ptr.offset(1)).0 } as $t;
+impl ::num_integer::Roots for Int {
+    /**
+     * Returns the truncated principal `n`th root.
+     *
+     * # Panics
+     *
+     * Panics if `n` is zero, or if `n` is even and self is negative.
+     */
+    fn nth_root(&self, n: u32) -> Int {
+        assert!(n > 0, "nth_root: n is zero");
+        if self.sign() < 0 {
+            assert!(n % 2 == 1, "nth_root: even root of a negative number");
+            return -(-self).nth_root(n);
+        }
+        if n == 1 || *self < 2 {
+            return self.clone();
+        }
+        if n == 2 {
+            return self.clone().sqrt_rem().unwrap().0;
+        }
 
-                        // Combine the two
-                        let n : $t = lower | higher.overflowing_shl(Limb::BITS as u32).0;
+        let bits = self.bit_length();
+        if n as usize >= bits {
+            return Int::one();
+        }
 
-                        return n;
-                    }
-                }
-                let n = i.to_single_limb().0;
-                return n as $t;
+        // Newton's method, starting above the root so it decreases to the floor of it
+        let n_minus_1 = Int::from(n - 1);
+        let mut x = Int::one() << bits.div_ceil(n as usize);
+        loop {
+            let y = (&n_minus_1 * &x + self / x.pow(n as usize - 1)) / Limb(n as BaseInt);
+            if y >= x {
+                return x;
             }
-        })*
-    )
-);
+            x = y;
+        }
+    }
 
-impl_from_for_prim!(signed   i8, i16, i32, i64, isize);
-impl_from_for_prim!(unsigned u8, u16, u32, u64, usize);
+    fn sqrt(&self) -> Int {
+        self.nth_root(2)
+    }
+}
 
 impl Zero for Int {
     fn zero() -> Int {
         Int {
-            ptr: unsafe { Unique::new(alloc::heap::EMPTY as *mut Limb) },
+            data: Storage { inline: [Limb(0); INLINE_LIMBS] },
             size: 0,
-            cap: 0
+            cap: INLINE_LIMBS
         }
     }
 
@@ -3638,23 +5828,40 @@ impl Num for Int {
 }
 
 impl Integer for Int {
-    #[inline]
+    // `/` and `%` truncate towards zero, so the floored versions adjust the result whenever
+    // the remainder has the opposite sign to the divisor
+
     fn div_floor(&self, other: &Int) -> Int {
-        self / other
+        let (q, r) = self.divmod(other);
+        if r.sign() != 0 && r.sign() != other.sign() {
+            q - Limb(1)
+        } else {
+            q
+        }
     }
 
-    #[inline]
     fn mod_floor(&self, other: &Int) -> Int {
-        self % other
+        let r = self % other;
+        if r.sign() != 0 && r.sign() != other.sign() {
+            r + other
+        } else {
+            r
+        }
     }
 
+    // Unlike `Int::gcd`, the trait methods follow `num-integer` in never returning a negative
+    // value, so they agree with the default `extended_gcd`
+
     #[inline]
     fn gcd(&self, other: &Int) -> Int {
-        self.gcd(other)
+        self.gcd(other).abs()
     }
 
     #[inline]
     fn lcm(&self, other: &Int) -> Int {
+        if self.is_zero() && other.is_zero() {
+            return Int::zero();
+        }
         self.lcm(other)
     }
 
@@ -3665,6 +5872,9 @@ impl Integer for Int {
 
     #[inline]
     fn is_multiple_of(&self, other: &Int) -> bool {
+        if other.is_zero() {
+            return self.is_zero();
+        }
         (self % other).is_zero()
     }
 
@@ -3684,48 +5894,270 @@ impl Integer for Int {
     }
 }
 
+// Needs the nightly-only iterator step API
+#[cfg(feature = "unstable")]
 impl std::iter::Step for Int {
-    fn step(&self, by: &Int) -> Option<Int> {
-        Some(self + by)
-    }
-
-    fn steps_between(start: &Int, end: &Int, by: &Int) -> Option<usize> {
-        if by.le(&0) { return None; }
-        let mut diff = (start - end).abs();
-        if by.ne(&1) {
-            diff = diff / by;
+    fn steps_between(start: &Int, end: &Int) -> (usize, Option<usize>) {
+        if start > end {
+            return (0, None);
         }
+        let diff = end - start;
 
         // Check to see if result fits in a usize
         if diff > !0usize {
-            None
+            (!0usize, None)
         } else {
-            Some(usize::from(&diff))
+            let n = diff.to_usize_wrapping();
+            (n, Some(n))
         }
     }
 
-    fn steps_between_by_one(start: &Self, end: &Self) -> Option<usize> {
-        Self::steps_between(start, end, &Self::one())
+    fn forward_checked(start: Int, count: usize) -> Option<Int> {
+        Some(start + count)
     }
 
-    fn is_negative(&self) -> bool {
-        self.sign() < 0
+    fn backward_checked(start: Int, count: usize) -> Option<Int> {
+        Some(start - count)
+    }
+}
+
+// Serde support
+//
+// Human-readable formats get a decimal string. Binary formats get a
+// `(sign, magnitude)` tuple, with the sign as an `i8` and the magnitude as
+// little-endian bytes.
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for Int {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            let mut tup = serializer.serialize_tuple(2)?;
+            tup.serialize_element(&(self.sign() as i8))?;
+            tup.serialize_element(&MagnitudeBytes(self.abs_bytes_le()))?;
+            tup.end()
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Int {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Int, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(IntVisitor)
+        } else {
+            deserializer.deserialize_tuple(2, IntVisitor)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+struct IntVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::de::Visitor<'de> for IntVisitor {
+    type Value = Int;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a decimal integer string or a (sign, magnitude) tuple")
+    }
+
+    fn visit_str<E: ::serde::de::Error>(self, v: &str) -> Result<Int, E> {
+        Int::from_str(v).map_err(E::custom)
+    }
+
+    fn visit_seq<A: ::serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Int, A::Error> {
+        use serde::de::Error;
+
+        let sign: i8 = match seq.next_element()? {
+            Some(s) => s,
+            None => return Err(A::Error::invalid_length(0, &self)),
+        };
+        let MagnitudeBytes(bytes) = match seq.next_element()? {
+            Some(b) => b,
+            None => return Err(A::Error::invalid_length(1, &self)),
+        };
+
+        let mag = Int::from_bytes_le(&bytes);
+        match (sign, mag.sign()) {
+            (0, 0) | (1, 1) => Ok(mag),
+            (-1, 1) => Ok(-mag),
+            _ => Err(A::Error::custom("sign does not match magnitude")),
+        }
+    }
+}
+
+// Byte buffer that goes through `serialize_bytes`/`deserialize_byte_buf`,
+// rather than being treated as a sequence of `u8`s.
+#[cfg(feature = "serde")]
+struct MagnitudeBytes(Vec<u8>);
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for MagnitudeBytes {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for MagnitudeBytes {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D)
+                                                  -> Result<MagnitudeBytes, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> ::serde::de::Visitor<'de> for BytesVisitor {
+            type Value = MagnitudeBytes;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a byte array")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<MagnitudeBytes, E> {
+                Ok(MagnitudeBytes(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<MagnitudeBytes, E> {
+                Ok(MagnitudeBytes(v))
+            }
+
+            fn visit_seq<A: ::serde::de::SeqAccess<'de>>(self, mut seq: A)
+                                                         -> Result<MagnitudeBytes, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(b) = seq.next_element()? {
+                    bytes.push(b);
+                }
+                Ok(MagnitudeBytes(bytes))
+            }
+        }
+
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+// Conversions to and from other bignum libraries
+//
+// These go through the little-endian bytes of the magnitude, so they take
+// linear time and never format the value as a string.
+
+#[cfg(feature = "num-bigint")]
+impl<'a> From<&'a ::num_bigint::BigUint> for Int {
+    fn from(n: &'a ::num_bigint::BigUint) -> Int {
+        Int::from_bytes_le(&n.to_bytes_le())
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl From<::num_bigint::BigUint> for Int {
+    #[inline]
+    fn from(n: ::num_bigint::BigUint) -> Int {
+        Int::from(&n)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl<'a> From<&'a ::num_bigint::BigInt> for Int {
+    fn from(n: &'a ::num_bigint::BigInt) -> Int {
+        let (sign, bytes) = n.to_bytes_le();
+        let mut i = Int::from_bytes_le(&bytes);
+        if sign == ::num_bigint::Sign::Minus {
+            i.negate();
+        }
+        i
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl From<::num_bigint::BigInt> for Int {
+    #[inline]
+    fn from(n: ::num_bigint::BigInt) -> Int {
+        Int::from(&n)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl<'a> From<&'a Int> for ::num_bigint::BigInt {
+    fn from(i: &'a Int) -> ::num_bigint::BigInt {
+        let sign = if i.sign() < 0 { ::num_bigint::Sign::Minus } else { ::num_bigint::Sign::Plus };
+        ::num_bigint::BigInt::from_bytes_le(sign, &i.abs_bytes_le())
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl From<Int> for ::num_bigint::BigInt {
+    #[inline]
+    fn from(i: Int) -> ::num_bigint::BigInt {
+        ::num_bigint::BigInt::from(&i)
+    }
+}
+
+/// Fails for negative values.
+#[cfg(feature = "num-bigint")]
+impl<'a> TryFrom<&'a Int> for ::num_bigint::BigUint {
+    type Error = TryFromIntError;
+
+    fn try_from(i: &'a Int) -> Result<::num_bigint::BigUint, TryFromIntError> {
+        if i.sign() < 0 {
+            Err(TryFromIntError { _priv: () })
+        } else {
+            Ok(::num_bigint::BigUint::from_bytes_le(&i.abs_bytes_le()))
+        }
+    }
+}
+
+/// Fails for negative values.
+#[cfg(feature = "num-bigint")]
+impl TryFrom<Int> for ::num_bigint::BigUint {
+    type Error = TryFromIntError;
+
+    #[inline]
+    fn try_from(i: Int) -> Result<::num_bigint::BigUint, TryFromIntError> {
+        ::num_bigint::BigUint::try_from(&i)
     }
+}
+
+// rust-gmp converts `Mpz` to and from big-endian bytes of the magnitude, so
+// the sign is carried across separately.
 
-    fn replace_one(&mut self) -> Self {
-        mem::replace(self, Self::one())
+#[cfg(feature = "rust-gmp")]
+impl<'a> From<&'a ::gmp::mpz::Mpz> for Int {
+    fn from(n: &'a ::gmp::mpz::Mpz) -> Int {
+        let bytes: Vec<u8> = n.into();
+        let mut i = Int::from_bytes_be(&bytes);
+        if n.sign() == ::gmp::sign::Sign::Negative {
+            i.negate();
+        }
+        i
     }
+}
 
-    fn replace_zero(&mut self) -> Self {
-        mem::replace(self, Self::zero())
+#[cfg(feature = "rust-gmp")]
+impl From<::gmp::mpz::Mpz> for Int {
+    #[inline]
+    fn from(n: ::gmp::mpz::Mpz) -> Int {
+        Int::from(&n)
     }
+}
 
-    fn add_one(&self) -> Self {
-        self + 1
+#[cfg(feature = "rust-gmp")]
+impl<'a> From<&'a Int> for ::gmp::mpz::Mpz {
+    fn from(i: &'a Int) -> ::gmp::mpz::Mpz {
+        let n = ::gmp::mpz::Mpz::from(&i.to_bytes_be()[..]);
+        if i.sign() < 0 {
+            -n
+        } else {
+            n
+        }
     }
+}
 
-    fn sub_one(&self) -> Self {
-        self - 1
+#[cfg(feature = "rust-gmp")]
+impl From<Int> for ::gmp::mpz::Mpz {
+    #[inline]
+    fn from(i: Int) -> ::gmp::mpz::Mpz {
+        ::gmp::mpz::Mpz::from(&i)
     }
 }
 
@@ -3746,6 +6178,7 @@ impl std::iter::Step for Int {
 ///     let big_i = rng.gen_int(256);
 /// }
 /// ```
+#[cfg(feature = "std")]
 pub trait RandomInt {
     /// Generate a random unsigned `Int` of given bit size.
     fn gen_uint(&mut self, bits: usize) -> Int;
@@ -3760,12 +6193,13 @@ pub trait RandomInt {
     fn gen_int_range(&mut self, lbound: &Int, ubound: &Int) -> Int;
 }
 
+#[cfg(feature = "std")]
 impl<R: Rng> RandomInt for R {
     fn gen_uint(&mut self, bits: usize) -> Int {
         assert!(bits > 0);
 
-        let limbs = (bits / &Limb::BITS) as u32;
-        let rem = bits % &Limb::BITS;
+        let limbs = bits / Limb::BITS;
+        let rem = bits % Limb::BITS;
 
         let mut i = Int::with_capacity(limbs + 1);
 
@@ -3776,7 +6210,7 @@ impl<R: Rng> RandomInt for R {
 
         if rem > 0 {
             let final_limb = Limb(self.gen());
-            i.push(final_limb >> (&Limb::BITS - rem));
+            i.push(final_limb >> (Limb::BITS - rem));
         }
 
         i.normalize();
@@ -3787,13 +6221,13 @@ impl<R: Rng> RandomInt for R {
     fn gen_int(&mut self, bits: usize) -> Int {
         let i = self.gen_uint(bits);
 
-        let r = if i == Int::zero() {
+        if i == Int::zero() {
             // ...except that if the BigUint is zero, we need to try
             // again with probability 0.5. This is because otherwise,
             // the probability of generating a zero BigInt would be
             // double that of any other number.
             if self.gen() {
-                return self.gen_uint(bits);
+                self.gen_uint(bits)
             } else {
                 i
             }
@@ -3801,9 +6235,7 @@ impl<R: Rng> RandomInt for R {
             -i
         } else {
             i
-        };
-
-        r
+        }
     }
 
     fn gen_uint_below(&mut self, bound: &Int) -> Int {
@@ -3813,7 +6245,7 @@ impl<R: Rng> RandomInt for R {
         // happening, in the worst case.
         const ITER_LIMIT : usize = 10000;
 
-        let bits = bound.bit_length() as usize;
+        let bits = bound.bit_length();
 
         // Since it uses a number of bits, gen_uint may return a number too large,
         // loop until we generate a valid number.
@@ -3839,14 +6271,19 @@ impl<R: Rng> RandomInt for R {
 
 #[cfg(test)]
 mod test {
+    #[cfg(feature = "std")]
     use std;
+    #[cfg(feature = "std")]
     use std::hash::{Hash, Hasher};
+    #[cfg(feature = "std")]
     use rand::{self, Rng};
+    #[cfg(feature = "unstable")]
     use test::{self, Bencher};
     use super::*;
     use ll::limb::Limb;
     use traits::DivRem;
     use std::str::FromStr;
+    use std::convert::TryFrom;
 
     macro_rules! assert_mp_eq (
         ($l:expr, $r:expr) => (
@@ -3854,8 +6291,8 @@ mod test {
                 let l : &Int = &$l;
                 let r : &Int = &$r;
                 if l != r {
-                    println!("assertion failed: {} == {}", stringify!($l), stringify!($r));
-                    panic!("{:} != {:}", l, r);
+                    panic!("assertion failed: {} == {}\n{:} != {:}",
+                           stringify!($l), stringify!($r), l, r);
                 }
             }
         )
@@ -3930,6 +6367,229 @@ mod test {
         }
     }
 
+    #[test]
+    fn format_flags() {
+        let i = Int::from(-255);
+        assert_eq!(format!("{:8}", i), "    -255");
+        assert_eq!(format!("{:<8}|", i), "-255    |");
+        assert_eq!(format!("{:*^9}", i), "**-255***");
+        assert_eq!(format!("{:08}", i), "-0000255");
+        assert_eq!(format!("{:+}", Int::from(255)), "+255");
+        assert_eq!(format!("{:#x}", i), "-0xff");
+        assert_eq!(format!("{:#010X}", Int::from(255)), "0x000000FF");
+        assert_eq!(format!("{:#b}", Int::from(5)), "0b101");
+        assert_eq!(format!("{:>5?}", Int::from(7)), "    7");
+    }
+
+    #[test]
+    fn format_builder() {
+        let i = Int::from_str("-1234567890123").unwrap();
+        assert_eq!(i.format().to_string(), i.to_string());
+        assert_eq!(i.format().group(',', 3).to_string(), "-1,234,567,890,123");
+        assert_eq!(i.format().group('_', 4).to_string(), "-1_2345_6789_0123");
+        assert_eq!(Int::from(123).format().group(',', 3).to_string(), "123");
+        assert_eq!(Int::zero().format().group(',', 3).to_string(), "0");
+        assert_eq!(format!("{:>12}", Int::from(1000000).format().group(',', 3)), "   1,000,000");
+        assert_eq!(format!("{:+}", Int::from(1000).format().group(',', 3)), "+1,000");
+
+        assert_eq!(Int::from(0xdeadbeefu32).format().radix(16).upper(true).group('_', 4).to_string(),
+                   "DEAD_BEEF");
+        assert_eq!(format!("{:#}", Int::from(10).format().radix(2)), "0b1010");
+        assert_eq!(Int::from(35).format().radix(36).to_string(), "z");
+
+        assert_eq!(i.format().scientific(4).to_string(), "-1.235e12");
+        assert_eq!(i.format().scientific(1).to_string(), "-1e12");
+        assert_eq!(Int::from(999).format().scientific(2).to_string(), "1.0e3");
+        assert_eq!(Int::from(125).format().scientific(2).to_string(), "1.3e2");
+        assert_eq!(Int::from(124).format().scientific(2).to_string(), "1.2e2");
+        assert_eq!(Int::from(7).format().scientific(3).to_string(), "7.00e0");
+        assert_eq!(Int::zero().format().scientific(2).to_string(), "0.0e0");
+        assert_eq!(Int::from(0xfff).format().radix(16).scientific(2).to_string(), "1.0@3");
+        assert_eq!(format!("{:>10}", Int::from(-31415).format().scientific(3)), "   -3.14e4");
+    }
+
+    #[test]
+    fn int_parser() {
+        let lenient = IntParser::lenient();
+        let cases = [
+            ("0x_dead_BEEF", 0xdeadbeefi64),
+            ("-0o17", -0o17),
+            ("+0B101", 5),
+            ("  \t42\n", 42),
+            ("1_000_", 1000),
+            ("-0", 0),
+            ("007", 7),
+        ];
+        for &(s, n) in cases.iter() {
+            assert_mp_eq!(lenient.parse(s).unwrap(), Int::from(n));
+        }
+
+        // Prefixes only apply when they can't be digits
+        let hex = IntParser::new().radix(16).prefixes(true);
+        assert_mp_eq!(hex.parse("0b1").unwrap(), Int::from(0xb1));
+        assert_mp_eq!(hex.parse("0xb1").unwrap(), Int::from(0xb1));
+        assert_mp_eq!(hex.parse("0o10").unwrap(), Int::from(8));
+
+        let errors = [
+            ("+1", Some(0)),
+            ("1_0", Some(1)),
+            (" 1", Some(0)),
+            ("0x10", Some(1)),
+            ("12a", Some(2)),
+            ("", None),
+            ("-", None),
+        ];
+        for &(s, offset) in errors.iter() {
+            let err = IntParser::new().parse(s).unwrap_err();
+            assert_eq!(err.offset(), offset);
+            assert_eq!(Int::from_str(s).unwrap_err(), err);
+        }
+
+        let errors = [
+            ("_1", Some(0)),
+            ("  1 2", Some(3)),
+            ("0x", None),
+            ("+-1", Some(1)),
+            ("0b102", Some(4)),
+            ("  ", None),
+        ];
+        for &(s, offset) in errors.iter() {
+            assert_eq!(lenient.parse(s).unwrap_err().offset(), offset);
+        }
+
+        assert_eq!(Int::from_str("12x").unwrap_err().to_string(),
+                   "invalid digit found in string at byte 2");
+    }
+
+    // Hands out its data a few bytes at a time
+    #[cfg(feature = "std")]
+    struct Trickle<'a>(&'a [u8], usize);
+
+    #[cfg(feature = "std")]
+    impl<'a> io::Read for Trickle<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.1 = self.1 % 5 + 1;
+            let n = std::cmp::min(std::cmp::min(self.1, buf.len()), self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_radix() {
+        let mut rng = rand::thread_rng();
+        for base in 2..37 {
+            for &bits in &[1, 7, 64, 65, 300, 5000] {
+                let i = rng.gen_int(bits);
+                let s = i.to_str_radix(base, base % 2 == 0);
+                let read = Int::read_radix(&mut Trickle(s.as_bytes(), 0), base).unwrap();
+                assert_mp_eq!(read, i);
+                assert_mp_eq!(Int::read_radix(&mut s.as_bytes(), base).unwrap(), i);
+            }
+        }
+
+        let zeros = "0".repeat(100) + "1f";
+        assert_mp_eq!(Int::read_radix(&mut zeros.as_bytes(), 16).unwrap(), Int::from(0x1f));
+        assert_mp_eq!(Int::read_radix(&mut zeros.as_bytes(), 36).unwrap(), Int::from(51));
+        assert_mp_eq!(Int::read_radix(&mut &b"-0"[..], 10).unwrap(), Int::zero());
+
+        for &(s, offset) in &[("12x4", Some(2)), ("--1", Some(1)), ("", None), ("-", None)] {
+            let err = Int::read_radix(&mut s.as_bytes(), 10).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            let err = err.into_inner().unwrap();
+            assert_eq!(err.downcast_ref::<ParseIntError>().unwrap().offset(), offset);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn write_radix() {
+        struct Fail(usize);
+        impl io::Write for Fail {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                if self.0 < buf.len() {
+                    return Err(io::Error::other("full"));
+                }
+                self.0 -= buf.len();
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> io::Result<()> { Ok(()) }
+        }
+
+        let i = -(Int::from(7).pow(2000));
+        let s = i.to_str_radix(10, false);
+        let mut out = Vec::new();
+        i.write_radix(&mut out, 10, false).unwrap();
+        assert_eq!(out, s.as_bytes());
+
+        assert!(i.write_radix(&mut Fail(s.len()), 10, false).is_ok());
+        assert!(i.write_radix(&mut Fail(s.len() - 1), 10, false).is_err());
+        assert!(i.write_radix(&mut Fail(10), 16, false).is_err());
+    }
+
+    #[test]
+    fn digits() {
+        assert_eq!(Int::zero().digits(10).collect::<Vec<_>>(), [0]);
+        assert_eq!(Int::from(-255).digits(16).collect::<Vec<_>>(), [15, 15]);
+        assert_eq!(Int::from(0x1234).digits(256).rev().collect::<Vec<_>>(), [0x34, 0x12]);
+        assert_eq!(Int::from(0x1_0000_0002u64).digits(1 << 32).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(Int::from_digits(&[], 10), 0);
+        assert_eq!(Int::from_digits(&[0, 0, 4, 2], 10), 42);
+        assert_eq!(Int::from_digits(&[1, 2], 1 << 32), Int::from(0x1_0000_0002u64));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn digits_rand() {
+        let mut rng = rand::thread_rng();
+        let bases = [2, 3, 7, 8, 10, 16, 32, 36, 255, 256, 257, 1000, 1 << 20, 12345678,
+                     (1 << 31) + 11, (1 << 32) - 1, 1 << 32];
+        for &base in bases.iter() {
+            for &bits in &[1, 31, 64, 65, 500, 3000] {
+                let i = rng.gen_int(bits);
+                let digits = i.digits(base).collect::<Vec<_>>();
+                assert_eq!(i.digits(base).len(), digits.len());
+
+                // Check against repeated division
+                let mut n = i.clone().abs();
+                for &d in digits.iter().rev() {
+                    let (q, r) = n.divmod(&Int::from(base));
+                    assert_eq!(r, Int::from(d));
+                    n = q;
+                }
+                assert_eq!(n, 0);
+                assert!(digits.len() == 1 || digits[0] != 0);
+
+                assert_mp_eq!(Int::from_digits(&digits, base), i.clone().abs());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_digits_invalid() {
+        Int::from_digits(&[1, 10], 10);
+    }
+
+    #[test]
+    fn limbs_roundtrip() {
+        assert!(Int::zero().as_limbs().is_empty());
+        assert_mp_eq!(Int::from_limbs(&[Limb(0), Limb(0)]), Int::zero());
+
+        let a = Int::from_str("-123456789012345678901234567890123456789").unwrap();
+        let b = Int::from_limbs(a.as_limbs());
+        assert_mp_eq!(b, a.clone().abs());
+        assert_eq!(b.as_limbs(), a.as_limbs());
+
+        // The product of two numbers, computed with the slice API
+        let c = Int::from_str("987654321987654321987654321").unwrap();
+        let mut w = vec![Limb(0); a.as_limbs().len() + c.as_limbs().len()];
+        ::ll::safe::mul(&mut w, a.as_limbs(), c.as_limbs());
+        assert_mp_eq!(Int::from_limbs(&w), (&a * &c).abs());
+    }
+
     #[test]
     fn num_base_digits_pow2() {
         use ::ll::base::num_base_digits;
@@ -4005,6 +6665,112 @@ mod test {
         }
     }
 
+    #[test]
+    fn try_alloc() {
+        let big = usize::MAX;
+        assert!(Int::from(3).try_pow(big).is_err());
+        assert!(Int::from(3).try_pow(big / 2).is_err());
+        assert_eq!(Int::from(-1).try_pow(big).unwrap(), -1);
+        assert_eq!(Int::one().pow(big), 1);
+        assert_eq!(Int::zero().try_pow(big).unwrap(), 0);
+        assert_mp_eq!(Int::from(3).try_pow(100).unwrap(), Int::from(3).pow(100));
+        assert_mp_eq!(Int::from(-3).try_pow(2).unwrap(), Int::from(9));
+
+        let x: Int = "-431343873217510631841".parse().unwrap();
+        assert!(x.try_shl(big).is_err());
+        assert_mp_eq!(x.try_shl(300).unwrap(), x.clone() << 300);
+        assert_mp_eq!(x.try_mul(&x).unwrap(), &x * &x);
+        assert_mp_eq!(x.try_mul(&Int::zero()).unwrap(), Int::zero());
+
+        let mut y = x.clone();
+        assert!(y.try_reserve(big).is_err());
+        assert!(y.try_reserve(isize::MAX as usize).is_err());
+        assert_mp_eq!(y, x);
+        y.try_reserve(100).unwrap();
+        assert!(y.cap >= 100);
+        assert_mp_eq!(y, x);
+    }
+
+    #[test]
+    fn try_mul_scratch() {
+        // Unbalanced sizes take the deepest paths through the scratch space
+        let sizes = [(30, 21), (100, 61), (300, 185), (500, 22), (777, 400), (1440, 890)];
+        for &(xs, ys) in sizes.iter() {
+            let x = (Int::one() << (xs * Limb::BITS)) / Int::from(7);
+            let y = (Int::one() << (ys * Limb::BITS)) / Int::from(11) + 1;
+            let p = x.try_mul(&y).unwrap();
+            assert_mp_eq!(p.clone(), &x * &y);
+            let (q, r) = p.divmod(&y);
+            assert_mp_eq!(q, x);
+            assert_eq!(r, 0);
+        }
+
+        let x = (Int::one() << (40 * Limb::BITS)) / Int::from(7);
+        assert_mp_eq!(x.try_pow(2).unwrap(), &x * &x);
+        assert_mp_eq!(x.try_pow(13).unwrap(), x.pow(12) * &x);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn pow_overflow() {
+        Int::from(3).pow(usize::MAX / 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "allocation failed")]
+    fn shl_overflow() {
+        let _ = Int::from(3) << (usize::MAX / 2);
+    }
+
+    // Without `std` the limb limit is shared by every thread, so the tests that
+    // set it only run on their own: `cargo test -- --ignored --test-threads=1`
+    struct ResetLimbLimit;
+
+    impl Drop for ResetLimbLimit {
+        fn drop(&mut self) {
+            Int::set_limb_limit(None);
+        }
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "std"), ignore)]
+    fn limb_limit() {
+        assert_eq!(Int::limb_limit(), None);
+        let _reset = ResetLimbLimit;
+        Int::set_limb_limit(Some(4));
+        assert_eq!(Int::limb_limit(), Some(4));
+
+        let x = Int::from(3);
+        let e = x.try_pow(1000).unwrap_err();
+        assert!(e.is_limit_exceeded());
+        assert!(x.try_shl(Limb::BITS * 4).unwrap_err().is_limit_exceeded());
+        assert_mp_eq!(x.try_shl(Limb::BITS + 36).unwrap(), Int::from(3) << (Limb::BITS + 36));
+        assert_mp_eq!(x.try_pow(40).unwrap(), Int::from(3).pow(40));
+
+        let digits = "1".repeat(200);
+        let e = Int::from_str_radix(&digits, 10).unwrap_err();
+        assert_eq!(e.to_string(), "number exceeds the limb limit");
+        assert!(Int::from_str_radix("12345678901234567890", 10).is_ok());
+
+        // A result that would only fail to allocate is refused up front
+        Int::set_limb_limit(Some(1 << 20));
+        assert!(x.try_pow(1 << 40).unwrap_err().is_limit_exceeded());
+
+        Int::set_limb_limit(None);
+        assert_eq!(Int::limb_limit(), None);
+        assert!(x.try_pow(1000).is_ok());
+        assert!(Int::from_str_radix(&digits, 10).is_ok());
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "std"), ignore)]
+    #[should_panic(expected = "limb limit exceeded")]
+    fn limb_limit_panic() {
+        let _reset = ResetLimbLimit;
+        Int::set_limb_limit(Some(8));
+        let _ = Int::one() << 10000;
+    }
+
     #[test]
     fn add() {
         let cases = [
@@ -4096,6 +6862,419 @@ mod test {
         }
     }
 
+    #[test]
+    fn add_mul() {
+        let cases = [
+            ("0", "0", "0", "0"),
+            ("5", "0", "7", "5"),
+            ("0", "3", "4", "12"),
+            ("5", "3", "4", "17"),
+            ("5", "-3", "4", "-7"),
+            ("-5", "3", "4", "7"),
+            ("12", "3", "-4", "0"),
+            ("1", "18446744073709551616", "18446744073709551616",
+             "340282366920938463463374607431768211457"),
+            ("-340282366920938463463374607431768211456", "18446744073709551616", "18446744073709551617",
+             "18446744073709551616"),
+            ("18446744073709551616", "-123456789", "340282366920938463463374607431768211456",
+             "-42010168373378879565782048119214895904921223168"),
+        ];
+
+        for &(acc, a, b, res) in cases.iter() {
+            let acc : Int = acc.parse().unwrap();
+            let a : Int = a.parse().unwrap();
+            let b : Int = b.parse().unwrap();
+            let res : Int = res.parse().unwrap();
+
+            let mut x = acc.clone();
+            x.add_mul(&a, &b);
+            assert_mp_eq!(x, res);
+
+            let mut x = -&acc;
+            x.sub_mul(&a, &b);
+            assert_mp_eq!(x, -&res);
+
+            let mut x = Int::zero();
+            Int::mul_into(&mut x, &a, &b);
+            assert_mp_eq!(x, &res - &acc);
+        }
+    }
+
+    #[test]
+    fn add_mul_limb() {
+        let cases = [
+            ("0", "0", 7, "0"),
+            ("5", "3", 0, "5"),
+            ("5", "3", 4, "17"),
+            ("-5", "3", 4, "7"),
+            ("5", "-3", 4, "-7"),
+            ("-12", "3", 4, "0"),
+            ("-18446744073709551616", "18446744073709551616", 2, "18446744073709551616"),
+            ("1", "-340282366920938463463374607431768211456", 3,
+             "-1020847100762815390390123822295304634367"),
+        ];
+
+        for &(acc, a, b, res) in cases.iter() {
+            let acc : Int = acc.parse().unwrap();
+            let a : Int = a.parse().unwrap();
+            let b = Limb(b);
+            let res : Int = res.parse().unwrap();
+
+            let mut x = acc.clone();
+            x.add_mul_limb(&a, b);
+            assert_mp_eq!(x, res);
+
+            let mut x = -&acc;
+            x.sub_mul_limb(&a, b);
+            assert_mp_eq!(x, -&res);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn add_mul_rand() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..RAND_ITER {
+            let acc = rng.gen_int(1280);
+            let a = rng.gen_int(640);
+            let b = rng.gen_int(320);
+
+            let mut x = acc.clone();
+            x.add_mul(&a, &b);
+            assert_mp_eq!(x, &acc + &a * &b);
+
+            let mut x = acc.clone();
+            x.sub_mul(&b, &a);
+            assert_mp_eq!(x, &acc - &a * &b);
+
+            let mut x = acc.clone();
+            Int::add_into(&mut x, &a, &b);
+            assert_mp_eq!(x, &a + &b);
+
+            Int::sub_into(&mut x, &a, &b);
+            assert_mp_eq!(x, &a - &b);
+        }
+    }
+
+    #[test]
+    fn bytes() {
+        let cases: &[(&str, &[u8], &[u8])] = &[
+            // (hex, magnitude big-endian, signed big-endian)
+            ("0", &[0], &[0]),
+            ("1", &[1], &[1]),
+            ("-1", &[1], &[0xff]),
+            ("7f", &[0x7f], &[0x7f]),
+            ("80", &[0x80], &[0, 0x80]),
+            ("-80", &[0x80], &[0x80]),
+            ("-81", &[0x81], &[0xff, 0x7f]),
+            ("ff", &[0xff], &[0, 0xff]),
+            ("-100", &[1, 0], &[0xff, 0]),
+            ("123456789abcdef0123", &[1, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23],
+             &[1, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23]),
+            ("-10000000000000000", &[1, 0, 0, 0, 0, 0, 0, 0, 0],
+             &[0xff, 0, 0, 0, 0, 0, 0, 0, 0]),
+        ];
+
+        for &(hex, mag, signed) in cases {
+            let i = Int::from_str_radix(hex, 16).unwrap();
+            let abs = i.clone().abs();
+
+            assert_eq!(i.to_bytes_be(), mag);
+            assert_eq!(i.to_signed_bytes_be(), signed);
+            assert_mp_eq!(Int::from_bytes_be(mag), abs.clone());
+            assert_mp_eq!(Int::from_signed_bytes_be(signed), i.clone());
+
+            let mut mag_le = mag.to_vec();
+            mag_le.reverse();
+            let mut signed_le = signed.to_vec();
+            signed_le.reverse();
+            assert_eq!(i.to_bytes_le(), mag_le);
+            assert_eq!(i.to_signed_bytes_le(), signed_le);
+            assert_mp_eq!(Int::from_bytes_le(&mag_le), abs.clone());
+            assert_mp_eq!(Int::from_signed_bytes_le(&signed_le), i.clone());
+        }
+
+        // Leading zeros and sign extension are accepted
+        assert_mp_eq!(Int::from_bytes_be(&[0, 0, 0, 1, 2]), Int::from(0x102));
+        assert_mp_eq!(Int::from_bytes_be(&[]), Int::zero());
+        assert_mp_eq!(Int::from_signed_bytes_be(&[0xff, 0xff, 0xfe]), Int::from(-2));
+        assert_mp_eq!(Int::from_signed_bytes_le(&[]), Int::zero());
+
+        let x = Int::from(0x1234);
+        assert_eq!(x.to_bytes_be_padded(4), Some(vec![0, 0, 0x12, 0x34]));
+        assert_eq!(x.to_bytes_le_padded(3), Some(vec![0x34, 0x12, 0]));
+        assert_eq!(x.to_bytes_be_padded(2), Some(vec![0x12, 0x34]));
+        assert_eq!(x.to_bytes_be_padded(1), None);
+        assert_eq!(Int::zero().to_bytes_be_padded(0), Some(vec![]));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        use serde_json;
+        use bincode;
+
+        let cases = ["0", "1", "-1", "255", "-256", "18446744073709551616",
+                     "-123456789012345678901234567890123456789"];
+        for s in &cases {
+            let i = Int::from_str(s).unwrap();
+
+            let json = serde_json::to_string(&i).unwrap();
+            assert_eq!(json, format!("\"{}\"", s));
+            assert_mp_eq!(serde_json::from_str::<Int>(&json).unwrap(), i);
+
+            let bin = bincode::serialize(&i).unwrap();
+            assert_mp_eq!(bincode::deserialize::<Int>(&bin).unwrap(), i);
+        }
+
+        // One sign byte, then the length-prefixed magnitude
+        assert_eq!(bincode::serialize(&Int::from(-0x1234)).unwrap(),
+                   vec![0xff, 2, 0, 0, 0, 0, 0, 0, 0, 0x34, 0x12]);
+
+        assert!(serde_json::from_str::<Int>("\"12a\"").is_err());
+        assert!(serde_json::from_str::<Int>("12").is_err());
+        assert!(bincode::deserialize::<Int>(&[0, 1, 0, 0, 0, 0, 0, 0, 0, 5]).is_err());
+        assert!(bincode::deserialize::<Int>(&[2, 1, 0, 0, 0, 0, 0, 0, 0, 5]).is_err());
+        assert!(bincode::deserialize::<Int>(&[1, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn der() {
+        let cases: &[(&str, &[u8])] = &[
+            ("0", &[0x02, 0x01, 0x00]),
+            ("127", &[0x02, 0x01, 0x7f]),
+            ("128", &[0x02, 0x02, 0x00, 0x80]),
+            ("256", &[0x02, 0x02, 0x01, 0x00]),
+            ("-1", &[0x02, 0x01, 0xff]),
+            ("-128", &[0x02, 0x01, 0x80]),
+            ("-129", &[0x02, 0x02, 0xff, 0x7f]),
+            ("65537", &[0x02, 0x03, 0x01, 0x00, 0x01]),
+        ];
+        for &(s, der) in cases {
+            let i = Int::from_str(s).unwrap();
+            assert_eq!(i.to_der(), der);
+            assert_mp_eq!(Int::from_der(der).unwrap(), i);
+        }
+
+        // Long form lengths
+        let big = (Int::one() << 1023) + Limb(1);
+        let der = big.to_der();
+        assert_eq!(&der[..4], &[0x02, 0x81, 0x81, 0x00]);
+        assert_eq!(der.len(), 4 + 128);
+        assert_mp_eq!(Int::from_der(&der).unwrap(), big);
+
+        let big = -(Int::one() << 4000);
+        let der = big.to_der();
+        assert_eq!(&der[..5], &[0x02, 0x82, 0x01, 0xf5, 0xff]);
+        assert_mp_eq!(Int::from_der(&der).unwrap(), big);
+
+        let bad: &[&[u8]] = &[
+            &[],
+            &[0x02],
+            &[0x04, 0x01, 0x00],
+            &[0x02, 0x00],
+            &[0x02, 0x02, 0x00],
+            &[0x02, 0x01, 0x00, 0x00],
+            &[0x02, 0x02, 0x00, 0x7f],
+            &[0x02, 0x02, 0xff, 0x80],
+            &[0x02, 0x80, 0x01, 0x00, 0x00],
+            &[0x02, 0x81, 0x01, 0x05],
+            &[0x02, 0x82, 0x00, 0x01, 0x05],
+            &[0x02, 0x89, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+        ];
+        for der in bad {
+            assert!(Int::from_der(der).is_err(), "{:?} was accepted", der);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn wire_formats() {
+        // Examples from RFC 4251, section 5
+        let ssh: &[(&str, &[u8])] = &[
+            ("0", &[0, 0, 0, 0]),
+            ("9a378f9b2e332a7", &[0, 0, 0, 8, 0x09, 0xa3, 0x78, 0xf9, 0xb2, 0xe3, 0x32, 0xa7]),
+            ("80", &[0, 0, 0, 2, 0x00, 0x80]),
+            ("-1234", &[0, 0, 0, 2, 0xed, 0xcc]),
+            ("-deadbeef", &[0, 0, 0, 5, 0xff, 0x21, 0x52, 0x41, 0x11]),
+        ];
+        for &(hex, bytes) in ssh {
+            let i = Int::from_str_radix(hex, 16).unwrap();
+            let mut buf = Vec::new();
+            i.write_ssh_mpint(&mut buf).unwrap();
+            assert_eq!(buf, bytes);
+            assert_mp_eq!(Int::read_ssh_mpint(&mut &bytes[..]).unwrap(), i);
+        }
+
+        let pgp: &[(&str, &[u8])] = &[
+            ("0", &[0, 0]),
+            ("1", &[0, 1, 1]),
+            ("1ff", &[0, 9, 1, 0xff]),
+            ("8000", &[0, 16, 0x80, 0x00]),
+        ];
+        for &(hex, bytes) in pgp {
+            let i = Int::from_str_radix(hex, 16).unwrap();
+            let mut buf = Vec::new();
+            i.write_pgp_mpi(&mut buf).unwrap();
+            assert_eq!(buf, bytes);
+            assert_mp_eq!(Int::read_pgp_mpi(&mut &bytes[..]).unwrap(), i);
+        }
+
+        let gmp: &[(&str, &[u8])] = &[
+            ("0", &[0, 0, 0, 0]),
+            ("1234", &[0, 0, 0, 2, 0x12, 0x34]),
+            ("-1234", &[0xff, 0xff, 0xff, 0xfe, 0x12, 0x34]),
+        ];
+        for &(hex, bytes) in gmp {
+            let i = Int::from_str_radix(hex, 16).unwrap();
+            let mut buf = Vec::new();
+            i.write_gmp_raw(&mut buf).unwrap();
+            assert_eq!(buf, bytes);
+            assert_mp_eq!(Int::read_gmp_raw(&mut &bytes[..]).unwrap(), i);
+        }
+        assert_mp_eq!(Int::read_gmp_raw(&mut &[0, 0, 0, 3, 0, 0x12, 0x34][..]).unwrap(),
+                      Int::from(0x1234));
+
+        // Reading leaves anything after the value in the reader
+        let mut r: &[u8] = &[0, 0, 0, 1, 0x05, 0xaa];
+        assert_mp_eq!(Int::read_ssh_mpint(&mut r).unwrap(), Int::from(5));
+        assert_eq!(r, &[0xaa]);
+
+        let truncated: &[&[u8]] = &[&[0, 0, 0], &[0, 0, 0, 2, 0x01], &[0xff, 0xff, 0xff, 0xff]];
+        for bytes in truncated {
+            let err = Int::read_ssh_mpint(&mut &bytes[..]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+            let err = Int::read_gmp_raw(&mut &bytes[..]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+        let err = Int::read_pgp_mpi(&mut &[0, 9, 1][..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let malformed_ssh: &[&[u8]] = &[&[0, 0, 0, 1, 0], &[0, 0, 0, 2, 0, 0x7f],
+                                        &[0, 0, 0, 2, 0xff, 0x80]];
+        for bytes in malformed_ssh {
+            let err = Int::read_ssh_mpint(&mut &bytes[..]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+        let malformed_pgp: &[&[u8]] = &[&[0, 8, 1], &[0, 9, 0, 0xff], &[0, 1, 0]];
+        for bytes in malformed_pgp {
+            let err = Int::read_pgp_mpi(&mut &bytes[..]).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+
+        let mut buf = Vec::new();
+        assert_eq!(Int::from(-1).write_pgp_mpi(&mut buf).unwrap_err().kind(),
+                   io::ErrorKind::InvalidInput);
+        assert_eq!((Int::one() << 65535).write_pgp_mpi(&mut buf).unwrap_err().kind(),
+                   io::ErrorKind::InvalidInput);
+        assert!(buf.is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn leb128() {
+        let uleb: &[(&str, &[u8])] = &[
+            ("0", &[0x00]),
+            ("127", &[0x7f]),
+            ("128", &[0x80, 0x01]),
+            ("624485", &[0xe5, 0x8e, 0x26]),
+            ("18446744073709551615", &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]),
+            ("18446744073709551616", &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x02]),
+        ];
+        for &(s, bytes) in uleb {
+            let i = Int::from_str(s).unwrap();
+            let mut buf = Vec::new();
+            i.write_uleb128(&mut buf).unwrap();
+            assert_eq!(buf, bytes);
+            assert_mp_eq!(Int::read_uleb128(&mut &bytes[..]).unwrap(), i);
+        }
+
+        let sleb: &[(&str, &[u8])] = &[
+            ("0", &[0x00]),
+            ("2", &[0x02]),
+            ("-1", &[0x7f]),
+            ("63", &[0x3f]),
+            ("64", &[0xc0, 0x00]),
+            ("-64", &[0x40]),
+            ("-65", &[0xbf, 0x7f]),
+            ("-123456", &[0xc0, 0xbb, 0x78]),
+            ("-9223372036854775808", &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f]),
+        ];
+        for &(s, bytes) in sleb {
+            let i = Int::from_str(s).unwrap();
+            let mut buf = Vec::new();
+            i.write_sleb128(&mut buf).unwrap();
+            assert_eq!(buf, bytes);
+            assert_mp_eq!(Int::read_sleb128(&mut &bytes[..]).unwrap(), i);
+        }
+
+        let zigzag: &[(&str, &[u8])] = &[
+            ("0", &[0x00]),
+            ("-1", &[0x01]),
+            ("1", &[0x02]),
+            ("-2", &[0x03]),
+            ("2147483647", &[0xfe, 0xff, 0xff, 0xff, 0x0f]),
+            ("-2147483648", &[0xff, 0xff, 0xff, 0xff, 0x0f]),
+        ];
+        for &(s, bytes) in zigzag {
+            let i = Int::from_str(s).unwrap();
+            let mut buf = Vec::new();
+            i.write_zigzag(&mut buf).unwrap();
+            assert_eq!(buf, bytes);
+            assert_mp_eq!(Int::read_zigzag(&mut &bytes[..]).unwrap(), i);
+        }
+
+        // Non-minimal encodings are still read, and trailing data is left alone
+        let mut r: &[u8] = &[0x85, 0x80, 0x00, 0xaa];
+        assert_mp_eq!(Int::read_uleb128(&mut r).unwrap(), Int::from(5));
+        assert_eq!(r, &[0xaa]);
+        assert_mp_eq!(Int::read_sleb128(&mut &[0xff, 0x7f][..]).unwrap(), Int::from(-1));
+
+        let err = Int::read_uleb128(&mut &[0x80, 0x80][..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let err = Int::from(-1).write_uleb128(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn inline_storage() {
+        let small = [Int::zero(), Int::one(), Int::from(-7), Int::from(!0 as BaseInt),
+                     Int::from_single_limb(Limb(42))];
+        for i in &small {
+            assert!(i.is_inline());
+            assert!(i.clone().is_inline());
+            assert!((i.clone() + Limb(1)).is_inline());
+            assert!((i.clone() * 3i32).is_inline());
+        }
+
+        // Grow past the inline storage and shrink back into it
+        let mut x = Int::from(3);
+        let mut pows = Vec::new();
+        for _ in 0..200 {
+            x *= 3;
+            pows.push(x.clone());
+        }
+        assert!(!x.is_inline());
+        for p in pows.iter().rev() {
+            assert_mp_eq!(x, *p);
+            x /= 3;
+        }
+        assert_mp_eq!(x, Int::from(3));
+        x.shrink_to_fit();
+        assert!(x.is_inline());
+        assert_mp_eq!(x, Int::from(3));
+
+        let big = Int::one() << 1000;
+        let mut y = big.clone();
+        y -= &big;
+        y += 5;
+        y.shrink_to_fit();
+        assert!(y.is_inline());
+        assert_eq!(y, 5);
+        y.clone_from(&big);
+        assert_eq!(y, big);
+    }
+
     #[test]
     fn div() {
         let cases = [
@@ -4122,7 +7301,6 @@ mod test {
 
     #[test]
     #[should_panic(expected = "divide by zero")]
-    #[cfg(debug_assertions)] // only a panic in this mode
     fn divmod_zero() {
         Int::from(1).divmod(&Int::zero());
     }
@@ -4156,7 +7334,7 @@ mod test {
              "100000000000000000000000000000000000000000000000"),
         ];
 
-        for t in cases.into_iter() {
+        for t in cases.iter() {
             let dividend: Int = t.0.parse().unwrap();
             let divisor: Int = t.1.parse().unwrap();
             let expected_quotient: Int = t.2.parse().unwrap();
@@ -4201,6 +7379,35 @@ mod test {
         }
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn sqrt_u64_fallback() {
+        let mut rng = rand::thread_rng();
+        // `sqrt_rem` only uses `sqrt_u64` below 2^52, where the float version is exact
+        for bits in 1..53 {
+            for _ in 0..100 {
+                let n = rng.gen::<u64>() >> (64 - bits);
+                assert_eq!(super::sqrt_u64_newton(n), super::sqrt_u64(n), "n = {}", n);
+            }
+        }
+
+        for &n in &[0, 1, 2, 3, 4, (1 << 32) - 1, 1 << 32, u64::MAX - 1, u64::MAX] {
+            let r = super::sqrt_u64_newton(n);
+            assert!(r * r <= n, "n = {}", n);
+            assert!((r + 1).checked_mul(r + 1).is_none_or(|s| s > n), "n = {}", n);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn weight_fallback() {
+        let mut rng = rand::thread_rng();
+        for len in 0..100 {
+            let bytes: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+            assert_eq!(super::weight_portable(&bytes), super::weight(&bytes));
+        }
+    }
+
     #[test]
     fn bitand() {
         let cases = [
@@ -4412,20 +7619,268 @@ mod test {
         assert_mp_eq!((-20i32) / &x, "-4".parse().unwrap());
     }
 
+    #[test]
+    #[allow(clippy::unnecessary_cast)] // Only a no-op with 64-bit limbs
+    fn arith_prim_usize_max() {
+        // `usize` can be wider than a limb, with `force-32-bit-limbs`
+        let m = usize::MAX;
+        let mi = Int::from(m);
+        let x = -Int::from(3).pow(50);
+
+        assert_mp_eq!(&x + m, &x + &mi);
+        assert_mp_eq!(&x - m, &x - &mi);
+        assert_mp_eq!(&x * m, &x * &mi);
+        assert_mp_eq!(&x / m, &x / &mi);
+        assert_mp_eq!(&x % m, &x % &mi);
+        assert_mp_eq!(&x & m, &x & &mi);
+        assert_mp_eq!(&x | m, &x | &mi);
+        assert_mp_eq!(&x ^ m, &x ^ &mi);
+
+        let (q, r) = x.clone().divrem(m);
+        assert_mp_eq!(q, &x / &mi);
+        assert_mp_eq!(Int::from(r), (&x % &mi).abs());
+
+        assert!(mi == m);
+        assert!(m == mi);
+        assert!(mi.clone() - 1 < m && m > mi.clone() - 1);
+        assert!(mi.clone() + 1 > m && m < mi + 1);
+
+        // The largest single limb, compared as a `u64`
+        let max_limb = BaseInt::MAX as u64;
+        let l = Int::from(max_limb);
+        assert!(l == max_limb && l.clone() - 1 != max_limb && l.clone() + 1 != max_limb);
+        assert!(l.clone() - 1 < max_limb && l.clone() + 1 > max_limb && l <= max_limb);
+    }
+
     #[test]
     fn int_from() {
-        let i = Int::from(::std::i64::MIN);
-        assert_eq!(i64::from(&i), ::std::i64::MIN);
+        let i = Int::from(i64::MIN);
+        assert_eq!(i64::try_from(&i), Ok(i64::MIN));
+
+        let i = Int::from(i32::MIN);
+        assert_eq!(i32::try_from(&i), Ok(i32::MIN));
+
+        let i = Int::from(usize::MAX);
+        assert_eq!(usize::try_from(&i), Ok(usize::MAX));
+
+        let i = Int::from(i128::MIN);
+        assert_eq!(i, -(Int::one() << 127));
+        assert_eq!(i128::try_from(&i), Ok(i128::MIN));
+
+        let i = Int::from(u128::MAX);
+        assert_eq!(i, (Int::one() << 128) - Limb(1));
+        assert_eq!(u128::try_from(&i), Ok(u128::MAX));
+    }
+
+    #[test]
+    fn int_try_from_overflow() {
+        let i = Int::from(i64::MIN) - Limb(1);
+        assert!(i64::try_from(&i).is_err());
+        assert_eq!(i128::try_from(&i), Ok(i64::MIN as i128 - 1));
+
+        let i = Int::from(i8::MAX) + Limb(1);
+        assert!(i8::try_from(&i).is_err());
+        assert_eq!(u8::try_from(&i), Ok(128));
 
-        let i = Int::from(::std::i32::MIN);
-        assert_eq!(i32::from(&i), ::std::i32::MIN);
+        assert!(u8::try_from(&Int::from(-1)).is_err());
+        assert!(u128::try_from(&(Int::one() << 128)).is_err());
+        assert!(i128::try_from(&(Int::one() << 127)).is_err());
+        assert_eq!(u32::try_from(&Int::zero()), Ok(0));
+    }
+
+    #[test]
+    fn to_float() {
+        let two = Int::from(2);
+        let p53 = two.pow(53);
+
+        // Ties go to the even significand
+        assert_eq!((p53.clone() + Limb(1)).to_f64(), 9007199254740992.0);
+        assert_eq!((p53.clone() + Limb(3)).to_f64(), 9007199254740996.0);
+        assert_eq!((-(p53.clone() + Limb(3))).to_f64(), -9007199254740996.0);
+        assert_eq!((p53.clone() + Limb(1)).to_f32(), 9007199254740992.0);
+        assert_eq!(Int::from(16777217).to_f32(), 16777216.0);
+        assert_eq!(Int::from(16777219).to_f32(), 16777220.0);
+
+        // Everything below the rounding bit counts
+        let x = (&p53 << 100) + (Int::one() << 100) + Limb(1);
+        assert_eq!(x.to_f64(), 9007199254740994.0 * 2f64.powi(100));
+
+        let max = (p53.clone() - Limb(1)) << 971;
+        assert_eq!(max.to_f64(), f64::MAX);
+        assert_eq!((&max + (Int::one() << 970) - Limb(1)).to_f64(), f64::MAX);
+        assert_eq!((&max + (Int::one() << 970)).to_f64(), f64::INFINITY);
+        assert_eq!((-(Int::one() << 5000)).to_f64(), f64::NEG_INFINITY);
+        assert_eq!((Int::one() << 128).to_f32(), f32::INFINITY);
+
+        assert_eq!(Int::zero().to_f64(), 0.0);
+        assert_eq!(Int::from(-12345).to_f32(), -12345.0);
+    }
+
+    #[test]
+    fn from_float() {
+        assert_eq!(Int::from_f64(2.5), Some(Int::from(2)));
+        assert_eq!(Int::from_f64(-2.7), Some(Int::from(-2)));
+        assert_eq!(Int::from_f64(0.3), Some(Int::zero()));
+        assert_eq!(Int::from_f64(1e-310), Some(Int::zero()));
+        assert_eq!(Int::from_f64(f64::MAX), Some((Int::from(2).pow(53) - Limb(1)) << 971));
+        assert_eq!(Int::from_f64(-1e20), Some(Int::from(-100000000000000000000i128)));
+
+        assert_eq!(Int::from_f64_rounded(2.5), Some(Int::from(2)));
+        assert_eq!(Int::from_f64_rounded(3.5), Some(Int::from(4)));
+        assert_eq!(Int::from_f64_rounded(-2.7), Some(Int::from(-3)));
+        assert_eq!(Int::from_f64_rounded(0.5), Some(Int::zero()));
+        assert_eq!(Int::from_f64_rounded(0.5000000000000001), Some(Int::one()));
+        assert_eq!(Int::from_f64_rounded(4503599627370495.5), Some(Int::from(4503599627370496u64)));
+
+        assert_eq!(Int::from_f64(f64::INFINITY), None);
+        assert_eq!(Int::from_f64_rounded(f64::NAN), None);
+    }
+
+    #[test]
+    fn num_traits() {
+        use num_traits::{Signed, Pow, CheckedDiv, CheckedSub, ToPrimitive, FromPrimitive};
+
+        let a = Int::from(-17);
+        assert_eq!(Signed::abs(&a), 17);
+        assert_eq!(a.signum(), -1);
+        assert!(a.is_negative() && !a.is_positive());
+        assert_eq!(Int::from(3).abs_sub(&a), 20);
+        assert_eq!(a.abs_sub(&Int::from(3)), 0);
+
+        assert_eq!(Pow::pow(&a, 3u32), -4913);
+        assert_eq!(Pow::pow(a.clone(), 0u8), 1);
+
+        assert_eq!(a.checked_div(&Int::zero()), None);
+        assert_eq!(a.checked_div(&Int::from(5)), Some(Int::from(-3)));
+        assert_eq!(a.checked_sub(&a), Some(Int::zero()));
+
+        assert_eq!(a.to_i8(), Some(-17));
+        assert_eq!(a.to_u64(), None);
+        assert_eq!((Int::one() << 100).to_i64(), None);
+        assert_eq!((Int::one() << 100).to_u128(), Some(1 << 100));
+        assert_eq!(ToPrimitive::to_f64(&a), Some(-17.0));
+
+        assert_eq!(Int::from_i64(-5), Some(Int::from(-5)));
+        assert_eq!(Int::from_f64(-2.5), Some(Int::from(-2)));
+        assert_eq!(<Int as FromPrimitive>::from_f64(f64::NAN), None);
+        assert_eq!(<Int as ::num_traits::NumCast>::from(1e20f64), Some(Int::from(100000000000000000000u128)));
+        assert_eq!(<Int as ::num_traits::NumCast>::from(u64::MAX), Some(Int::from(u64::MAX)));
+        assert_eq!(<Int as ::num_traits::NumCast>::from(-3.9f32), Some(Int::from(-3)));
+    }
+
+    #[test]
+    fn roots() {
+        use num_integer::Roots;
+
+        assert_eq!(Int::from(0).nth_root(3), 0);
+        assert_eq!(Int::from(1).nth_root(7), 1);
+        assert_eq!(Int::from(26).cbrt(), 2);
+        assert_eq!(Int::from(27).cbrt(), 3);
+        assert_eq!(Int::from(-28).cbrt(), -3);
+        assert_eq!(Int::from(99).sqrt(), 9);
+        assert_eq!(Int::from(100).nth_root(1), 100);
+        assert_eq!(Int::from(255).nth_root(8), 1);
+
+        let x = Int::from(3).pow(500);
+        assert_eq!(x.nth_root(100), 243);
+        assert_eq!((x.clone() - Limb(1)).nth_root(100), 242);
+        assert_eq!((&x * &x).sqrt(), x);
+    }
+
+    #[test]
+    #[should_panic]
+    fn even_root_of_negative() {
+        use num_integer::Roots;
+        Int::from(-4).sqrt();
+    }
+
+    #[test]
+    fn extended_gcd() {
+        let a = Int::from(240);
+        let b = Int::from(-46);
+        let e = a.extended_gcd(&b);
+        assert_eq!(e.gcd, 2);
+        assert_eq!(&e.x * &a + &e.y * &b, e.gcd);
+    }
+
+    #[test]
+    fn integer() {
+        let cases = [(7, 2, 3, 1), (-7, 2, -4, 1), (7, -2, -4, -1), (-7, -2, 3, -1), (-6, 2, -3, 0)];
+        for &(a, b, q, r) in cases.iter() {
+            let (a, b) = (Int::from(a), Int::from(b));
+            assert_eq!(Integer::div_floor(&a, &b), q);
+            assert_eq!(Integer::mod_floor(&a, &b), r);
+            assert_eq!(a.div_mod_floor(&b), (Int::from(q), Int::from(r)));
+        }
+
+        assert_eq!(Integer::gcd(&Int::zero(), &Int::from(-5)), 5);
+        assert_eq!(Integer::gcd(&Int::from(-4), &Int::from(-6)), 2);
+        assert_eq!(Integer::lcm(&Int::zero(), &Int::zero()), 0);
+        assert_eq!(Integer::lcm(&Int::from(-4), &Int::from(6)), 12);
+        assert!(Integer::is_multiple_of(&Int::zero(), &Int::zero()));
+        assert!(!Integer::is_multiple_of(&Int::from(3), &Int::zero()));
+        assert!(Integer::is_multiple_of(&Int::from(-9), &Int::from(3)));
+
+        let e = Int::zero().extended_gcd(&Int::from(-5));
+        assert_eq!(e.gcd, Integer::gcd(&Int::zero(), &Int::from(-5)));
+    }
+
+    #[test]
+    #[cfg(feature = "num-bigint")]
+    fn num_bigint_conversions() {
+        use num_bigint::{BigInt, BigUint};
+
+        for s in &["0", "1", "-1", "255", "-256", "18446744073709551616",
+                   "-123456789012345678901234567890"] {
+            let i = Int::from_str(s).unwrap();
+            let b = BigInt::from_str(s).unwrap();
+            assert_eq!(Int::from(&b), i);
+            assert_eq!(BigInt::from(&i), b);
+        }
+
+        let u = BigUint::from_str("340282366920938463463374607431768211455").unwrap();
+        assert_eq!(Int::from(u.clone()), Int::from(u128::MAX));
+        assert_eq!(BigUint::try_from(Int::from(u128::MAX)), Ok(u));
+        assert!(BigUint::try_from(&Int::from(-1)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "rust-gmp")]
+    fn gmp_conversions() {
+        use gmp::mpz::Mpz;
+
+        for s in &["0", "1", "-1", "255", "-256", "18446744073709551616",
+                   "-123456789012345678901234567890"] {
+            let i = Int::from_str(s).unwrap();
+            let m = Mpz::from_str(s).unwrap();
+            assert_eq!(Int::from(&m), i);
+            assert_eq!(Mpz::from(&i), m);
+        }
+    }
+
+    #[test]
+    fn int_to_wrapping() {
+        let i = (Int::from(0x1234_5678_9abc_def0u64) << 128) + Limb(0x1ff);
+        assert_eq!(i.to_u8_wrapping(), 0xff);
+        assert_eq!(i.to_i8_wrapping(), -1);
+        assert_eq!(i.to_u16_wrapping(), 0x1ff);
+        assert_eq!(i.to_u128_wrapping(), 0x1ff);
 
-        let i = Int::from(::std::usize::MAX);
-        assert_eq!(usize::from(&i), ::std::usize::MAX);
+        let i = -Int::from(0x1ff);
+        assert_eq!(i.to_u8_wrapping(), 0x01);
+        assert_eq!(i.to_i16_wrapping(), -0x1ff);
+        assert_eq!(i.to_u32_wrapping(), (-0x1ffi32) as u32);
+        assert_eq!(i.to_u128_wrapping(), (-0x1ffi128) as u128);
+
+        let i = -(Int::one() << 200);
+        assert_eq!(i.to_i64_wrapping(), 0);
+        assert_eq!(Int::from(i64::MIN).to_u64_wrapping(), 1 << 63);
     }
 
+    #[cfg(feature = "std")]
     const RAND_ITER : usize = 1000;
 
+    #[cfg(feature = "std")]
     #[test]
     fn div_rand() {
         let mut rng = rand::thread_rng();
@@ -4440,6 +7895,7 @@ mod test {
         }
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn sqr_rand() {
         let mut rng = rand::thread_rng();
@@ -4454,6 +7910,7 @@ mod test {
     }
 
 
+    #[cfg(feature = "std")]
     #[test]
     fn shl_rand() {
         let mut rng = rand::thread_rng();
@@ -4472,6 +7929,7 @@ mod test {
         }
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn shl_rand_large() {
         let mut rng = rand::thread_rng();
@@ -4488,6 +7946,7 @@ mod test {
         }
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn shr_rand() {
         let mut rng = rand::thread_rng();
@@ -4502,6 +7961,7 @@ mod test {
         }
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn bitand_rand() {
         let mut rng = rand::thread_rng();
@@ -4513,6 +7973,7 @@ mod test {
         }
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn hash_rand() {
         let mut rng = rand::thread_rng();
@@ -4534,6 +7995,23 @@ mod test {
         }
     }
 
+    #[test]
+    fn stable_hash() {
+        // 64-bit FNV-1a over the sign byte, the magnitude's length in bytes as a
+        // little-endian u64, and the magnitude's bytes, least significant first.
+        let cases = [
+            (Int::zero(), 0xe604823a249029bf),
+            (Int::one(), 0xe4983b263241c1a4),
+            (-Int::one(), 0x69fdb3a58d3a218a),
+            (Int::from_str_radix("123456789abcdef0123", 16).unwrap(), 0x6a6e4453f714ed5a),
+            (-Int::from(3).pow(200), 0x0b8fae25f0b0a6a0),
+        ];
+        for &(ref x, expected) in cases.iter() {
+            assert_eq!(x.stable_hash(), expected, "stable_hash of {}", x);
+        }
+    }
+
+    #[cfg(feature = "std")]
     #[test]
     #[should_panic]
     fn gen_uint_with_zero_bits() {
@@ -4541,6 +8019,7 @@ mod test {
         rng.gen_uint(0);
     }
 
+    #[cfg(feature = "std")]
     #[test]
     #[should_panic]
     fn gen_int_with_zero_bits() {
@@ -4548,6 +8027,7 @@ mod test {
         rng.gen_int(0);
     }
 
+    #[cfg(feature = "std")]
     #[test]
     #[should_panic]
     fn gen_uint_below_zero_or_negative() {
@@ -4560,6 +8040,7 @@ mod test {
         rng.gen_uint_below(&j);
     }
 
+    #[cfg(feature = "std")]
     #[test]
     #[should_panic]
     fn gen_int_range_zero() {
@@ -4569,6 +8050,7 @@ mod test {
         rng.gen_int_range(&b, &b);
     }
 
+    #[cfg(feature = "std")]
     #[test]
     #[should_panic]
     fn gen_int_range_negative() {
@@ -4580,6 +8062,7 @@ mod test {
         rng.gen_int_range(&ub, &lb);
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn gen_int_range() {
         let mut rng = rand::thread_rng();
@@ -4601,9 +8084,10 @@ mod test {
         }
     }
 
+    #[cfg(feature = "std")]
     #[test]
     fn gen_uint_below_all_ones() {
-        static N : &'static str =
+        static N : &str =
             "000001FFFFFFFFFFFFFFFFFFFFFFFFFFF\
              FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF\
              FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF";
@@ -4710,6 +8194,21 @@ mod test {
         }
     }
 
+    #[cfg(feature = "unstable")]
+    #[test]
+    fn step() {
+        let r: Vec<Int> = (Int::from(-2)..Int::from(2)).collect();
+        assert_eq!(r, [-2, -1, 0, 1].iter().map(|&i| Int::from(i)).collect::<Vec<_>>());
+
+        let big = Int::one() << 100;
+        let r: Vec<Int> = (&big - 1..=&big + 1).rev().step_by(2).collect();
+        assert_eq!(r, vec![&big + 1, &big - 1]);
+
+        assert_eq!((Int::from(3)..Int::from(3)).count(), 0);
+        assert_eq!((Int::zero()..big).size_hint(), (!0, None));
+    }
+
+    #[cfg(feature = "unstable")]
     fn bench_add(b: &mut Bencher, xs: usize, ys: usize) {
         let mut rng = rand::thread_rng();
 
@@ -4722,31 +8221,37 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_add_1_1(b: &mut Bencher) {
         bench_add(b, 1, 1);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_add_10_10(b: &mut Bencher) {
         bench_add(b, 10, 10);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_add_100_100(b: &mut Bencher) {
         bench_add(b, 100, 100);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_add_1000_1000(b: &mut Bencher) {
         bench_add(b, 1000, 1000);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_add_1000_10(b: &mut Bencher) {
         bench_add(b, 1000, 10);
     }
 
+    #[cfg(feature = "unstable")]
     fn bench_mul(b: &mut Bencher, xs: usize, ys: usize) {
         let mut rng = rand::thread_rng();
 
@@ -4759,6 +8264,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     fn bench_pow(b: &mut Bencher, xs: usize, ys: usize) {
         let mut rng = rand::thread_rng();
 
@@ -4771,46 +8277,55 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_mul_1_1(b: &mut Bencher) {
         bench_mul(b, 1, 1);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_mul_10_10(b: &mut Bencher) {
         bench_mul(b, 10, 10);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_mul_2_20(b: &mut Bencher) {
         bench_mul(b, 2, 20);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_mul_50_50(b: &mut Bencher) {
         bench_mul(b, 50, 50);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_mul_5_50(b: &mut Bencher) {
         bench_mul(b, 5, 50);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_mul_250_250(b: &mut Bencher) {
         bench_mul(b, 250, 250);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_mul_1000_1000(b: &mut Bencher) {
         bench_mul(b, 1000, 1000);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_mul_50_1500(b: &mut Bencher) {
         bench_mul(b, 50, 1500);
     }
 
+    #[cfg(feature = "unstable")]
     fn bench_sqr(b: &mut Bencher, xs: usize) {
         let mut rng = rand::thread_rng();
 
@@ -4822,71 +8337,85 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_sqr_1(b: &mut Bencher) {
         bench_sqr(b, 1);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_sqr_10(b: &mut Bencher) {
         bench_sqr(b, 10);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_sqr_50(b: &mut Bencher) {
         bench_sqr(b, 50);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_sqr_250(b: &mut Bencher) {
         bench_sqr(b, 250);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_sqr_1000(b: &mut Bencher) {
         bench_sqr(b, 1000);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_pow_1_1(b: &mut Bencher) {
         bench_pow(b, 1, 1);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_pow_10_10(b: &mut Bencher) {
         bench_pow(b, 10, 10);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_pow_2_20(b: &mut Bencher) {
         bench_pow(b, 2, 20);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_pow_50_50(b: &mut Bencher) {
         bench_pow(b, 50, 50);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_pow_5_50(b: &mut Bencher) {
         bench_mul(b, 5, 50);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_pow_250_250(b: &mut Bencher) {
         bench_mul(b, 250, 250);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_pow_1000_1000(b: &mut Bencher) {
         bench_mul(b, 1000, 1000);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_pow_50_1500(b: &mut Bencher) {
         bench_mul(b, 50, 1500);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_factorial_100(b: &mut Bencher) {
         b.iter(|| {
@@ -4901,6 +8430,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_factorial_1000(b: &mut Bencher) {
         b.iter(|| {
@@ -4916,6 +8446,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     fn bench_div(b: &mut Bencher, xs: usize, ys: usize) {
         let mut rng = rand::thread_rng();
 
@@ -4928,41 +8459,49 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_div_1_1(b: &mut Bencher) {
         bench_div(b, 1, 1);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_div_10_10(b: &mut Bencher) {
         bench_div(b, 10, 10);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_div_20_2(b: &mut Bencher) {
         bench_div(b, 20, 2);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_div_50_50(b: &mut Bencher) {
         bench_div(b, 50, 50);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_div_50_5(b: &mut Bencher) {
         bench_div(b, 50, 5);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_div_250_250(b: &mut Bencher) {
         bench_div(b, 250, 250);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_div_1000_1000(b: &mut Bencher) {
         bench_div(b, 1000, 1000);
     }
 
+    #[cfg(feature = "unstable")]
     fn bench_gcd(b: &mut Bencher, xs: usize, ys: usize) {
         let mut rng = rand::thread_rng();
 
@@ -4975,51 +8514,61 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_gcd_1_1(b: &mut Bencher) {
         bench_gcd(b, 1, 1);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_gcd_10_10(b: &mut Bencher) {
         bench_gcd(b, 10, 10);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_gcd_20_2(b: &mut Bencher) {
         bench_gcd(b, 20, 2);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_gcd_50_50(b: &mut Bencher) {
         bench_gcd(b, 50, 50);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_gcd_50_5(b: &mut Bencher) {
         bench_gcd(b, 50, 5);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_gcd_250_150(b: &mut Bencher) {
         bench_gcd(b, 250, 150);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_gcd_100_100(b: &mut Bencher) {
         bench_gcd(b, 100, 100);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_gcd_100_10(b: &mut Bencher) {
         bench_gcd(b, 100, 10);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_gcd_100_50(b: &mut Bencher) {
         bench_gcd(b, 100, 50);
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_rng_all_ones(b: &mut Bencher) {
         let mut rng = rand::thread_rng();
@@ -15,22 +15,44 @@
 #![crate_type="lib"]
 #![crate_name="framp"]
 
-#![feature(core_intrinsics, asm, heap_api, associated_consts)]
-#![feature(step_trait, unique, alloc)]
+// Without the default `std` feature the crate only needs `core` and `alloc`.
+// I/O, `Error` impls, random number generation and the per-thread scratch
+// space pool are left out.
+#![cfg_attr(not(feature = "std"), no_std)]
 
-#![cfg_attr(test, feature(test))]
+// Everything builds on stable Rust. The `unstable` feature turns on the parts
+// that need a nightly compiler: inline assembly, optimizer hints, the `Step`
+// impl for `Int` and the benchmarks.
+#![cfg_attr(feature = "unstable", feature(step_trait))]
+#![cfg_attr(all(test, feature = "unstable"), feature(test))]
 
-#[cfg(test)] extern crate test;
+#[cfg(all(test, feature = "unstable"))] extern crate test;
+
+#[cfg(not(feature = "std"))] #[macro_use] extern crate alloc;
 
-extern crate alloc;
 extern crate ieee754;
-extern crate rand;
-extern crate hamming;
+#[cfg(feature = "std")] extern crate rand;
+#[cfg(feature = "std")] extern crate hamming;
 extern crate num_integer;
 extern crate num_traits;
+#[cfg(feature = "serde")] extern crate serde;
+#[cfg(feature = "num-bigint")] extern crate num_bigint;
+#[cfg(feature = "num-rational")] extern crate num_rational;
+#[cfg(feature = "rust-gmp")] extern crate gmp;
+#[cfg(all(test, feature = "serde"))] extern crate serde_json;
+#[cfg(all(test, feature = "serde"))] extern crate bincode;
+
+/// Stands in for `std` when it isn't available, so the rest of the crate can
+/// keep using `std` paths for the parts that also live in `core` and `alloc`.
+#[cfg(not(feature = "std"))]
+mod std {
+    pub use core::*;
+    pub use alloc::{alloc, fmt, slice, str, string, vec};
+}
 
 pub mod ll;
 mod mem;
+mod float;
 
 pub mod traits;
 pub mod int;
@@ -39,4 +61,5 @@ pub mod rational;
 // Re-exports
 
 pub use int::Int;
+#[cfg(feature = "std")]
 pub use int::RandomInt;
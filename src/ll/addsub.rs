@@ -20,7 +20,7 @@ use super::{copy_rest, same_or_separate};
 
 #[allow(dead_code)]
 unsafe fn add_n_generic(mut wp: LimbsMut, mut xp: Limbs, mut yp: Limbs,
-                        mut n: i32) -> Limb {
+                        mut n: isize) -> Limb {
 
     let mut carry = Limb(0);
 
@@ -53,16 +53,17 @@ unsafe fn add_n_generic(mut wp: LimbsMut, mut xp: Limbs, mut yp: Limbs,
 #[inline]
 #[cfg(asm)]
 pub unsafe fn add_n(mut wp: LimbsMut, xp: Limbs, yp: Limbs,
-                    n: i32) -> Limb {
+                    n: isize) -> Limb {
     #[cfg(all(not(feature="fallbacks"),target_arch="x86_64"))]
     extern "C" { fn ramp_add_n(wp: *mut Limb, xp: *const Limb, yp: *const Limb,
-                               n: i32) -> Limb; }
+                               n: isize) -> Limb; }
 
     debug_assert!(n >= 1);
-    debug_assert!(same_or_separate(wp, n, xp, n));
-    debug_assert!(same_or_separate(wp, n, yp, n));
+    check_overlap!(same_or_separate(wp, n, xp, n));
+    check_overlap!(same_or_separate(wp, n, yp, n));
 
-    return ramp_add_n(&mut *wp, &*xp, &*yp, n);
+    check_range!(n, wp, xp, yp);
+    ramp_add_n(&mut *wp, &*xp, &*yp, n)
 }
 
 /**
@@ -72,22 +73,22 @@ pub unsafe fn add_n(mut wp: LimbsMut, xp: Limbs, yp: Limbs,
 #[cfg(any(feature="fallbacks",not(asm)))]
 #[inline]
 pub unsafe fn add_n(wp: LimbsMut, xp: Limbs, yp: Limbs,
-                    n: i32) -> Limb {
+                    n: isize) -> Limb {
     debug_assert!(n >= 1);
-    debug_assert!(same_or_separate(wp, n, xp, n));
-    debug_assert!(same_or_separate(wp, n, yp, n));
+    check_overlap!(same_or_separate(wp, n, xp, n));
+    check_overlap!(same_or_separate(wp, n, yp, n));
 
     add_n_generic(wp, xp, yp, n)
 }
 
 #[allow(dead_code)]
 unsafe fn sub_n_generic(mut wp: LimbsMut, mut xp: Limbs, mut yp: Limbs,
-                        mut n: i32) -> Limb {
+                        mut n: isize) -> Limb {
     let mut carry = Limb(0);
 
     debug_assert!(n >= 1);
-    debug_assert!(same_or_separate(wp, n, xp, n));
-    debug_assert!(same_or_separate(wp, n, yp, n));
+    check_overlap!(same_or_separate(wp, n, xp, n));
+    check_overlap!(same_or_separate(wp, n, yp, n));
 
     loop {
         let xl = *xp;
@@ -118,12 +119,13 @@ unsafe fn sub_n_generic(mut wp: LimbsMut, mut xp: Limbs, mut yp: Limbs,
 #[cfg(asm)]
 #[inline]
 pub unsafe fn sub_n(mut wp: LimbsMut, xp: Limbs, yp: Limbs,
-                    n: i32) -> Limb {
+                    n: isize) -> Limb {
     extern "C" {
         fn ramp_sub_n(wp: *mut Limb, xp: *const Limb, yp: *const Limb,
-                      n: i32) -> Limb;
+                      n: isize) -> Limb;
     }
 
+    check_range!(n, wp, xp, yp);
     ramp_sub_n(&mut *wp, &*xp, &*yp, n)
 }
 
@@ -134,7 +136,7 @@ pub unsafe fn sub_n(mut wp: LimbsMut, xp: Limbs, yp: Limbs,
 #[cfg(not(asm))]
 #[inline]
 pub unsafe fn sub_n(wp: LimbsMut, xp: Limbs, yp: Limbs,
-                    n: i32) -> Limb {
+                    n: isize) -> Limb {
     sub_n_generic(wp, xp, yp, n)
 }
 
@@ -142,8 +144,8 @@ macro_rules! aors {
     ($op:ident, $lop:ident, $f:ident) => {
         #[inline]
         pub unsafe fn $op(wp: LimbsMut,
-                          xp: Limbs, xs: i32,
-                          yp: Limbs, ys: i32) -> Limb {
+                          xp: Limbs, xs: isize,
+                          yp: Limbs, ys: isize) -> Limb {
 
             debug_assert!(xs >= ys);
             debug_assert!(ys >= 0);
@@ -179,7 +181,7 @@ macro_rules! aors_1 {
     ($op:ident, $f:ident) => {
         #[inline]
         pub unsafe fn $op(mut wp: LimbsMut,
-                          xp: Limbs, xs: i32,
+                          xp: Limbs, xs: isize,
                           y: Limb) -> Limb {
 
             if xs > 0 {
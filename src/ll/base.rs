@@ -16,18 +16,20 @@
  * Base conversion utilities
  *
  * Provides functions for converting an integer to/from a given base. In both `to_base` and
- * `from_base` the base-N output or input (respectively) is stored as raw bytes. That means that a
- * base-10 input contains bytes each with a value from 0-9.
+ * `from_base` the base-N output or input (respectively) is stored as raw digit values, as
+ * either `u8` or `u32` (see `Digit`). That means that a base-10 input contains digits each
+ * with a value from 0-9.
  */
 
-use std::intrinsics::assume;
+use std::vec::Vec;
 
 use ll;
 use ll::limb::Limb;
 use ll::limb_ptr::{Limbs, LimbsMut};
 
 /// Information for converting to/from a given base, B. Stored in a table generated
-/// by build.rs
+/// by build.rs for bases up to 256, and calculated as needed for larger ones.
+#[derive(Clone, Copy)]
 struct Base {
     /// Number of digits a limb can hold in a given base. Except if B is a power of 2,
     /// in which case it is equal to the number of bits per digit in base B
@@ -39,6 +41,71 @@ struct Base {
 // Include BASES table
 include!(concat!(env!("OUT_DIR"), "/bases_table.rs"));
 
+/// The largest base supported by the conversion routines.
+pub const MAX_BASE: u64 = 1 << 32;
+
+#[inline]
+fn base_info(base: u64) -> Base {
+    debug_assert!((2..=MAX_BASE).contains(&base));
+
+    if base < BASES.len() as u64 {
+        unsafe { *BASES.get_unchecked(base as usize) }
+    } else {
+        compute_base_info(base)
+    }
+}
+
+// Works out the same values build.rs puts in the table
+fn compute_base_info(base: u64) -> Base {
+    if base.is_power_of_two() {
+        let bits = base.trailing_zeros();
+        Base {
+            digits_per_limb: Limb::BITS as u32 / bits,
+            big_base: Limb(bits as ll::limb::BaseInt),
+        }
+    } else {
+        let b = base as ll::limb::BaseInt;
+        let mut digits_per_limb = 1;
+        let mut big_base = b;
+        while let Some(next) = big_base.checked_mul(b) {
+            big_base = next;
+            digits_per_limb += 1;
+        }
+        Base {
+            digits_per_limb,
+            big_base: Limb(big_base),
+        }
+    }
+}
+
+/// A digit value used by `to_base` and `from_base`. `u8` can hold the digits of
+/// bases up to 256, `u32` those of any base up to `MAX_BASE`.
+pub trait Digit: Copy {
+    fn from_limb(l: Limb) -> Self;
+    fn to_limb(self) -> Limb;
+}
+
+impl Digit for u8 {
+    #[inline(always)]
+    fn from_limb(l: Limb) -> u8 { l.0 as u8 }
+    #[inline(always)]
+    fn to_limb(self) -> Limb { Limb(self as ll::limb::BaseInt) }
+}
+
+impl Digit for u32 {
+    #[inline(always)]
+    #[allow(clippy::unnecessary_cast)] // A no-op with 32-bit limbs
+    fn from_limb(l: Limb) -> u32 { l.0 as u32 }
+    #[inline(always)]
+    fn to_limb(self) -> Limb { Limb(self as ll::limb::BaseInt) }
+}
+
+// Mask for the low `bits` bits of a limb, where `bits` may be a whole limb
+#[inline(always)]
+fn low_mask(bits: usize) -> Limb {
+    !Limb(0) >> (Limb::BITS - bits)
+}
+
 #[inline(always)]
 fn div_unnorm(n: Limb, d: Limb) -> (Limb, Limb) {
     (n / d, n % d)
@@ -50,13 +117,13 @@ fn div_unnorm(n: Limb, d: Limb) -> (Limb, Limb) {
 /// an estimate. It can equal the the actually needed digits or overestimate
 /// by 1.
 /// Returns 1 if the number is 0;
-pub unsafe fn num_base_digits(p: Limbs, n: i32, base: u32) -> usize {
+pub unsafe fn num_base_digits(p: Limbs, n: isize, base: u64) -> usize {
     debug_assert!(base >= 2);
-    assume(base >= 2);
+    assume!(base >= 2);
 
     if n == 0 { return 1; }
 
-    let cnt = (*p.offset((n - 1) as isize)).leading_zeros() as usize;
+    let cnt = (*p.offset(n - 1)).leading_zeros() as usize;
     let total_bits = (Limb::BITS * (n as usize)) - cnt;
 
     if base == 2 {
@@ -65,35 +132,81 @@ pub unsafe fn num_base_digits(p: Limbs, n: i32, base: u32) -> usize {
         // `bit_length`)
         total_bits
     } else if base.is_power_of_two() {
-        let bits_per_digit = BASES.get_unchecked(base as usize).big_base.0 as usize;
+        let bits_per_digit = base.trailing_zeros() as usize;
         if bits_per_digit.is_power_of_two() {
             // doing an actual division here is much slower than this
             (total_bits + bits_per_digit - 1) >> bits_per_digit.trailing_zeros()
         } else {
-            (total_bits + bits_per_digit - 1) / bits_per_digit
+            total_bits.div_ceil(bits_per_digit)
         }
     } else {
-        // Not sure if using floating-point arithmetic here is the best idea,
-        // but it should be a reasonable accurate result, maybe a little high
-        let total_bits = total_bits as f64;
+        digits_estimate(total_bits, base)
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn digits_estimate(total_bits: usize, base: u64) -> usize {
+    // Not sure if using floating-point arithmetic here is the best idea,
+    // but it should be a reasonable accurate result, maybe a little high
+    let total_bits = total_bits as f64;
+
+    let lg2b = (base as f64).log2();
+    let digits = total_bits / lg2b;
+    digits.ceil() as usize
+}
 
-        let lg2b = (base as f64).log2();
-        let digits = total_bits / lg2b;
-        return digits.ceil() as usize;
+#[cfg(not(feature = "std"))]
+#[inline]
+fn digits_estimate(total_bits: usize, base: u64) -> usize {
+    digits_estimate_fixed(total_bits, base)
+}
+
+/// Without `std` there's no `log2`, so this works with `log2(base)` in fixed point
+/// instead. It's rounded down, so the estimate can only come out high.
+#[cfg(any(not(feature = "std"), test))]
+fn digits_estimate_fixed(total_bits: usize, base: u64) -> usize {
+    const FRAC_BITS: u32 = 32;
+    const ONE: u128 = 1 << FRAC_BITS;
+
+    let int_part = 63 - base.leading_zeros();
+    // base / 2^int_part, in [1, 2). Squaring it gives the fractional bits of
+    // the logarithm one at a time.
+    let mut m = ((base as u128) << FRAC_BITS) >> int_part;
+    let mut lg2b = (int_part as u128) << FRAC_BITS;
+    for i in (0..FRAC_BITS).rev() {
+        m = (m * m) >> FRAC_BITS;
+        if m >= 2 * ONE {
+            m >>= 1;
+            lg2b |= 1 << i;
+        }
     }
+
+    ((total_bits as u128) << FRAC_BITS).div_ceil(lg2b) as usize
 }
 
 #[inline]
-pub fn base_digits_to_len(num: usize, base: u32) -> usize {
+pub fn base_digits_to_len(num: usize, base: u64) -> usize {
     debug_assert!(base >= 2);
 
     if num == 0 { return 0; }
 
-    let digits_per_limb = BASES[base as usize].digits_per_limb as usize;
+    let digits_per_limb = base_info(base).digits_per_limb as usize;
 
     (num / digits_per_limb) + 1
 }
 
+/// Returns the number of digits in base `base` that always fit in a limb, along
+/// with the "big base", `base` raised to that number. `base` must not be a power
+/// of two.
+#[inline]
+pub fn big_base(base: u64) -> (u32, Limb) {
+    debug_assert!(base > 2 && !base.is_power_of_two());
+
+    let b = base_info(base);
+    (b.digits_per_limb, b.big_base)
+}
+
 /**
  * Converts `nn` limbs at `np` to the given base, storing the output in `out`. `out` is assumed to
  * have enough space for the entire digit. The output is stored from most-significant digit to least.
@@ -101,23 +214,23 @@ pub fn base_digits_to_len(num: usize, base: u32) -> usize {
  * The values in `out` are the raw values of the base. Conversion for output should be done as a second
  * step.
  */
-pub unsafe fn to_base<F: FnMut(u8)>(base: u32, np: Limbs, nn: i32, mut out_byte: F) {
+pub unsafe fn to_base<D: Digit, F: FnMut(D)>(base: u64, np: Limbs, nn: isize, mut out_byte: F) {
     debug_assert!(nn >= 0);
-    debug_assert!(base < BASES.len() as u32);
+    debug_assert!(base <= MAX_BASE);
     debug_assert!(base >= 2);
-    assume(base < BASES.len() as u32);
-    assume(base >= 2);
+    assume!(base >= 2);
 
     if nn <= 0 {
-        out_byte(0);
+        out_byte(D::from_limb(Limb(0)));
         return;
     }
     // Fast path for powers-of-two, since each limb is already in base B^m format
     if base.is_power_of_two() {
-        let bits_per_digit = BASES.get_unchecked(base as usize).big_base.0 as usize;
-        assume(bits_per_digit > 0);
+        let bits_per_digit = base.trailing_zeros() as usize;
+        assume!(bits_per_digit > 0);
+        let mask = low_mask(bits_per_digit);
 
-        let mut n1 = *np.offset((nn - 1) as isize);
+        let mut n1 = *np.offset(nn - 1);
         let cnt = n1.leading_zeros() as usize;
 
         let mut bits = Limb::BITS * (nn as usize) - cnt;
@@ -133,15 +246,19 @@ pub unsafe fn to_base<F: FnMut(u8)>(base: u32, np: Limbs, nn: i32, mut out_byte:
         loop {
             bit_pos -= bits_per_digit as isize;
             while bit_pos >= 0 {
-                let b = ((n1 >> (bit_pos as usize)) & ((Limb(1) << bits_per_digit) - 1)).0 as u8;
+                let b = D::from_limb((n1 >> (bit_pos as usize)) & mask);
                 out_byte(b);
                 bit_pos -= bits_per_digit as isize;
             }
             i -= 1;
             if i < 0 { break; }
 
-            let n0 = (n1 << ((-bit_pos) as usize)) & ((Limb(1) << bits_per_digit) - 1);
-            n1 = *np.offset(i as isize);
+            let n0 = if (-bit_pos) as usize >= Limb::BITS {
+                Limb(0)
+            } else {
+                (n1 << ((-bit_pos) as usize)) & mask
+            };
+            n1 = *np.offset(i);
             bit_pos += Limb::BITS as isize;
             // We have a potential overlap of bits, so get the value of the digit
             // that spans the two limbs.
@@ -149,7 +266,7 @@ pub unsafe fn to_base<F: FnMut(u8)>(base: u32, np: Limbs, nn: i32, mut out_byte:
             //
             //    bbbbbbbb bbbbbbbb
             //          ^---^         Bits for next digit
-            let b = (n0 | (n1 >> (bit_pos as usize))).0 as u8;
+            let b = D::from_limb(n0 | (n1 >> (bit_pos as usize)));
             out_byte(b);
         }
         return;
@@ -158,28 +275,29 @@ pub unsafe fn to_base<F: FnMut(u8)>(base: u32, np: Limbs, nn: i32, mut out_byte:
     to_base_impl(0, base, np, nn, out_byte);
 }
 
-unsafe fn to_base_impl<F: FnMut(u8)>(mut len: u32, base: u32, np: Limbs, mut nn: i32, mut out_byte: F) {
+unsafe fn to_base_impl<D: Digit, F: FnMut(D)>(mut len: u32, base: u64, np: Limbs, mut nn: isize, mut out_byte: F) {
     debug_assert!(base > 2);
 
     let buf_len = num_base_digits(np, nn, base);
-    let mut buf : Vec<u8> = vec![0; buf_len];
+    let mut buf : Vec<D> = vec![D::from_limb(Limb(0)); buf_len];
     let mut r : Vec<Limb> = vec![Limb(0); (nn + 1) as usize];
-    let rp = LimbsMut::new(&mut r[0], 0, r.len() as i32);
+    let rp = LimbsMut::new(&mut r[0], 0, r.len() as isize);
 
     ll::copy_incr(np, rp.offset(1), nn);
 
     let mut sz = 0;
 
-    let s : *mut u8 = &mut buf[0];
-    let mut s = s.offset(buf_len as isize);
+    let s : *mut D = &mut buf[0];
+    let mut s = s.add(buf_len);
 
     let base = Limb(base as ll::limb::BaseInt);
 
     macro_rules! base_impl (
         ($base:expr, $s:expr, $rp:expr, $sz:ident, $nn:ident) => (
             {
-                let digits_per_limb = BASES.get_unchecked($base.0 as usize).digits_per_limb;
-                let big_base = BASES.get_unchecked($base.0 as usize).big_base;
+                let info = base_info($base.0 as u64);
+                let digits_per_limb = info.digits_per_limb;
+                let big_base = info.big_base;
 
                 // Process limbs from least-significant to most, until there is only one
                 // limb left
@@ -202,7 +320,7 @@ unsafe fn to_base_impl<F: FnMut(u8)>(mut len: u32, base: u32, np: Limbs, mut nn:
                         // amount is the next digit we want
                         let (digit, f) = frac.mul_hilo($base);
                         frac = f;
-                        *$s = digit.0 as u8;
+                        *$s = D::from_limb(digit);
                         $s = $s.offset(1);
 
                         $sz += 1;
@@ -220,7 +338,7 @@ unsafe fn to_base_impl<F: FnMut(u8)>(mut len: u32, base: u32, np: Limbs, mut nn:
                 while ul != 0 {
                     let (q, r) = div_unnorm(ul, base);
                     $s = $s.offset(-1);
-                    *$s = r.0 as u8;
+                    *$s = D::from_limb(r);
                     ul = q;
 
                     $sz += 1;
@@ -242,7 +360,7 @@ unsafe fn to_base_impl<F: FnMut(u8)>(mut len: u32, base: u32, np: Limbs, mut nn:
 
     // Output any leading zeros we may want
     while l < len {
-        out_byte(0);
+        out_byte(D::from_limb(Limb(0)));
         len -= 1;
     }
 
@@ -258,12 +376,11 @@ unsafe fn to_base_impl<F: FnMut(u8)>(mut len: u32, base: u32, np: Limbs, mut nn:
  * Converts the base `base` bytestring {bp, bs}, storing the limbs in `out`. `out` is assumed to
  * have enough space to store the result.
  */
-pub unsafe fn from_base(mut out: LimbsMut, bp: *const u8, bs: i32, base: u32) -> usize {
+pub unsafe fn from_base<D: Digit>(mut out: LimbsMut, bp: *const D, bs: isize, base: u64) -> usize {
     debug_assert!(bs > 0);
-    debug_assert!(base < BASES.len() as u32);
+    debug_assert!(base <= MAX_BASE);
     debug_assert!(base >= 2);
-    assume(base < BASES.len() as u32);
-    assume(base >= 2);
+    assume!(base >= 2);
 
     if bs <= 0 {
         *out = Limb(0);
@@ -271,16 +388,16 @@ pub unsafe fn from_base(mut out: LimbsMut, bp: *const u8, bs: i32, base: u32) ->
     }
 
     if base.is_power_of_two() {
-        let bits_per_digit = BASES.get_unchecked(base as usize).big_base.0 as usize;
-        assume(bits_per_digit > 0);
+        let bits_per_digit = base.trailing_zeros() as usize;
+        assume!(bits_per_digit > 0);
 
         let mut size = 0;
 
-        let mut b = bp.offset((bs - 1) as isize);
+        let mut b = bp.offset(bs - 1);
         let mut res_digit = Limb(0);
         let mut next_bitpos = 0;
         while b >= bp {
-            let digit = Limb((*b) as ll::limb::BaseInt);
+            let digit = (*b).to_limb();
 
             res_digit = res_digit | (digit << next_bitpos);
             next_bitpos += bits_per_digit;
@@ -288,7 +405,11 @@ pub unsafe fn from_base(mut out: LimbsMut, bp: *const u8, bs: i32, base: u32) ->
                 next_bitpos -= Limb::BITS;
                 *out.offset(size as isize) = res_digit;
                 size += 1;
-                res_digit = digit >> (bits_per_digit - next_bitpos);
+                res_digit = if next_bitpos == 0 {
+                    Limb(0)
+                } else {
+                    digit >> (bits_per_digit - next_bitpos)
+                };
             }
 
             b = b.offset(-1);
@@ -306,30 +427,32 @@ pub unsafe fn from_base(mut out: LimbsMut, bp: *const u8, bs: i32, base: u32) ->
     from_base_small(out, bp, bs, base)
 }
 
-unsafe fn from_base_small(mut out: LimbsMut, mut bp: *const u8, bs: i32, base: u32) -> usize {
+unsafe fn from_base_small<D: Digit>(mut out: LimbsMut, mut bp: *const D, bs: isize, base: u64) -> usize {
     debug_assert!(base > 2);
-    assume(base > 2);
+    assume!(base > 2);
 
-    let big_base = BASES.get_unchecked(base as usize).big_base;
-    let digits_per_limb = BASES.get_unchecked(base as usize).digits_per_limb;
+    let info = base_info(base);
+    let big_base = info.big_base;
+    let digits_per_limb = info.digits_per_limb;
+
+    let (full_chunks, last_digits) = small_chunks(bs as usize, digits_per_limb);
 
-    let mut i = digits_per_limb;
     let mut size : usize = 0;
-    while i < (bs as u32) {
-        let mut res_digit = Limb((*bp) as ll::limb::BaseInt);
+    for _ in 0..full_chunks {
+        let mut res_digit = (*bp).to_limb();
         bp = bp.offset(1);
 
         if base == 10 {
             let mut j = digits_per_limb - 1;
             while j > 0 {
-                res_digit = res_digit * 10 + ((*bp) as ll::limb::BaseInt);
+                res_digit = res_digit * 10 + (*bp).to_limb();
                 bp = bp.offset(1);
                 j -= 1;
             }
         } else {
             let mut j = digits_per_limb - 1;
             while j > 0 {
-                res_digit = res_digit * (base as ll::limb::BaseInt) + ((*bp) as ll::limb::BaseInt);
+                res_digit = res_digit * (base as ll::limb::BaseInt) + (*bp).to_limb();
                 bp = bp.offset(1);
                 j -= 1;
             }
@@ -341,33 +464,31 @@ unsafe fn from_base_small(mut out: LimbsMut, mut bp: *const u8, bs: i32, base: u
                 size = 1;
             }
         } else {
-            let mut carry = ll::mul_1(out, out.as_const(), size as i32, big_base);
-            carry = carry + ll::add_1(out, out.as_const(), size as i32, res_digit);
+            let mut carry = ll::mul_1(out, out.as_const(), size as isize, big_base);
+            carry = carry + ll::add_1(out, out.as_const(), size as isize, res_digit);
             if carry != 0 {
                 *out.offset(size as isize) = carry;
                 size += 1;
             }
         }
-
-        i += digits_per_limb;
     }
 
     let mut big_base = base as ll::limb::BaseInt;
-    let mut res_digit = Limb((*bp) as ll::limb::BaseInt);
+    let mut res_digit = (*bp).to_limb();
     bp = bp.offset(1);
 
     if base == 10 {
-        let mut j = (bs as u32) - (i - digits_per_limb) - 1;
+        let mut j = last_digits - 1;
         while j > 0 {
-            res_digit = res_digit * 10 + ((*bp) as ll::limb::BaseInt);
+            res_digit = res_digit * 10 + (*bp).to_limb();
             big_base *= 10;
             bp = bp.offset(1);
             j -= 1;
         }
     } else {
-        let mut j = (bs as u32) - (i - digits_per_limb) - 1;
+        let mut j = last_digits - 1;
         while j > 0 {
-            res_digit = res_digit * (base as ll::limb::BaseInt) + ((*bp) as ll::limb::BaseInt);
+            res_digit = res_digit * (base as ll::limb::BaseInt) + (*bp).to_limb();
             bp = bp.offset(1);
             big_base *= base as ll::limb::BaseInt;
             j -= 1;
@@ -380,13 +501,66 @@ unsafe fn from_base_small(mut out: LimbsMut, mut bp: *const u8, bs: i32, base: u
             size = 1;
         }
     } else {
-        let mut carry = ll::mul_1(out, out.as_const(), size as i32, Limb(big_base));
-        carry = carry + ll::add_1(out, out.as_const(), size as i32, res_digit);
+        let mut carry = ll::mul_1(out, out.as_const(), size as isize, Limb(big_base));
+        carry = carry + ll::add_1(out, out.as_const(), size as isize, res_digit);
         if carry != 0 {
             *out.offset(size as isize) = carry;
             size += 1;
         }
     }
 
-    return size;
+    size
+}
+
+/**
+ * Splits `bs` digits (`bs > 0`) into the number of full limb-sized chunks that
+ * `from_base_small` reads before the final chunk, and the number of digits in
+ * that final chunk, which is between 1 and `digits_per_limb`.
+ */
+fn small_chunks(bs: usize, digits_per_limb: u32) -> (usize, usize) {
+    debug_assert!(bs > 0 && digits_per_limb > 0);
+    let per_limb = digits_per_limb as usize;
+    let full_chunks = (bs - 1) / per_limb;
+    let last_digits = bs - full_chunks * per_limb;
+    (full_chunks, last_digits)
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn digits_estimate_fallback() {
+        for base in 3..257u64 {
+            if base.is_power_of_two() { continue; }
+            for &bits in &[1, 2, 63, 64, 65, 1000, 12345, 1 << 20, 1 << 40] {
+                // The fixed-point logarithm is rounded down to 32 fractional bits, so the
+                // fallback can come out higher, by about `bits / 2^32` digits, but never lower
+                let float = super::digits_estimate(bits, base);
+                let fixed = super::digits_estimate_fixed(bits, base);
+                assert!(fixed >= float && fixed <= float + 1 + (bits >> 32),
+                        "base {}, {} bits: {} vs {}", base, bits, fixed, float);
+            }
+        }
+    }
+
+    #[test]
+    fn small_chunks() {
+        assert_eq!(super::small_chunks(1, 19), (0, 1));
+        assert_eq!(super::small_chunks(19, 19), (0, 19));
+        assert_eq!(super::small_chunks(20, 19), (1, 1));
+        assert_eq!(super::small_chunks(38, 19), (1, 19));
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn small_chunks_past_u32() {
+        // Lengths past `u32::MAX` used to be truncated, so check the chunking
+        // without needing a 4GB string
+        let bs = u32::MAX as usize + 10;
+        for &per_limb in &[9, 19, 63] {
+            let (full, last) = super::small_chunks(bs, per_limb);
+            assert!(last >= 1 && last <= per_limb as usize);
+            assert_eq!(full * per_limb as usize + last, bs);
+        }
+        assert_eq!(super::small_chunks(usize::MAX, 19), (usize::MAX / 19, 16));
+    }
 }
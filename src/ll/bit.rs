@@ -23,16 +23,16 @@ use ll::limb_ptr::{Limbs, LimbsMut};
  *
  * If `cnt` is greater than or equal to the number of bits in a limb, the result is undefined.
  */
-pub unsafe fn shl(mut rp: LimbsMut, mut xp: Limbs, mut xs: i32, cnt: u32) -> Limb {
+pub unsafe fn shl(mut rp: LimbsMut, mut xp: Limbs, mut xs: isize, cnt: u32) -> Limb {
     debug_assert!(xs >= 1);
     debug_assert!(cnt >= 1);
     debug_assert!(cnt < Limb::BITS as u32);
-    debug_assert!(same_or_decr(rp, xs, xp, xs));
+    check_overlap!(same_or_decr(rp, xs, xp, xs));
 
     let cnt = cnt as usize;
 
-    rp = rp.offset((xs - 1) as isize);
-    xp = xp.offset((xs - 1) as isize);
+    rp = rp.offset(xs - 1);
+    xp = xp.offset(xs - 1);
 
     let inv_cnt = Limb::BITS - cnt;
 
@@ -54,7 +54,7 @@ pub unsafe fn shl(mut rp: LimbsMut, mut xp: Limbs, mut xs: i32, cnt: u32) -> Lim
 
     *rp = high_limb;
 
-    return ret;
+    ret
 }
 
 /**
@@ -63,11 +63,11 @@ pub unsafe fn shl(mut rp: LimbsMut, mut xp: Limbs, mut xs: i32, cnt: u32) -> Lim
  *
  * If `cnt` is greater than or equal to the number of bits in a limb, the result is undefined.
  */
-pub unsafe fn shr(mut rp: LimbsMut, mut xp: Limbs, mut xs: i32, cnt: u32) -> Limb {
+pub unsafe fn shr(mut rp: LimbsMut, mut xp: Limbs, mut xs: isize, cnt: u32) -> Limb {
     debug_assert!(xs >= 1);
     debug_assert!(cnt >= 1);
     debug_assert!(cnt < Limb::BITS as u32);
-    debug_assert!(same_or_incr(rp, xs, xp, xs));
+    check_overlap!(same_or_incr(rp, xs, xp, xs));
 
     let cnt = cnt as usize;
 
@@ -93,16 +93,16 @@ pub unsafe fn shr(mut rp: LimbsMut, mut xp: Limbs, mut xs: i32, cnt: u32) -> Lim
 
     *rp = low_limb;
 
-    return ret;
+    ret
 }
 
 // Common function for the operations below, since they're all essentially the same
 #[inline(always)]
 unsafe fn bitop<F: Fn(Limb, Limb) -> Limb>(mut wp: LimbsMut,
                                            mut xp: Limbs, mut yp: Limbs,
-                                           n: i32, op: F) {
-    debug_assert!(same_or_incr(wp, n, xp, n));
-    debug_assert!(same_or_incr(wp, n, yp, n));
+                                           n: isize, op: F) {
+    check_overlap!(same_or_incr(wp, n, xp, n));
+    check_overlap!(same_or_incr(wp, n, yp, n));
 
     let mut i = 0;
     while i < n {
@@ -120,7 +120,7 @@ unsafe fn bitop<F: Fn(Limb, Limb) -> Limb>(mut wp: LimbsMut,
  */
 pub unsafe fn and_n(wp: LimbsMut,
                     xp: Limbs, yp: Limbs,
-                    n: i32) {
+                    n: isize) {
     bitop(wp, xp, yp, n, |x, y| x & y);
 }
 
@@ -132,7 +132,7 @@ pub unsafe fn and_n(wp: LimbsMut,
  */
 pub unsafe fn and_not_n(wp: LimbsMut,
                      xp: Limbs, yp: Limbs,
-                     n: i32) {
+                     n: isize) {
     bitop(wp, xp, yp, n, |x, y| x & !y);
 }
 
@@ -144,7 +144,7 @@ pub unsafe fn and_not_n(wp: LimbsMut,
  */
 pub unsafe fn nand_n(wp: LimbsMut,
                      xp: Limbs, yp: Limbs,
-                     n: i32) {
+                     n: isize) {
     bitop(wp, xp, yp, n, |x, y| !(x & y));
 }
 
@@ -154,7 +154,7 @@ pub unsafe fn nand_n(wp: LimbsMut,
  */
 pub unsafe fn or_n(wp: LimbsMut,
                     xp: Limbs, yp: Limbs,
-                    n: i32) {
+                    n: isize) {
     bitop(wp, xp, yp, n, |x, y| x | y);
 }
 
@@ -164,7 +164,7 @@ pub unsafe fn or_n(wp: LimbsMut,
  */
 pub unsafe fn or_not_n(wp: LimbsMut,
                     xp: Limbs, yp: Limbs,
-                    n: i32) {
+                    n: isize) {
     bitop(wp, xp, yp, n, |x, y| x | !y);
 }
 
@@ -176,7 +176,7 @@ pub unsafe fn or_not_n(wp: LimbsMut,
  */
 pub unsafe fn nor_n(wp: LimbsMut,
                     xp: Limbs, yp: Limbs,
-                    n: i32) {
+                    n: isize) {
     bitop(wp, xp, yp, n, |x, y| !(x | y));
 }
 
@@ -186,7 +186,7 @@ pub unsafe fn nor_n(wp: LimbsMut,
  */
 pub unsafe fn xor_n(wp: LimbsMut,
                     xp: Limbs, yp: Limbs,
-                    n: i32) {
+                    n: isize) {
     bitop(wp, xp, yp, n, |x, y| x ^ y);
 }
 
@@ -194,8 +194,8 @@ pub unsafe fn xor_n(wp: LimbsMut,
  * Performs a bitwise inversion ("not") of the n least signficant limbs of `xp`, storing the
  * result in `wp`
  */
-pub unsafe fn not(mut wp: LimbsMut, mut xp: Limbs, n: i32) {
-    debug_assert!(same_or_incr(wp, n, xp, n));
+pub unsafe fn not(mut wp: LimbsMut, mut xp: Limbs, n: isize) {
+    check_overlap!(same_or_incr(wp, n, xp, n));
 
     let mut i = 0;
     while i < n {
@@ -211,7 +211,7 @@ pub unsafe fn not(mut wp: LimbsMut, mut xp: Limbs, n: i32) {
  * of `xp`. The result is stored the result in `wp`, and a carry is
  * returned, if there is one.
  */
-pub unsafe fn twos_complement(mut wp: LimbsMut, mut xp: Limbs, xs: i32) -> Limb {
+pub unsafe fn twos_complement(mut wp: LimbsMut, mut xp: Limbs, xs: isize) -> Limb {
     let mut i = 0;
     let mut carry = Limb(1);
 
@@ -231,40 +231,40 @@ pub unsafe fn twos_complement(mut wp: LimbsMut, mut xp: Limbs, xs: i32) -> Limb
  * Scans for the first 1 bit starting from the least-significant bit the the most, returning
  * the bit index.
  */
-pub unsafe fn scan_1(mut xp: Limbs, mut xs: i32) -> u32 {
+pub unsafe fn scan_1(mut xp: Limbs, mut xs: isize) -> usize {
     debug_assert!(xs > 0);
-    let mut cnt = 0u32;
+    let mut cnt = 0;
 
     while *xp == 0 {
-        cnt += Limb::BITS as u32;
+        cnt += Limb::BITS;
         xp = xp.offset(1);
         xs -= 1;
         if xs == 0 { return cnt; }
     }
-    cnt += (*xp).trailing_zeros() as u32;
+    cnt += (*xp).trailing_zeros() as usize;
 
-    return cnt;
+    cnt
 }
 
 /**
  * Scans for the first 0 bit starting from the least-significant bit the the most, returning
  * the bit index.
  */
-pub unsafe fn scan_0(mut xp: Limbs, mut xs: i32) -> u32 {
+pub unsafe fn scan_0(mut xp: Limbs, mut xs: isize) -> usize {
     debug_assert!(xs > 0);
-    let mut cnt = 0u32;
+    let mut cnt = 0;
 
     while *xp == !0 {
-        cnt += Limb::BITS as u32;
+        cnt += Limb::BITS;
         xp = xp.offset(1);
         xs -= 1;
         if xs == 0 { return cnt; }
     }
-    let mut last = (*xp).0;
+    let mut last = xp.0;
     while last & 1 != 0 {
         cnt += 1;
         last >>= 1;
     }
 
-    return cnt;
+    cnt
 }
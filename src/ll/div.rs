@@ -12,7 +12,6 @@
 //    See the License for the specific language governing permissions and
 //    limitations under the License.
 
-use std::intrinsics::assume;
 use std::cmp::{self, Ordering};
 
 use mem;
@@ -27,16 +26,16 @@ use ll::limb_ptr::{Limbs, LimbsMut};
  * Specifically, the integer part is stored in {qp+qxn, xs} and the fractional part (if any) is
  * stored in {qp, qxn}. The remainder is returned.
  */
-pub unsafe fn divrem_1(mut qp: LimbsMut, qxn: i32,
-                       xp: Limbs, mut xs: i32, d: Limb) -> Limb {
+pub unsafe fn divrem_1(mut qp: LimbsMut, qxn: isize,
+                       xp: Limbs, mut xs: isize, d: Limb) -> Limb {
     debug_assert!(qxn >= 0);
     debug_assert!(xs >= 0);
     debug_assert!(d != 0);
-    debug_assert!(same_or_separate(qp.offset(qxn as isize), xs, xp, xs));
+    check_overlap!(same_or_separate(qp.offset(qxn), xs, xp, xs));
 
-    assume(qxn >= 0);
-    assume(xs >= 0);
-    assume(d != 0);
+    assume!(qxn >= 0);
+    assume!(xs >= 0);
+    assume!(d != 0);
 
     let mut n = xs + qxn;
     if n == 0 { return Limb(0); }
@@ -44,12 +43,12 @@ pub unsafe fn divrem_1(mut qp: LimbsMut, qxn: i32,
     // FIXME (#49): this is used for bounds checks below, which may be
     // unnecessary.
     let qp_lo = qp;
-    qp = qp.offset((n - 1) as isize);
+    qp = qp.offset(n - 1);
 
     let mut r = Limb(0);
     if d.high_bit_set() {
         if xs != 0 {
-            r = *xp.offset((xs - 1) as isize);
+            r = *xp.offset(xs - 1);
             let q = if r >= d { Limb(1) } else { Limb(0) };
             *qp = q;
             if qp > qp_lo {
@@ -62,7 +61,7 @@ pub unsafe fn divrem_1(mut qp: LimbsMut, qxn: i32,
         let dinv = d.invert();
         let mut i = xs - 1;
         while i >= 0 {
-            let n0 = *xp.offset(i as isize);
+            let n0 = *xp.offset(i);
             let (q, rem) = limb::div_preinv(r, n0, d, dinv);
             r = rem;
             *qp = q;
@@ -82,10 +81,10 @@ pub unsafe fn divrem_1(mut qp: LimbsMut, qxn: i32,
             i -= 1;
         }
 
-        return r;
+        r
     } else {
         if xs != 0 {
-            let n1 = *xp.offset((xs - 1) as isize);
+            let n1 = *xp.offset(xs - 1);
             if n1 < d {
                 r = n1;
                 *qp = Limb(0);
@@ -107,11 +106,11 @@ pub unsafe fn divrem_1(mut qp: LimbsMut, qxn: i32,
 
         let dinv = d.invert();
         if xs != 0 {
-            let mut n1 = *xp.offset((xs - 1) as isize);
+            let mut n1 = *xp.offset(xs - 1);
             r = r | (n1 >> (Limb::BITS - cnt));
             let mut i = xs - 2;
             while i >= 0 {
-                let n0 = *xp.offset(i as isize);
+                let n0 = *xp.offset(i);
                 let nshift = (n1 << cnt) | (n0 >> (Limb::BITS - cnt));
                 let (q, rem) = limb::div_preinv(r, nshift, d, dinv);
 
@@ -142,19 +141,19 @@ pub unsafe fn divrem_1(mut qp: LimbsMut, qxn: i32,
             i -= 1;
         }
 
-        return r >> cnt;
+        r >> cnt
     }
 }
 
-pub unsafe fn divrem_2(mut qp: LimbsMut, qxn: i32,
-                       mut np: LimbsMut, ns: i32,
+pub unsafe fn divrem_2(mut qp: LimbsMut, qxn: isize,
+                       mut np: LimbsMut, ns: isize,
                        dp: Limbs) -> Limb {
     debug_assert!(ns >= 2);
     debug_assert!(qxn >= 0);
     debug_assert!((*dp.offset(1)).high_bit_set());
-    debug_assert!(!overlap(qp, ns-2+qxn, np.as_const(), ns) || qp >= np.offset(2));
+    check_overlap!(!overlap(qp, ns-2+qxn, np.as_const(), ns) || qp >= np.offset(2));
 
-    np = np.offset((ns - 2) as isize);
+    np = np.offset(ns - 2);
 
     let d1 = *dp.offset(1);
     let d0 = *dp.offset(0);
@@ -171,7 +170,7 @@ pub unsafe fn divrem_2(mut qp: LimbsMut, qxn: i32,
 
     let dinv = invert_pi(d1, d0);
 
-    qp = qp.offset(qxn as isize);
+    qp = qp.offset(qxn);
 
     let mut i = ns - 2 - 1;
     while i >= 0 {
@@ -180,20 +179,20 @@ pub unsafe fn divrem_2(mut qp: LimbsMut, qxn: i32,
         np = np.offset(-1);
         r1 = r_1;
         r0 = r_0;
-        *qp.offset(i as isize) = q;
+        *qp.offset(i) = q;
 
         i -= 1;
     }
 
     if qxn != 0 {
-        qp = qp.offset(-qxn as isize);
+        qp = qp.offset(-qxn);
         let mut i = qxn - 1;
         while i >= 0 {
             let (q, r_1, r_0) = divrem_3by2(r1, r0, Limb(0), d1, d0, dinv);
             r1 = r_1;
             r0 = r_0;
 
-            *qp.offset(i as isize) = q;
+            *qp.offset(i) = q;
 
             i -= 1;
         }
@@ -202,7 +201,7 @@ pub unsafe fn divrem_2(mut qp: LimbsMut, qxn: i32,
     *np.offset(1) = r1;
     *np = r0;
 
-    return Limb(most_significant_q_limb);
+    Limb(most_significant_q_limb)
 }
 
 #[inline]
@@ -258,13 +257,13 @@ fn divrem_3by2(n2: Limb, n1: Limb, n0: Limb, d1: Limb, d0: Limb, dinv: Limb) ->
  * the quotient is stored to {qp, (ns - ds) + 1}. The remainder is always stored to {rp, ds}.
  */
 pub unsafe fn divrem(mut qp: LimbsMut, mut rp: LimbsMut,
-                     np: Limbs, ns: i32,
-                     dp: Limbs, ds: i32) {
+                     np: Limbs, ns: isize,
+                     dp: Limbs, ds: isize) {
     // Space for at least one limb is always needed, even if
     // (logarithmically) the result will be so small that negative
     // would work.
     let max_result_size = cmp::max((ns - ds) + 1, 1);
-    debug_assert!(!overlap(qp, max_result_size, np, ns));
+    check_overlap!(!overlap(qp, max_result_size, np, ns));
 
     if ns < ds {
         *qp = Limb(0);
@@ -285,42 +284,41 @@ pub unsafe fn divrem(mut qp: LimbsMut, mut rp: LimbsMut,
         }
         2 => {
             let mut tmp = mem::TmpAllocator::new();
-            let dh = *dp.offset((ds - 1) as isize);
+            let dh = *dp.offset(ds - 1);
 
             let cnt = dh.leading_zeros() as usize;
             if cnt == 0 {
                 let np_tmp = tmp.allocate((ns + 1) as usize);
                 ll::copy_incr(np, np_tmp, ns);
                 let qhl = divrem_2(qp, 0, np_tmp, ns, dp);
-                *qp.offset((ns - 2) as isize) = qhl;
+                *qp.offset(ns - 2) = qhl;
                 *rp = *np_tmp;
                 *rp.offset(1) = *np_tmp.offset(1);
             } else {
                 let dtmp = [*dp << cnt, (*dp.offset(1) << cnt) | *dp >> (Limb::BITS - cnt)];
-                let dp_tmp = Limbs::new(&dtmp[0], 0, dtmp.len() as i32);
+                let dp_tmp = Limbs::new(&dtmp[0], 0, dtmp.len() as isize);
 
                 let np_tmp = tmp.allocate((ns + 1) as usize);
                 let c = ll::shl(np_tmp, np, ns, cnt as u32);
-                *np_tmp.offset(ns as isize) = c;
+                *np_tmp.offset(ns) = c;
 
                 let ns_tmp = ns + if c == 0 { 0 } else { 1 };
 
                 let qhl = divrem_2(qp, 0, np_tmp, ns_tmp, dp_tmp);
                 if c == 0 {
-                    *qp.offset((ns - 2) as isize) = qhl;
+                    *qp.offset(ns - 2) = qhl;
                 }
 
-                *rp = (*np_tmp >> cnt) | (*np_tmp.offset(1) << Limb::BITS - cnt);
+                *rp = (*np_tmp >> cnt) | (*np_tmp.offset(1) << (Limb::BITS - cnt));
                 *rp.offset(1) = *np_tmp.offset(1) >> cnt;
             }
-            return;
         }
         _ => {
             let mut tmp = mem::TmpAllocator::new();
 
-            let dh = *dp.offset((ds - 1) as isize);
+            let dh = *dp.offset(ds - 1);
 
-            let cnt = dh.leading_zeros() as u32;
+            let cnt = dh.0.leading_zeros();
             let dp_tmp;
             let np_tmp;
             let mut ns_tmp = ns;
@@ -335,7 +333,7 @@ pub unsafe fn divrem(mut qp: LimbsMut, mut rp: LimbsMut,
 
                 let c = ll::shl(np_tmp, np, ns, cnt);
                 if c > 0 {
-                    *np_tmp.offset(ns as isize) = c;
+                    *np_tmp.offset(ns) = c;
                 } else {
                     ns_tmp -= 1;
                 }
@@ -345,11 +343,11 @@ pub unsafe fn divrem(mut qp: LimbsMut, mut rp: LimbsMut,
                 dp_tmp = dtmp.as_const();
             }
 
-            let dinv = invert_pi(*dp_tmp.offset((ds - 1) as isize),
-                                 *dp_tmp.offset((ds - 2) as isize));
+            let dinv = invert_pi(*dp_tmp.offset(ds - 1),
+                                 *dp_tmp.offset(ds - 2));
             let qh = sb_div(qp, np_tmp, ns_tmp, dp_tmp, ds, dinv);
             if qh > 0 {
-                *qp.offset((ns - ds) as isize) = qh;
+                *qp.offset(ns - ds) = qh;
             }
 
             if cnt == 0 {
@@ -379,49 +377,49 @@ pub unsafe fn divrem(mut qp: LimbsMut, mut rp: LimbsMut,
  * It is also assumed that `ns >= ds`.
  */
 unsafe fn sb_div(qp: LimbsMut,
-                 np: LimbsMut, ns: i32,
-                 dp: Limbs, ds: i32,
+                 np: LimbsMut, ns: isize,
+                 dp: Limbs, ds: isize,
                  dinv: Limb) -> Limb {
     debug_assert!(ds > 2);
     debug_assert!(ns >= ds);
-    debug_assert!((*dp.offset((ds - 1) as isize)).high_bit_set());
+    debug_assert!((*dp.offset(ds - 1)).high_bit_set());
 
-    let mut np = np.offset(ns as isize);
+    let mut np = np.offset(ns);
 
     // If N < D*B^(m-n-1), then the high limb is zero. If not, then the high limb
     // is 1 and we subtract D*B^(m-n-1) from N.
-    let qh = if let Ordering::Less = ll::cmp(np.offset(-ds as isize).as_const(), dp, ds) {
+    let qh = if let Ordering::Less = ll::cmp(np.offset(-ds).as_const(), dp, ds) {
         Limb(0)
     } else {
-        let np = np.offset(-ds as isize);
+        let np = np.offset(-ds);
         ll::sub_n(np, np.as_const(), dp, ds);
         Limb(1)
     };
 
-    let mut qp = qp.offset((ns - ds) as isize);
+    let mut qp = qp.offset(ns - ds);
 
-    let ds = (ds - 2) as isize;
+    let ds = ds - 2;
 
     let d1 = *dp.offset(ds + 1);
-    let d0 = *dp.offset(ds + 0);
+    let d0 = *dp.offset(ds);
 
     np = np.offset(-2);
 
     let mut n2 = *np.offset(1);
 
-    let mut i = ns - (ds + 2) as i32;
+    let mut i = ns - (ds + 2);
     while i > 0 {
         np = np.offset(-1);
         let n1 = *np.offset(1);
         let n0 = *np;
 
         let q = if n2 == d1 && n1 == d0 {
-            ll::submul_1(np.offset(-ds), dp, (ds + 2) as i32, Limb(!0));
+            ll::submul_1(np.offset(-ds), dp, ds + 2, Limb(!0));
             n2 = *np.offset(1);
             Limb(!0)
         } else {
             let (q, r1, mut r0) = divrem_3by2(n2, n1, n0, d1, d0, dinv);
-            let cy = ll::submul_1(np.offset(-ds), dp, ds as i32, q);
+            let cy = ll::submul_1(np.offset(-ds), dp, ds, q);
 
             n2 = r1;
 
@@ -434,7 +432,7 @@ unsafe fn sb_div(qp: LimbsMut,
             *np = r0;
 
             if cy {
-                n2 = d1 + n2 + ll::add_n(np.offset(-ds), np.offset(-ds).as_const(), dp, (ds + 1) as i32);
+                n2 = d1 + n2 + ll::add_n(np.offset(-ds), np.offset(-ds).as_const(), dp, ds + 1);
                 q - 1
             } else {
                 q
@@ -449,5 +447,5 @@ unsafe fn sb_div(qp: LimbsMut,
 
     *np.offset(1) = n2;
 
-    return qh;
+    qh
 }
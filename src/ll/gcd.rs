@@ -17,7 +17,8 @@ use std::cmp::Ordering;
 use ll;
 use ll::limb_ptr::LimbsMut;
 
-pub unsafe fn gcd(mut gp: LimbsMut, mut ap: LimbsMut, mut an: i32, mut bp: LimbsMut, mut bn: i32) -> i32 {
+#[allow(clippy::unnecessary_cast)] // `Limb::trailing_zeros` gives a `u32` with 32-bit limbs
+pub unsafe fn gcd(mut gp: LimbsMut, mut ap: LimbsMut, mut an: isize, mut bp: LimbsMut, mut bn: isize) -> isize {
     assert!(an >= bn);
 
     let mut gc = 0;
@@ -91,7 +92,7 @@ pub unsafe fn gcd(mut gp: LimbsMut, mut ap: LimbsMut, mut an: i32, mut bp: Limbs
     if trailing > 0 {
         let v = ll::shl(gp, gp.as_const(), bn, trailing);
         if v > 0 {
-            *gp.offset(bn as isize) = v;
+            *gp.offset(bn) = v;
         }
     }
 
@@ -12,7 +12,6 @@
 //    See the License for the specific language governing permissions and
 //    limitations under the License.
 
-use std;
 use std::ops::{
     Add, Sub, Mul, Div, Rem, Neg,
     Shl, Shr, Not, BitAnd, BitOr, BitXor
@@ -20,8 +19,6 @@ use std::ops::{
 use std::cmp::{Ordering, PartialEq, PartialOrd};
 use std::fmt;
 
-use std::intrinsics::assume;
-
 use ::std::num::Wrapping;
 #[allow(dead_code)]
 type Word = Wrapping<usize>;
@@ -33,9 +30,9 @@ macro_rules! if_cfg {
     )
 }
 
-#[cfg(target_pointer_width = "32")]
+#[cfg(limb_width = "32")]
 pub type BaseInt = u32;
-#[cfg(target_pointer_width = "64")]
+#[cfg(limb_width = "64")]
 pub type BaseInt = u64;
 
 /**
@@ -43,6 +40,8 @@ pub type BaseInt = u64;
  *
  * A "Limb" is a single digit in base 2^word size.
  */
+// The comparisons are written out by hand to force inlining, but agree with the derived ones.
+#[allow(clippy::derived_hash_with_manual_eq, clippy::derive_ord_xor_partial_ord)]
 #[derive(Copy, Eq, Ord, Hash)]
 pub struct Limb(pub BaseInt);
 
@@ -52,9 +51,9 @@ impl Clone for Limb {
 }
 
 impl Limb {
-    #[cfg(target_pointer_width = "32")]
+    #[cfg(limb_width = "32")]
     pub const BITS : usize = 32;
-    #[cfg(target_pointer_width = "64")]
+    #[cfg(limb_width = "64")]
     pub const BITS : usize = 64;
 
     pub const B : Limb = Limb(1 << (Limb::BITS / 2));
@@ -76,10 +75,8 @@ impl Limb {
      */
     #[inline(always)]
     pub fn add_overflow(self, other: Limb) -> (Limb, bool) {
-        unsafe {
-            let (val, c) = std::intrinsics::add_with_overflow(self.0, other.0);
-            (Limb(val), c)
-        }
+        let (val, c) = self.0.overflowing_add(other.0);
+        (Limb(val), c)
     }
 
     /**
@@ -87,10 +84,8 @@ impl Limb {
      */
     #[inline(always)]
     pub fn sub_overflow(self, other: Limb) -> (Limb, bool) {
-        unsafe {
-            let (val, c) = std::intrinsics::sub_with_overflow(self.0, other.0);
-            (Limb(val), c)
-        }
+        let (val, c) = self.0.overflowing_sub(other.0);
+        (Limb(val), c)
     }
 
     /**
@@ -256,7 +251,7 @@ impl Div<Limb> for Limb {
     #[inline(always)]
     fn div(self, other: Limb) -> Limb {
         debug_assert!(other.0 != 0);
-        unsafe { assume(other.0 != 0); }
+        assume!(other.0 != 0);
         Limb(self.0 / other.0)
     }
 }
@@ -267,7 +262,7 @@ impl Div<BaseInt> for Limb {
     #[inline(always)]
     fn div(self, other: BaseInt) -> Limb {
         debug_assert!(other != 0);
-        unsafe { assume(other != 0); }
+        assume!(other != 0);
         Limb(self.0 / other)
     }
 }
@@ -278,7 +273,7 @@ impl Rem<Limb> for Limb {
     #[inline(always)]
     fn rem(self, other: Limb) -> Limb {
         debug_assert!(other.0 != 0);
-        unsafe { assume(other.0 != 0); }
+        assume!(other.0 != 0);
         Limb(self.0 % other.0)
     }
 }
@@ -289,7 +284,7 @@ impl Rem<BaseInt> for Limb {
     #[inline(always)]
     fn rem(self, other: BaseInt) -> Limb {
         debug_assert!(other != 0);
-        unsafe { assume(other != 0); }
+        assume!(other != 0);
         Limb(self.0 % other)
     }
 }
@@ -362,13 +357,9 @@ impl PartialEq<Limb> for Limb {
     fn eq(&self, other: &Limb) -> bool {
         self.0 == other.0
     }
-
-    #[inline(always)]
-    fn ne(&self, other: &Limb) -> bool {
-        self.0 != other.0
-    }
 }
 
+#[allow(clippy::non_canonical_partial_ord_impl)]
 impl PartialOrd<Limb> for Limb {
     #[inline(always)]
     fn partial_cmp(&self, other: &Limb) -> Option<Ordering> {
@@ -393,11 +384,6 @@ impl PartialEq<BaseInt> for Limb {
     fn eq(&self, other: &BaseInt) -> bool {
         self.0 == *other
     }
-
-    #[inline(always)]
-    fn ne(&self, other: &BaseInt) -> bool {
-        self.0 != *other
-    }
 }
 
 impl PartialOrd<BaseInt> for Limb {
@@ -433,37 +419,41 @@ impl fmt::Display for Limb {
 
 pub fn mul(u: Limb, v: Limb) -> (Limb, Limb) {
     if_cfg! {
-        #[cfg(all(not(feature="fallbacks"),target_arch="x86_64"))]
+        #[cfg(all(feature="unstable",not(feature="fallbacks"),target_arch="x86_64",limb_width="64"))]
         #[inline(always)]
         fn mul_impl(u: Limb, v: Limb) -> (Limb, Limb) {
-            let mut high: Limb = Limb(0);
-            let mut low: Limb  = Limb(0);
+            let high: BaseInt;
+            let low: BaseInt;
             unsafe {
-                asm!("mulq $3"
-                     : "={rdx}"(high.0), "={rax}"(low.0)
-                     : "{rax}"(u.0), "r"(v.0));
+                ::std::arch::asm!("mul {v}",
+                                  v = in(reg) v.0,
+                                  inout("rax") u.0 => low,
+                                  out("rdx") high,
+                                  options(pure, nomem, nostack));
             }
 
-            (high, low)
+            (Limb(high), Limb(low))
         }
 
-        #[cfg(all(not(feature="fallbacks"),target_arch="x86"))]
+        #[cfg(all(feature="unstable",not(feature="fallbacks"),target_arch="x86"))]
         #[inline(always)]
         fn mul_impl(u: Limb, v: Limb) -> (Limb, Limb) {
-            let mut high: Limb = Limb(0);
-            let mut low: Limb  = Limb(0);
+            let high: BaseInt;
+            let low: BaseInt;
             unsafe {
-                asm!("mull $3"
-                     : "={edx}"(high.0), "={eax}"(low.0)
-                     : "{eax}"(u.0), "r"(v.0));
+                ::std::arch::asm!("mul {v}",
+                                  v = in(reg) v.0,
+                                  inout("eax") u.0 => low,
+                                  out("edx") high,
+                                  options(pure, nomem, nostack));
             }
 
-            (high, low)
+            (Limb(high), Limb(low))
         }
 
         #[cfg(all(  not(feature="fallbacks"),
-                    not(target_arch="x86"),
-                    target_pointer_width="32",
+                    not(all(feature="unstable", target_arch="x86")),
+                    limb_width="32",
             ))]
         #[inline(always)]
         fn mul_impl(u: Limb, v: Limb) -> (Limb, Limb) {
@@ -473,6 +463,18 @@ pub fn mul(u: Limb, v: Limb) -> (Limb, Limb) {
             (Limb((p>>32) as u32), Limb(p as u32))
         }
 
+        #[cfg(all(  not(feature="fallbacks"),
+                    not(all(feature="unstable", target_arch="x86_64")),
+                    limb_width="64",
+            ))]
+        #[inline(always)]
+        fn mul_impl(u: Limb, v: Limb) -> (Limb, Limb) {
+            let u = u.0 as u128;
+            let v = v.0 as u128;
+            let p = u*v;
+            (Limb((p>>64) as u64), Limb(p as u64))
+        }
+
         fallback:
         #[inline(always)]
         fn mul_impl(u: Limb, v: Limb) -> (Limb, Limb) {
@@ -508,7 +510,7 @@ pub fn mul(u: Limb, v: Limb) -> (Limb, Limb) {
 
         }
     }
-    return mul_impl(u, v);
+    mul_impl(u, v)
 }
 
 /**
@@ -517,39 +519,45 @@ pub fn mul(u: Limb, v: Limb) -> (Limb, Limb) {
 #[inline(always)]
 pub fn add_2(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
     if_cfg! {
-        #[cfg(all(not(feature="fallbacks"),target_arch="x86_64"))]
+        #[cfg(all(feature="unstable",not(feature="fallbacks"),target_arch="x86_64",limb_width="64"))]
         #[inline(always)]
         fn add_2_impl(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
-            let mut high: Limb = Limb(0);
-            let mut low: Limb  = Limb(0);
+            let high: BaseInt;
+            let low: BaseInt;
             unsafe {
-                asm!("addq $4, $0
-                      adcq $5, $1"
-                     : "=r"(low.0), "=r"(high.0)
-                     : "0"(al.0), "1"(ah.0), "r"(bl.0), "r"(bh.0));
+                ::std::arch::asm!("add {l}, {bl}",
+                                  "adc {h}, {bh}",
+                                  l = inout(reg) al.0 => low,
+                                  h = inout(reg) ah.0 => high,
+                                  bl = in(reg) bl.0,
+                                  bh = in(reg) bh.0,
+                                  options(pure, nomem, nostack));
             }
 
-            (high, low)
+            (Limb(high), Limb(low))
         }
 
-        #[cfg(all(not(feature="fallbacks"),target_arch="x86"))]
+        #[cfg(all(feature="unstable",not(feature="fallbacks"),target_arch="x86"))]
         #[inline(always)]
         fn add_2_impl(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
-            let mut high: Limb = Limb(0);
-            let mut low: Limb  = Limb(0);
+            let high: BaseInt;
+            let low: BaseInt;
             unsafe {
-                asm!("addl $4, $0
-                      adcl $5, $1"
-                     : "=r"(low.0), "=r"(high.0)
-                     : "0"(al.0), "1"(ah.0), "r"(bl.0), "r"(bh.0));
+                ::std::arch::asm!("add {l}, {bl}",
+                                  "adc {h}, {bh}",
+                                  l = inout(reg) al.0 => low,
+                                  h = inout(reg) ah.0 => high,
+                                  bl = in(reg) bl.0,
+                                  bh = in(reg) bh.0,
+                                  options(pure, nomem, nostack));
             }
 
-            (high, low)
+            (Limb(high), Limb(low))
         }
 
         #[cfg(all(  not(feature="fallbacks"),
-                    not(target_arch="x86"),
-                    target_pointer_width="32",
+                    not(all(feature="unstable", target_arch="x86")),
+                    limb_width="32",
             ))]
         #[inline(always)]
         fn add_2_impl(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
@@ -559,6 +567,18 @@ pub fn add_2(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
             (Limb((s>>32) as u32), Limb(s as u32))
         }
 
+        #[cfg(all(  not(feature="fallbacks"),
+                    not(all(feature="unstable", target_arch="x86_64")),
+                    limb_width="64",
+            ))]
+        #[inline(always)]
+        fn add_2_impl(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
+            let a = ((ah.0 as u128) << 64) | al.0 as u128;
+            let b = ((bh.0 as u128) << 64) | bl.0 as u128;
+            let s = a.overflowing_add(b).0;
+            (Limb((s>>64) as u64), Limb(s as u64))
+        }
+
         fallback:
         #[inline(always)]
         fn add_2_impl(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
@@ -568,7 +588,7 @@ pub fn add_2(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
             (high, low)
         }
     }
-    return add_2_impl(ah, al, bh, bl);
+    add_2_impl(ah, al, bh, bl)
 }
 
 /**
@@ -577,39 +597,45 @@ pub fn add_2(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
 #[inline(always)]
 pub fn sub_2(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
     if_cfg! {
-        #[cfg(all(not(feature="fallbacks"),target_arch="x86_64"))]
+        #[cfg(all(feature="unstable",not(feature="fallbacks"),target_arch="x86_64",limb_width="64"))]
         #[inline(always)]
         fn sub_2_impl(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
-            let mut high: Limb = Limb(0);
-            let mut low: Limb  = Limb(0);
+            let high: BaseInt;
+            let low: BaseInt;
             unsafe {
-                asm!("subq $4, $0
-                      sbbq $5, $1"
-                     : "=r"(low.0), "=r"(high.0)
-                     : "0"(al.0), "1"(ah.0), "r"(bl.0), "r"(bh.0));
+                ::std::arch::asm!("sub {l}, {bl}",
+                                  "sbb {h}, {bh}",
+                                  l = inout(reg) al.0 => low,
+                                  h = inout(reg) ah.0 => high,
+                                  bl = in(reg) bl.0,
+                                  bh = in(reg) bh.0,
+                                  options(pure, nomem, nostack));
             }
 
-            (high, low)
+            (Limb(high), Limb(low))
         }
 
-        #[cfg(all(not(feature="fallbacks"),target_arch="x86"))]
+        #[cfg(all(feature="unstable",not(feature="fallbacks"),target_arch="x86"))]
         #[inline(always)]
         fn sub_2_impl(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
-            let mut high: Limb = Limb(0);
-            let mut low: Limb  = Limb(0);
+            let high: BaseInt;
+            let low: BaseInt;
             unsafe {
-                asm!("subl $4, $0
-                      sbbl $5, $1"
-                     : "=r"(low.0), "=r"(high.0)
-                     : "0"(al.0), "1"(ah.0), "r"(bl.0), "r"(bh.0));
+                ::std::arch::asm!("sub {l}, {bl}",
+                                  "sbb {h}, {bh}",
+                                  l = inout(reg) al.0 => low,
+                                  h = inout(reg) ah.0 => high,
+                                  bl = in(reg) bl.0,
+                                  bh = in(reg) bh.0,
+                                  options(pure, nomem, nostack));
             }
 
-            (high, low)
+            (Limb(high), Limb(low))
         }
 
         #[cfg(all(  not(feature="fallbacks"),
-                    not(target_arch="x86"),
-                    target_pointer_width="32",
+                    not(all(feature="unstable", target_arch="x86")),
+                    limb_width="32",
             ))]
         #[inline(always)]
         fn sub_2_impl(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
@@ -619,6 +645,18 @@ pub fn sub_2(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
             (Limb((s>>32) as u32), Limb(s as u32))
         }
 
+        #[cfg(all(  not(feature="fallbacks"),
+                    not(all(feature="unstable", target_arch="x86_64")),
+                    limb_width="64",
+            ))]
+        #[inline(always)]
+        fn sub_2_impl(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
+            let a = ((ah.0 as u128) << 64) | al.0 as u128;
+            let b = ((bh.0 as u128) << 64) | bl.0 as u128;
+            let s = a.overflowing_sub(b).0;
+            (Limb((s>>64) as u64), Limb(s as u64))
+        }
+
         fallback:
         #[inline(always)]
         fn sub_2_impl(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
@@ -628,7 +666,7 @@ pub fn sub_2(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
             (high, low)
         }
     }
-    return sub_2_impl(ah, al, bh, bl);
+    sub_2_impl(ah, al, bh, bl)
 }
 
 /**
@@ -644,35 +682,39 @@ pub fn sub_2(ah: Limb, al: Limb, bh: Limb, bl: Limb) -> (Limb, Limb) {
 pub fn div(nh: Limb, nl: Limb, d: Limb) -> (Limb, Limb) {
 
     if_cfg! {
-        #[cfg(all(not(feature="fallbacks"),target_arch="x86_64"))]
+        #[cfg(all(feature="unstable",not(feature="fallbacks"),target_arch="x86_64",limb_width="64"))]
         #[inline(always)]
         fn div_impl(nh: Limb, nl: Limb, d: Limb) -> (Limb, Limb) {
-            let mut q: Limb = Limb(0);
-            let mut r: Limb = Limb(0);
+            let q: BaseInt;
+            let r: BaseInt;
             unsafe {
-                asm!("divq $4"
-                     : "={rdx}"(r.0), "={rax}"(q.0)
-                     : "0"(nh.0), "1"(nl.0), "r"(d.0));
+                ::std::arch::asm!("div {d}",
+                                  d = in(reg) d.0,
+                                  inout("rdx") nh.0 => r,
+                                  inout("rax") nl.0 => q,
+                                  options(nomem, nostack));
             }
-            (q, r)
+            (Limb(q), Limb(r))
         }
 
-        #[cfg(all(not(feature="fallbacks"),target_arch="x86"))]
+        #[cfg(all(feature="unstable",not(feature="fallbacks"),target_arch="x86"))]
         #[inline(always)]
         fn div_impl(nh: Limb, nl: Limb, d: Limb) -> (Limb, Limb) {
-            let mut q: Limb = Limb(0);
-            let mut r: Limb = Limb(0);
+            let q: BaseInt;
+            let r: BaseInt;
             unsafe {
-                asm!("divl $4"
-                     : "={edx}"(r.0), "={eax}"(q.0)
-                     : "0"(nh.0), "1"(nl.0), "r"(d.0));
+                ::std::arch::asm!("div {d}",
+                                  d = in(reg) d.0,
+                                  inout("edx") nh.0 => r,
+                                  inout("eax") nl.0 => q,
+                                  options(nomem, nostack));
             }
-            (q, r)
+            (Limb(q), Limb(r))
         }
 
         #[cfg(all(  not(feature="fallbacks"),
-                    not(target_arch="x86"),
-                    target_pointer_width="32",
+                    not(all(feature="unstable", target_arch="x86")),
+                    limb_width="32",
             ))]
         #[inline(always)]
         fn div_impl(nh: Limb, nl: Limb, d: Limb) -> (Limb, Limb) {
@@ -681,6 +723,17 @@ pub fn div(nh: Limb, nl: Limb, d: Limb) -> (Limb, Limb) {
             (Limb((n/d) as u32), Limb((n%d) as u32))
         }
 
+        #[cfg(all(  not(feature="fallbacks"),
+                    not(all(feature="unstable", target_arch="x86_64")),
+                    limb_width="64",
+            ))]
+        #[inline(always)]
+        fn div_impl(nh: Limb, nl: Limb, d: Limb) -> (Limb, Limb) {
+            let n = (nh.0 as u128) << 64 | nl.0 as u128;
+            let d = d.0 as u128;
+            (Limb((n/d) as u64), Limb((n%d) as u64))
+        }
+
         fallback:
         #[inline(always)]
         fn div_impl(nh: Limb, nl: Limb, d: Limb) -> (Limb, Limb) {
@@ -737,12 +790,10 @@ pub fn div(nh: Limb, nl: Limb, d: Limb) -> (Limb, Limb) {
 
     debug_assert!(d.high_bit_set());
     debug_assert!(nh < d);
-    unsafe {
-        assume(nh < d);
-        assume(d.high_bit_set());
-    }
+    assume!(nh < d);
+    assume!(d.high_bit_set());
 
-    return div_impl(nh, nl, d);
+    div_impl(nh, nl, d)
 }
 
 /**
@@ -769,11 +820,11 @@ pub fn div_preinv(nh: Limb, nl: Limb, d: Limb, dinv: Limb) -> (Limb, Limb) {
 
 #[test]
 fn test_bug_div_1() {
-    let (q,r) = div(Limb(0), Limb(10), Limb((usize::max_value()/2+1) as BaseInt));
+    let (q,r) = div(Limb(0), Limb(10), Limb(BaseInt::MAX/2+1));
     assert_eq!((q.0, r.0), (0, 10));
 }
 
-#[cfg(target_pointer_width = "64")]
+#[cfg(limb_width = "64")]
 #[test]
 fn test_bug_mul_1() {
     let (h,l) = mul(Limb(18446744073709551615), Limb(7868907223611932671));
@@ -14,20 +14,31 @@
 
 use ll::limb::Limb;
 
-use std::{fmt, ops};
-#[cfg(debug_assertions)]
-use std::mem;
+use std::{fmt, mem, ops};
 use std::cmp::Ordering;
 
+// Bounds are checked with debug assertions, unless the `checked-limbs`
+// feature asks for them to be checked in release builds too.
+#[cfg(feature = "checked-limbs")]
+macro_rules! check_bounds {
+    ($($arg:tt)*) => (assert!($($arg)*))
+}
+
+#[cfg(not(feature = "checked-limbs"))]
+macro_rules! check_bounds {
+    ($($arg:tt)*) => (debug_assert!($($arg)*))
+}
 
-/// A version of `*const Limb` that is bounds-checked when debug assertions are on
+/// A version of `*const Limb` that is bounds-checked when debug assertions or the
+/// `checked-limbs` feature are on
 #[derive(Copy, Clone, Debug)]
 pub struct Limbs {
     ptr: *const Limb,
     bounds: Bounds,
 }
 
-/// A version of `*mut Limb` that is bounds-checked when debug assertions are on
+/// A version of `*mut Limb` that is bounds-checked when debug assertions or the
+/// `checked-limbs` feature are on
 #[derive(Copy, Clone)]
 pub struct LimbsMut {
     ptr: *mut Limb,
@@ -39,7 +50,7 @@ macro_rules! api {
         impl $ty {
             /// Create a new instance, pointing at `base` and valid
             /// from `base.offset(start)` to `base.offset(end)`.
-            pub unsafe fn new(base: $ptr, start: i32, end: i32) -> $ty {
+            pub unsafe fn new(base: $ptr, start: isize, end: isize) -> $ty {
                 $ty {
                     ptr: base,
                     bounds: Bounds::new(base as usize, start, end)
@@ -50,13 +61,26 @@ macro_rules! api {
             /// current location.
             #[inline]
             pub unsafe fn offset(self, x: isize) -> $ty {
-                debug_assert!(self.bounds.offset_valid(self.ptr as usize, x),
+                check_bounds!(self.bounds.offset_valid(self.ptr as usize, x),
                               "invalid offset of {:?} by {}, which should be in {:?}", self.ptr, x, self.bounds);
                 $ty {
                     ptr: self.ptr.offset(x),
                     bounds: self.bounds,
                 }
             }
+
+            /// Read the Limb `x` places from the current location, or
+            /// `None` if that's outside the bounds being checked. Without
+            /// bounds checks, this always reads.
+            #[inline]
+            pub unsafe fn get(self, x: isize) -> Option<Limb> {
+                let ptr = (self.ptr as usize).wrapping_add((x * mem::size_of::<Limb>() as isize) as usize);
+                if self.bounds.can_deref(ptr) {
+                    Some(*(ptr as *const Limb))
+                } else {
+                    None
+                }
+            }
         }
 
         impl PartialEq for $ty {
@@ -66,7 +90,7 @@ macro_rules! api {
         }
         impl PartialOrd for $ty {
             fn partial_cmp(&self, other: &$ty) -> Option<Ordering> {
-                self.ptr.partial_cmp(&other.ptr)
+                Some(self.cmp(other))
             }
         }
         impl Eq for $ty {}
@@ -79,7 +103,7 @@ macro_rules! api {
         impl ops::Deref for $ty {
             type Target = Limb;
             fn deref(&self) -> &Limb {
-                debug_assert!(self.bounds.can_deref(self.ptr as usize),
+                check_bounds!(self.bounds.can_deref(self.ptr as usize),
                               "invalid deref of {:?}, which should be in {:?}", self.ptr, self.bounds);
                 unsafe { &*self.ptr }
             }
@@ -101,28 +125,29 @@ impl LimbsMut {
 }
 impl ops::DerefMut for LimbsMut {
     fn deref_mut(&mut self) -> &mut Limb {
-        debug_assert!(self.bounds.can_deref(self.ptr as usize),
+        check_bounds!(self.bounds.can_deref(self.ptr as usize),
                       "invalid mut deref of {:?}, which should be in {:?}", self.ptr, self.bounds);
         unsafe { &mut *self.ptr }
     }
 }
 
 // This is where the magic is at: the bounds are only stored/checked
-// in debug mode; release mode just powers ahead without any overhead.
+// in debug mode or with `checked-limbs`; otherwise release mode just
+// powers ahead without any overhead.
 
 #[derive(Copy, Clone)]
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "checked-limbs"))]
 struct Bounds {
     lo: usize,
     hi: usize,
 }
 #[derive(Copy, Clone)]
-#[cfg(not(debug_assertions))]
+#[cfg(not(any(debug_assertions, feature = "checked-limbs")))]
 struct Bounds;
 
-#[cfg(debug_assertions)]
+#[cfg(any(debug_assertions, feature = "checked-limbs"))]
 impl Bounds {
-    fn new(ptr: usize, start: i32, end: i32) -> Bounds {
+    fn new(ptr: usize, start: isize, end: isize) -> Bounds {
         assert!(start <= end);
         Bounds {
             lo: ptr + start as usize * mem::size_of::<Limb>(),
@@ -140,20 +165,20 @@ impl Bounds {
         self.lo <= new && new <= self.hi
     }
 }
-#[cfg(not(debug_assertions))]
+#[cfg(not(any(debug_assertions, feature = "checked-limbs")))]
 impl Bounds {
-    fn new(_ptr: usize, _start: i32, _end: i32) -> Bounds { Bounds }
+    fn new(_ptr: usize, _start: isize, _end: isize) -> Bounds { Bounds }
     #[inline]
     fn can_deref(self, _ptr: usize) -> bool { true }
     #[inline]
     fn offset_valid(self, _ptr: usize, _offset: isize) -> bool { true }
 }
 impl fmt::Debug for Bounds {
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "checked-limbs"))]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Bounds {{ lo: 0x{:x}, hi: 0x{:x} }}", self.lo, self.hi)
     }
-    #[cfg(not(debug_assertions))]
+    #[cfg(not(any(debug_assertions, feature = "checked-limbs")))]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Bounds {{ <optimised out> }}")
     }
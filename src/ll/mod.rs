@@ -29,6 +29,10 @@
  * The functions in this module assume that all inputs are valid, though some checking is performed
  * in debug builds.
  *
+ * The `safe` submodule wraps the most common operations in functions that take slices instead,
+ * checking their requirements at runtime. It's the place to start when writing new limb-level
+ * code outside this crate.
+ *
  * ## Limbs
  *
  * A `Limb` is a single "digit" in an arbitrary-precision integer. To explain, consider the
@@ -44,8 +48,9 @@
  * `Limb` itself implements a number of useful methods. The basic mathematical operators are
  * implemented to provide wrapping behaviour by default. The most basic operations are also
  * implemented on `Limb`, notably multiplication with a two-word output and division of a two-word
- * numerator by a one-word denominator. The implementations of these operations are done with
- * inline assembly on x86 platforms with a Rust implementation as fallback.
+ * numerator by a one-word denominator. The implementations of these operations use double-width
+ * integer arithmetic, or inline assembly on x86 platforms with the `unstable` feature, with a
+ * portable Rust implementation as fallback.
  *
  * ## Integer representation
  *
@@ -75,10 +80,64 @@
  * * Pointer and matching size are kept close together in the argument list.
  * * Sizes come after the matching pointers. For example, `add_n` takes two pointers and a length,
  *   the length applies to both pointers and so comes after both of them.
+ *
+ * ## Safety
+ *
+ * Every pointer passed to the unsafe functions here must be valid for the number of limbs given
+ * with it, and outputs may only overlap inputs where a function's documentation allows it. Any
+ * further requirements, like a normalized divisor, are given in the documentation of each
+ * function rather than in a separate `# Safety` section.
  */
 
-use std::intrinsics::abort;
+#![allow(clippy::missing_safety_doc)]
+
 use std::cmp::Ordering;
+use std::fmt;
+
+/// Tells the optimizer that a condition holds. Only the `unstable` feature
+/// passes it on, on stable Rust it's checked in debug builds instead.
+#[cfg(feature = "unstable")]
+macro_rules! assume {
+    ($cond:expr) => (unsafe { ::std::hint::assert_unchecked($cond) })
+}
+
+#[cfg(not(feature = "unstable"))]
+macro_rules! assume {
+    ($cond:expr) => (debug_assert!($cond))
+}
+
+/// Checks one of the `overlap`/`same_or_*` requirements between an output
+/// and an input range. Like the bounds checks on `Limbs`, this is a debug
+/// assertion unless the `checked-limbs` feature is on. A failure dumps both
+/// ranges.
+macro_rules! check_overlap {
+    ($($f:ident)::+($wp:expr, $wn:expr, $xp:expr, $xn:expr)) => (
+        check_overlap!(@ $($f)::+($wp, $wn, $xp, $xn), $wp, $wn, $xp, $xn)
+    );
+    (!$($f:ident)::+($wp:expr, $wn:expr, $xp:expr, $xn:expr)) => (
+        check_overlap!(@ !$($f)::+($wp, $wn, $xp, $xn), $wp, $wn, $xp, $xn)
+    );
+    (!$($f:ident)::+($wp:expr, $wn:expr, $xp:expr, $xn:expr) || $alt:expr) => (
+        check_overlap!(@ !$($f)::+($wp, $wn, $xp, $xn) || $alt, $wp, $wn, $xp, $xn)
+    );
+    (@ $cond:expr, $wp:expr, $wn:expr, $xp:expr, $xn:expr) => (
+        if cfg!(any(debug_assertions, feature = "checked-limbs")) && !($cond) {
+            ::ll::overlap_violation(stringify!($cond), $wp, $wn, $xp, $xn);
+        }
+    );
+}
+
+/// Checks that `{p, n}` is in bounds for each of the given pointers. The asm kernels only get
+/// raw pointers, so this is done before calling them. Like `check_overlap!`, it only does
+/// anything with debug assertions or the `checked-limbs` feature on.
+#[cfg(asm)]
+macro_rules! check_range {
+    ($n:expr, $($p:expr),+) => (
+        if cfg!(any(debug_assertions, feature = "checked-limbs")) {
+            $( let _ = *$p.offset($n - 1); )+
+        }
+    );
+}
 
 mod addsub;
 mod mul;
@@ -91,6 +150,7 @@ pub mod base;
 pub mod limb;
 pub mod limb_ptr;
 pub mod mtgy;
+pub mod safe;
 use self::limb::Limb;
 
 use ll::limb_ptr::{Limbs, LimbsMut};
@@ -105,27 +165,28 @@ pub use self::bit::{
 };
 pub use self::addsub::{add_n, sub_n, add, sub, add_1, sub_1, incr, decr};
 pub use self::mul::{addmul_1, submul_1, mul_1, mul, sqr};
+pub use self::mul::{mul_with_scratch, mul_scratch_limbs, sqr_with_scratch, sqr_scratch_limbs};
 pub use self::div::{divrem_1, divrem_2, divrem};
 pub use self::gcd::gcd;
 
 #[inline(always)]
-pub unsafe fn overlap(xp: LimbsMut, xs: i32, yp: Limbs, ys: i32) -> bool {
-    xp.offset(xs as isize).as_const() > yp
-        && yp.offset(ys as isize) > xp.as_const()
+pub unsafe fn overlap(xp: LimbsMut, xs: isize, yp: Limbs, ys: isize) -> bool {
+    xp.offset(xs).as_const() > yp
+        && yp.offset(ys) > xp.as_const()
 }
 
 #[inline(always)]
-pub unsafe fn same_or_separate(xp: LimbsMut, xs: i32, yp: Limbs, ys: i32) -> bool {
+pub unsafe fn same_or_separate(xp: LimbsMut, xs: isize, yp: Limbs, ys: isize) -> bool {
     xp.as_const() == yp || !overlap(xp, xs, yp, ys)
 }
 
 #[inline(always)]
-pub unsafe fn same_or_incr(xp: LimbsMut, xs: i32, yp: Limbs, ys: i32) -> bool {
+pub unsafe fn same_or_incr(xp: LimbsMut, xs: isize, yp: Limbs, ys: isize) -> bool {
     xp.as_const() <= yp || !overlap(xp, xs, yp, ys)
 }
 
 #[inline(always)]
-pub unsafe fn same_or_decr(xp: LimbsMut, xs: i32, yp: Limbs, ys: i32) -> bool {
+pub unsafe fn same_or_decr(xp: LimbsMut, xs: isize, yp: Limbs, ys: isize) -> bool {
     xp.as_const() >= yp || !overlap(xp, xs, yp, ys)
 }
 
@@ -133,12 +194,12 @@ pub unsafe fn same_or_decr(xp: LimbsMut, xs: i32, yp: Limbs, ys: i32) -> bool {
  * Copies the `n` limbs from `src` to `dst` in an incremental fashion.
  */
 #[inline]
-pub unsafe fn copy_incr(src: Limbs, dst: LimbsMut, n: i32) {
-    debug_assert!(same_or_incr(dst, n, src, n));
+pub unsafe fn copy_incr(src: Limbs, dst: LimbsMut, n: isize) {
+    check_overlap!(same_or_incr(dst, n, src, n));
 
     let mut i = 0;
     while i < n {
-        *dst.offset(i as isize) = *src.offset(i as isize);
+        *dst.offset(i) = *src.offset(i);
         i += 1;
     }
 }
@@ -147,12 +208,12 @@ pub unsafe fn copy_incr(src: Limbs, dst: LimbsMut, n: i32) {
  * Copies the `n` limbs from `src` to `dst` in a decremental fashion.
  */
 #[inline]
-pub unsafe fn copy_decr(src: Limbs, dst: LimbsMut, mut n: i32) {
-    debug_assert!(same_or_decr(dst, n, src, n));
+pub unsafe fn copy_decr(src: Limbs, dst: LimbsMut, mut n: isize) {
+    check_overlap!(same_or_decr(dst, n, src, n));
 
     n -= 1;
     while n >= 0 {
-        *dst.offset(n as isize) = *src.offset(n as isize);
+        *dst.offset(n) = *src.offset(n);
         n -= 1;
     }
 }
@@ -161,8 +222,8 @@ pub unsafe fn copy_decr(src: Limbs, dst: LimbsMut, mut n: i32) {
  * Copies the `n - start` limbs from `src + start` to `dst + start`
  */
 #[inline]
-pub unsafe fn copy_rest(src: Limbs, dst: LimbsMut, n: i32, start: i32) {
-    copy_incr(src.offset(start as isize), dst.offset(start as isize),
+pub unsafe fn copy_rest(src: Limbs, dst: LimbsMut, n: isize, start: isize) {
+    copy_incr(src.offset(start), dst.offset(start),
                n - start);
 }
 
@@ -171,44 +232,39 @@ pub unsafe fn copy_rest(src: Limbs, dst: LimbsMut, n: i32, start: i32) {
  * Returns the size of the integer pointed to by `p` such that the most
  * significant limb is non-zero.
  */
-pub unsafe fn normalize(p: Limbs, mut n: i32) -> i32 {
+pub unsafe fn normalize(p: Limbs, mut n: isize) -> isize {
     debug_assert!(n >= 0);
-    while n > 0 && *p.offset((n - 1) as isize) == 0 {
+    while n > 0 && *p.offset(n - 1) == 0 {
         n -= 1;
     }
 
-    return n;
+    n
 }
 
 /**
  * Called when a divide by zero occurs.
  *
- * If debug assertions are enabled, a message is printed and the
- * stack unwinds. Otherwise it will simply abort the process.
+ * This panics, just like dividing a primitive integer by zero does.
  */
 #[cold]
 #[inline(never)]
 pub fn divide_by_zero() -> ! {
-    if cfg!(debug_assertions) {
-        panic!("divide by zero")
-    } else {
-        unsafe { abort() }
-    }
+    panic!("divide by zero")
 }
 
 /**
  * Checks that all `nn` limbs in `np` are zero
  */
-pub unsafe fn is_zero(mut np: Limbs, mut nn: i32) -> bool {
+pub unsafe fn is_zero(mut np: Limbs, mut nn: isize) -> bool {
     while nn > 0 {
         if *np != 0 { return false; }
         np = np.offset(1);
         nn -= 1;
     }
-    return true;
+    true
 }
 
-pub unsafe fn zero(mut np: LimbsMut, mut nn: i32) {
+pub unsafe fn zero(mut np: LimbsMut, mut nn: isize) {
     while nn > 0 {
         *np = Limb(0);
         np = np.offset(1);
@@ -220,11 +276,11 @@ pub unsafe fn zero(mut np: LimbsMut, mut nn: i32) {
  * Compares the `n` least-significant limbs of `xp` and `yp`, returning whether
  * {xp, n} is less than, equal to or greater than {yp, n}
  */
-pub unsafe fn cmp(xp: Limbs, yp: Limbs, n: i32) -> Ordering {
+pub unsafe fn cmp(xp: Limbs, yp: Limbs, n: isize) -> Ordering {
     let mut i = n - 1;
     while i >= 0 {
-        let x = *xp.offset(i as isize);
-        let y = *yp.offset(i as isize);
+        let x = *xp.offset(i);
+        let y = *yp.offset(i);
         if x != y {
             return if x > y {
                 Ordering::Greater
@@ -238,46 +294,74 @@ pub unsafe fn cmp(xp: Limbs, yp: Limbs, n: i32) -> Ordering {
     Ordering::Equal
 }
 
+/**
+ * Formats `n` limbs at `p` the way `dump` prints them. Limbs outside the bounds of `p` are shown
+ * as `??` instead of being read, so this is safe to use when reporting a bad pointer.
+ */
+struct Dump<'a> {
+    lbl: &'a str,
+    p: Limbs,
+    n: isize,
+}
+
+impl<'a> fmt::Display for Dump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}: ({})[", self.lbl, self.n)?;
+        let mut i = 0;
+        while i < self.n {
+            match unsafe { self.p.get(i) } {
+                Some(l) => write!(f, "0x{:0>2X}", l.0)?,
+                None => f.write_str("??")?,
+            }
+            i += 1;
+            if i != self.n {
+                f.write_str(", ")?;
+            }
+            if (i % 8) == 0 {
+                f.write_str("\n")?;
+            }
+        }
+        f.write_str("]\n")
+    }
+}
+
 #[doc(hidden)]
+#[cfg(feature = "std")]
 #[allow(unused_must_use)]
 #[cold] #[inline(never)]
-pub unsafe fn dump(lbl: &str, mut p: Limbs, mut n: i32) {
+pub unsafe fn dump(lbl: &str, p: Limbs, n: isize) {
     use std::io::{self, Write};
     let stdout = io::stdout();
     let mut stdout = stdout.lock();
 
-    stdout.write_all(lbl.as_bytes());
-    write!(stdout, ": ({})", n);
-    stdout.write_all(b"[\n");
-    let mut i = 0;
-    while n > 0 {
-        write!(stdout, "0x{:0>2X}", (*p).0);
-        p = p.offset(1);
-        n -= 1;
-        if n != 0 {
-            stdout.write_all(b", ");
-        }
-        i += 1;
-        if (i % 8) == 0 {
-            stdout.write_all(b"\n");
-        }
-    }
-
-    stdout.write_all(b"]\n");
+    write!(stdout, "{}", Dump { lbl, p, n });
     stdout.flush();
 }
 
+/**
+ * Called when `check_overlap!` fails, panicking with the contents of both ranges.
+ */
+#[cold]
+#[inline(never)]
+fn overlap_violation(cond: &str, wp: LimbsMut, wn: isize, xp: Limbs, xn: isize) -> ! {
+    panic!("overlapping limbs, `{}` doesn't hold for {:?} and {:?}\n{}{}", cond,
+           wp.as_const(), xp,
+           Dump { lbl: "output", p: wp.as_const(), n: wn },
+           Dump { lbl: "input", p: xp, n: xn })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use ll::limb::Limb;
     use ll::limb_ptr::{Limbs, LimbsMut};
+    use std::vec::Vec;
 
     macro_rules! make_limbs {
         (const $nm:ident, $($d:expr),*) => (
             {
                 $nm = [$(Limb($d)),*];
-                let len = $nm.len() as i32;
+                let len = $nm.len() as isize;
                 let ptr = unsafe {Limbs::new($nm.as_ptr(), 0, len)};
                 (ptr, len)
             }
@@ -285,7 +369,7 @@ mod test {
         (out $nm:ident, $len:expr) => (
             {
                 $nm = [Limb(0);$len];
-                unsafe {LimbsMut::new($nm.as_mut_ptr(), 0, $len as i32)}
+                unsafe {LimbsMut::new($nm.as_mut_ptr(), 0, $len as isize)}
             }
         );
     }
@@ -510,29 +594,26 @@ mod test {
 
         // Warning, dragons lie ahead, mostly to avoid writing out 150-limb numbers
 
-        let a; let b; let mut c;
-        // Abuse the fact that fixed-size arrays and tuples are laid out sequentially in memory
-        let expected : [Limb; 73] = unsafe {
-            ::std::mem::transmute((
-                Limb(1),
-                [Limb(0); 29],
-                [Limb(!0); 13],
-                Limb(!1),
-                [Limb(!0); 29]
-            ))
-        };
+        // Tuple layout isn't guaranteed, so build the expected limbs up piece by piece
+        let expected : Vec<Limb> = [
+            &[Limb(1)][..],
+            &[Limb(0); 29][..],
+            &[Limb(!0); 13][..],
+            &[Limb(!1)][..],
+            &[Limb(!0); 29][..],
+        ].concat();
 
         // (B^43 - 1)
-        a = [Limb(!0); 43];
+        let a = [Limb(!0); 43];
         // (B^30 - 1)
-        b = [Limb(!0); 30];
+        let b = [Limb(!0); 30];
 
-        c = [Limb(0); 73];
+        let mut c = [Limb(0); 73];
 
         unsafe {
-            let ap = Limbs::new(&a[0], 0, a.len() as i32);
-            let bp = Limbs::new(&b[0], 0, b.len() as i32);
-            let cp = LimbsMut::new(&mut c[0], 0, c.len() as i32);
+            let ap = Limbs::new(&a[0], 0, a.len() as isize);
+            let bp = Limbs::new(&b[0], 0, b.len() as isize);
+            let cp = LimbsMut::new(&mut c[0], 0, c.len() as isize);
 
             mul(cp, ap, 43, bp, 30);
         }
@@ -541,29 +622,26 @@ mod test {
         let cp : &[Limb] = &c;
         assert_eq!(cp, ep);
 
-        let a; let b; let mut c;
-        // Abuse the fact that fixed-size arrays and tuples are laid out sequentially in memory
-        let expected : [Limb; 150] = unsafe {
-            ::std::mem::transmute((
-                Limb(1),
-                [Limb(0); 25],
-                [Limb(!0); 98],
-                Limb(!1),
-                [Limb(!0); 25]
-            ))
-        };
+        // Tuple layout isn't guaranteed, so build the expected limbs up piece by piece
+        let expected : Vec<Limb> = [
+            &[Limb(1)][..],
+            &[Limb(0); 25][..],
+            &[Limb(!0); 98][..],
+            &[Limb(!1)][..],
+            &[Limb(!0); 25][..],
+        ].concat();
 
         // (B^124 - 1)
-        a = [Limb(!0); 124];
+        let a = [Limb(!0); 124];
         // (B^25 - 1)
-        b = [Limb(!0); 26];
+        let b = [Limb(!0); 26];
 
-        c = [Limb(0); 150];
+        let mut c = [Limb(0); 150];
 
         unsafe {
-            let ap = Limbs::new(&a[0], 0, a.len() as i32);
-            let bp = Limbs::new(&b[0], 0, b.len() as i32);
-            let cp = LimbsMut::new(&mut c[0], 0, c.len() as i32);
+            let ap = Limbs::new(&a[0], 0, a.len() as isize);
+            let bp = Limbs::new(&b[0], 0, b.len() as isize);
+            let cp = LimbsMut::new(&mut c[0], 0, c.len() as isize);
 
             mul(cp, ap, 124, bp, 26);
         }
@@ -714,7 +792,7 @@ mod test {
             scan_1(ap, asz)
         };
 
-        assert_eq!(pos, Limb::BITS as u32 + 8);
+        assert_eq!(pos, Limb::BITS + 8);
 
         let a;
         let (ap, asz) = make_limbs!(const a, !256);
@@ -732,7 +810,53 @@ mod test {
             scan_0(ap, asz)
         };
 
-        assert_eq!(pos, Limb::BITS as u32 + 8);
+        assert_eq!(pos, Limb::BITS + 8);
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "checked-limbs"))]
+    fn dump_out_of_bounds() {
+        let a;
+        let (ap, _) = make_limbs!(const a, 1, 2);
+        let s = format!("{}", Dump { lbl: "a", p: ap, n: 3 });
+        assert_eq!(s, "a: (3)[\n0x01, 0x02, ??]\n");
     }
 
+    #[test]
+    #[cfg(any(debug_assertions, feature = "checked-limbs"))]
+    #[should_panic(expected = "invalid deref")]
+    fn checked_deref() {
+        let a;
+        let (ap, _) = make_limbs!(const a, 1);
+        unsafe {
+            let _ = *ap.offset(1);
+        }
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "checked-limbs"))]
+    #[should_panic(expected = "overlapping limbs")]
+    fn checked_overlap() {
+        let a; let mut w;
+        let (ap, asz) = make_limbs!(const a, 1, 2);
+        let wp = make_limbs!(out w, 4);
+        unsafe {
+            copy_incr(ap, wp, asz);
+            // The product can't be written over one of the inputs
+            mul(wp, wp.as_const(), 2, ap, asz);
+        }
+    }
+
+    #[test]
+    #[cfg(all(asm, any(debug_assertions, feature = "checked-limbs")))]
+    #[should_panic(expected = "invalid deref")]
+    fn checked_asm_range() {
+        let a; let mut w;
+        let (ap, _) = make_limbs!(const a, 1, 2);
+        let wp = make_limbs!(out w, 3);
+        unsafe {
+            // Only the last limb of `a` is out of bounds
+            addmul_1(wp, ap, 3, Limb(2));
+        }
+    }
 }
@@ -12,6 +12,8 @@
 //    See the License for the specific language governing permissions and
 //    limitations under the License.
 
+use std::vec::Vec;
+
 use ll;
 use ll::limb::Limb;
 use mem;
@@ -19,7 +21,7 @@ use mem;
 use ll::limb_ptr::{Limbs, LimbsMut};
 
 // w <- a^b [m]
-pub unsafe fn modpow(wp: LimbsMut, r_limbs: i32, n: Limbs, nquote0: Limb, a: Limbs, bp: Limbs, bn: i32) {
+pub unsafe fn modpow(wp: LimbsMut, r_limbs: isize, n: Limbs, nquote0: Limb, a: Limbs, bp: Limbs, bn: isize) {
     let k = 6;
 
     let mut tmp = mem::TmpAllocator::new();
@@ -31,7 +33,7 @@ pub unsafe fn modpow(wp: LimbsMut, r_limbs: i32, n: Limbs, nquote0: Limb, a: Lim
     let mut pow_0 = tmp.allocate(r_limbs as usize);
     *pow_0 = Limb(1);
     let pow_1 = tmp.allocate(r_limbs as usize);
-    ll::copy_incr(a, pow_1, r_limbs as i32);
+    ll::copy_incr(a, pow_1, r_limbs);
     table.push(pow_0);
     table.push(pow_1);
     for _ in 2..(1 << k) {
@@ -50,8 +52,8 @@ pub unsafe fn modpow(wp: LimbsMut, r_limbs: i32, n: Limbs, nquote0: Limb, a: Lim
         table.push(next);
     }
 
-    let exp_bit_length = ll::base::num_base_digits(bp, bn, 2) as usize;
-    let block_count = (exp_bit_length + k - 1) / k;
+    let exp_bit_length = ll::base::num_base_digits(bp, bn, 2);
+    let block_count = exp_bit_length.div_ceil(k);
     for i in (0..block_count).rev() {
         let mut block_value: usize = 0;
         for j in 0..k {
@@ -78,8 +80,9 @@ pub unsafe fn modpow(wp: LimbsMut, r_limbs: i32, n: Limbs, nquote0: Limb, a: Lim
 }
 
 #[inline]
+#[allow(clippy::too_many_arguments)]
 unsafe fn mul(wp: LimbsMut,
-              r_limbs: i32,
+              r_limbs: isize,
               a: Limbs,
               b: Limbs,
               n: Limbs,
@@ -92,7 +95,7 @@ unsafe fn mul(wp: LimbsMut,
 
 #[inline]
 unsafe fn sqr(wp: LimbsMut,
-              r_limbs: i32,
+              r_limbs: isize,
               a: Limbs,
               n: Limbs,
               nquote0: Limb,
@@ -103,11 +106,11 @@ unsafe fn sqr(wp: LimbsMut,
 }
 
 #[inline]
-pub unsafe fn redc(wp: LimbsMut, r_limbs: i32, n: Limbs, nquote0: Limb, t: LimbsMut) {
+pub unsafe fn redc(wp: LimbsMut, r_limbs: isize, n: Limbs, nquote0: Limb, t: LimbsMut) {
     let mut carry = 0;
     for i in 0..r_limbs {
         carry = 0;
-        let m = (*t.offset(i as _)).0.wrapping_mul(nquote0.0 as _);
+        let m = t.offset(i as _).0.wrapping_mul(nquote0.0 as _);
         for j in 0..r_limbs {
             let (h_mnj, l_mnj) = Limb(m).mul_hilo(*(n.offset(j as _)));
             let (s, c1) = t.offset((i + j) as _).add_overflow(l_mnj);
@@ -122,10 +125,10 @@ pub unsafe fn redc(wp: LimbsMut, r_limbs: i32, n: Limbs, nquote0: Limb, t: Limbs
         }
     }
     if carry > 0 ||
-       ll::cmp(t.offset(r_limbs as isize).as_const(), n, r_limbs) != ::std::cmp::Ordering::Less {
-        ll::addsub::sub_n(wp, t.offset(r_limbs as isize).as_const(), n, r_limbs);
+       ll::cmp(t.offset(r_limbs).as_const(), n, r_limbs) != ::std::cmp::Ordering::Less {
+        ll::addsub::sub_n(wp, t.offset(r_limbs).as_const(), n, r_limbs);
     } else {
-        ll::copy_incr(t.offset(r_limbs as isize).as_const(), wp, r_limbs);
+        ll::copy_incr(t.offset(r_limbs).as_const(), wp, r_limbs);
     }
 }
 
@@ -134,11 +137,11 @@ pub fn inv1(x: Limb) -> Limb {
     let mut y = 1;
     for i in 2..(Limb::BITS) {
         if 1 << (i - 1) < (x.wrapping_mul(y) % (1 << i)) {
-            y += 1 << i - 1;
+            y += 1 << (i - 1);
         }
     }
     if 1 << (Limb::BITS - 1) < x.wrapping_mul(y) {
-        y += 1 << Limb::BITS - 1;
+        y += 1 << (Limb::BITS - 1);
     }
     Limb(y as _)
 }
@@ -148,7 +151,7 @@ fn test_inv1() {
     assert_eq!(inv1(Limb(23)).0.wrapping_mul(23), 1);
 }
 
-#[cfg(target_pointer_width = "64")]
+#[cfg(limb_width = "64")]
 #[test]
 fn test_inv1_64() {
     assert_eq!(inv1(Limb(193514046488575)).0.wrapping_mul(193514046488575),
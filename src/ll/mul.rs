@@ -23,10 +23,10 @@ use mem;
 
 use ll::limb_ptr::{Limbs, LimbsMut};
 
-const TOOM22_THRESHOLD : i32 = 20;
+const TOOM22_THRESHOLD : isize = 20;
 
 #[allow(dead_code)]
-unsafe fn mul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: i32, vl: Limb) -> Limb {
+unsafe fn mul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: isize, vl: Limb) -> Limb {
     let mut cl = Limb(0);
     loop {
         let xl = *xp;
@@ -43,7 +43,7 @@ unsafe fn mul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: i32, vl: Limb) -
         xp = xp.offset(1);
     }
 
-    return cl;
+    cl
 }
 
 /**
@@ -54,9 +54,9 @@ unsafe fn mul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: i32, vl: Limb) -
  */
 #[cfg(not(asm))]
 #[inline]
-pub unsafe fn mul_1(wp: LimbsMut, xp: Limbs, n: i32, vl: Limb) -> Limb {
+pub unsafe fn mul_1(wp: LimbsMut, xp: Limbs, n: isize, vl: Limb) -> Limb {
     debug_assert!(n > 0);
-    debug_assert!(same_or_incr(wp, n, xp, n));
+    check_overlap!(same_or_incr(wp, n, xp, n));
 
     mul_1_generic(wp, xp, n, vl)
 }
@@ -69,20 +69,21 @@ pub unsafe fn mul_1(wp: LimbsMut, xp: Limbs, n: i32, vl: Limb) -> Limb {
  */
 #[cfg(asm)]
 #[inline]
-pub unsafe fn mul_1(mut wp: LimbsMut, xp: Limbs, n: i32, vl: Limb) -> Limb {
+pub unsafe fn mul_1(mut wp: LimbsMut, xp: Limbs, n: isize, vl: Limb) -> Limb {
     debug_assert!(n > 0);
-    debug_assert!(same_or_incr(wp, n, xp, n));
+    check_overlap!(same_or_incr(wp, n, xp, n));
     extern "C" {
-        fn ramp_mul_1(wp: *mut Limb, xp: *const Limb, n: i32, vl: Limb) -> Limb;
+        fn ramp_mul_1(wp: *mut Limb, xp: *const Limb, n: isize, vl: Limb) -> Limb;
     }
 
+    check_range!(n, wp, xp);
     ramp_mul_1(&mut *wp, &*xp, n, vl)
 }
 
 #[allow(dead_code)]
-unsafe fn addmul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: i32, vl: Limb) -> Limb {
+unsafe fn addmul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: isize, vl: Limb) -> Limb {
     debug_assert!(n > 0);
-    debug_assert!(same_or_separate(wp, n, xp, n));
+    check_overlap!(same_or_separate(wp, n, xp, n));
 
     let mut cl = Limb(0);
     loop {
@@ -103,7 +104,7 @@ unsafe fn addmul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: i32, vl: Limb
         xp = xp.offset(1);
     }
 
-    return cl;
+    cl
 }
 
 /**
@@ -112,7 +113,7 @@ unsafe fn addmul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: i32, vl: Limb
  */
 #[cfg(not(asm))]
 #[inline]
-pub unsafe fn addmul_1(wp: LimbsMut, xp: Limbs, n: i32, vl: Limb) -> Limb {
+pub unsafe fn addmul_1(wp: LimbsMut, xp: Limbs, n: isize, vl: Limb) -> Limb {
     addmul_1_generic(wp, xp, n, vl)
 }
 
@@ -122,18 +123,19 @@ pub unsafe fn addmul_1(wp: LimbsMut, xp: Limbs, n: i32, vl: Limb) -> Limb {
  */
 #[cfg(asm)]
 #[inline]
-pub unsafe fn addmul_1(mut wp: LimbsMut, xp:  Limbs, n: i32, vl: Limb) -> Limb {
+pub unsafe fn addmul_1(mut wp: LimbsMut, xp:  Limbs, n: isize, vl: Limb) -> Limb {
     extern "C" {
-        fn ramp_addmul_1(wp: *mut Limb, xp: *const Limb, n: i32, vl: Limb) -> Limb;
+        fn ramp_addmul_1(wp: *mut Limb, xp: *const Limb, n: isize, vl: Limb) -> Limb;
     }
 
+    check_range!(n, wp, xp);
     ramp_addmul_1(&mut *wp, &*xp, n, vl)
 }
 
 #[allow(dead_code)]
-unsafe fn submul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: i32, vl: Limb) -> Limb {
+unsafe fn submul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: isize, vl: Limb) -> Limb {
     debug_assert!(n > 0);
-    debug_assert!(same_or_separate(wp, n, xp, n));
+    check_overlap!(same_or_separate(wp, n, xp, n));
 
     let mut cl = Limb(0);
     loop {
@@ -154,7 +156,7 @@ unsafe fn submul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: i32, vl: Limb
         xp = xp.offset(1);
     }
 
-    return cl;
+    cl
 }
 
 /**
@@ -163,7 +165,7 @@ unsafe fn submul_1_generic(mut wp: LimbsMut, mut xp: Limbs, mut n: i32, vl: Limb
  */
 #[cfg(not(asm))]
 #[inline]
-pub unsafe fn submul_1(wp: LimbsMut, xp: Limbs, n: i32, vl: Limb) -> Limb {
+pub unsafe fn submul_1(wp: LimbsMut, xp: Limbs, n: isize, vl: Limb) -> Limb {
     submul_1_generic(wp, xp, n, vl)
 }
 
@@ -173,11 +175,12 @@ pub unsafe fn submul_1(wp: LimbsMut, xp: Limbs, n: i32, vl: Limb) -> Limb {
  */
 #[cfg(asm)]
 #[inline]
-pub unsafe fn submul_1(mut wp: LimbsMut, xp: Limbs, n: i32, vl: Limb) -> Limb {
+pub unsafe fn submul_1(mut wp: LimbsMut, xp: Limbs, n: isize, vl: Limb) -> Limb {
     extern "C" {
-        fn ramp_submul_1(wp: *mut Limb, xp: *const Limb, n: i32, vl: Limb) -> Limb;
+        fn ramp_submul_1(wp: *mut Limb, xp: *const Limb, n: isize, vl: Limb) -> Limb;
     }
 
+    check_range!(n, wp, xp);
     ramp_submul_1(&mut *wp, &*xp, n, vl)
 }
 
@@ -186,19 +189,53 @@ pub unsafe fn submul_1(mut wp: LimbsMut, xp: Limbs, n: i32, vl: Limb) -> Limb {
  *
  * `{wp, xs + ys}` must be disjoint from both inputs.
  */
-pub unsafe fn mul(wp: LimbsMut, xp: Limbs, xs: i32, yp: Limbs, ys: i32) {
+pub unsafe fn mul(wp: LimbsMut, xp: Limbs, xs: isize, yp: Limbs, ys: isize) {
     debug_assert!(xs >= ys);
     debug_assert!(ys > 0);
-    debug_assert!(!overlap(wp, xs + ys, xp, xs));
-    debug_assert!(!overlap(wp, xs + ys, yp, ys));
+    check_overlap!(!overlap(wp, xs + ys, xp, xs));
+    check_overlap!(!overlap(wp, xs + ys, yp, ys));
 
-    // TODO: Pick between algorithms based on input sizes
     if ys <= TOOM22_THRESHOLD {
         mul_basecase(wp, xp, xs, yp, ys);
     } else {
         let mut tmp = mem::TmpAllocator::new();
-        let scratch = tmp.allocate((xs * 2) as usize);
+        let scratch = tmp.allocate(mul_scratch_limbs(xs, ys));
+        mul_with_scratch(wp, xp, xs, yp, ys, scratch);
+    }
+}
+
+/**
+ * Returns the number of limbs of scratch space `mul_with_scratch` needs to multiply `xs` limbs
+ * by `ys` limbs, where `xs >= ys`. `mul_scratch_limbs(n, n)` is enough for any multiplication
+ * of operands of at most `n` limbs.
+ */
+pub fn mul_scratch_limbs(xs: isize, ys: isize) -> usize {
+    // The recursion carves the scratch space for each level out of what its caller was given:
+    // `mul_toom22` takes `xs + 1` limbs, and `mul_unbalanced` `ys * 3`. Working through the
+    // cases, no path through `mul_rec` for operands of at most `xs` limbs needs more than
+    // `xs * 6` in total.
+    if ys <= TOOM22_THRESHOLD {
+        0
+    } else {
+        (xs as usize).saturating_mul(6)
+    }
+}
+
+/**
+ * Same as `mul`, but uses `{scratch, mul_scratch_limbs(xs, ys)}` as scratch space instead of
+ * allocating it.
+ */
+pub unsafe fn mul_with_scratch(wp: LimbsMut, xp: Limbs, xs: isize, yp: Limbs, ys: isize,
+                               scratch: LimbsMut) {
+    debug_assert!(xs >= ys);
+    debug_assert!(ys > 0);
+    check_overlap!(!overlap(wp, xs + ys, xp, xs));
+    check_overlap!(!overlap(wp, xs + ys, yp, ys));
 
+    // TODO: Pick between algorithms based on input sizes
+    if ys <= TOOM22_THRESHOLD {
+        mul_basecase(wp, xp, xs, yp, ys);
+    } else {
         // Can't use xs >= (ys * 2) because if xs is odd, some other invariants
         // in toom22 don't hold
         if (xs * 2) >= (ys * 3) {
@@ -209,15 +246,15 @@ pub unsafe fn mul(wp: LimbsMut, xp: Limbs, xs: i32, yp: Limbs, ys: i32) {
     }
 }
 
-unsafe fn mul_basecase(mut wp: LimbsMut, xp: Limbs, xs: i32, mut yp: Limbs, mut ys: i32) {
+unsafe fn mul_basecase(mut wp: LimbsMut, xp: Limbs, xs: isize, mut yp: Limbs, mut ys: isize) {
 
-    *wp.offset(xs as isize) = ll::mul_1(wp, xp, xs, *yp);
+    *wp.offset(xs) = ll::mul_1(wp, xp, xs, *yp);
     wp = wp.offset(1);
     yp = yp.offset(1);
     ys -= 1;
 
     while ys > 0 {
-        *wp.offset(xs as isize) = ll::addmul_1(wp, xp, xs, *yp);
+        *wp.offset(xs) = ll::addmul_1(wp, xp, xs, *yp);
 
         wp = wp.offset(1);
         yp = yp.offset(1);
@@ -228,8 +265,8 @@ unsafe fn mul_basecase(mut wp: LimbsMut, xp: Limbs, xs: i32, mut yp: Limbs, mut
 // Helper fn
 #[inline(always)]
 pub unsafe fn mul_rec(wp: LimbsMut,
-           xp: Limbs, xs: i32,
-           yp: Limbs, ys: i32,
+           xp: Limbs, xs: isize,
+           yp: Limbs, ys: isize,
            scratch: LimbsMut) {
     if ys < TOOM22_THRESHOLD {
         mul_basecase(wp, xp, xs, yp, ys);
@@ -241,8 +278,8 @@ pub unsafe fn mul_rec(wp: LimbsMut,
 }
 
 unsafe fn mul_toom22(wp: LimbsMut,
-                     xp: Limbs, xs: i32,
-                     yp: Limbs, ys: i32,
+                     xp: Limbs, xs: isize,
+                     yp: Limbs, ys: isize,
                      scratch: LimbsMut) {
     // Split x into x1, x0 where x = x1*(B^n) + x0
     // Split y into y1, y0 where y = y1*(B^n) + y0
@@ -279,11 +316,11 @@ unsafe fn mul_toom22(wp: LimbsMut,
     let x0 = xp; // nl limbs
     let y0 = yp; // nl limbs
 
-    let x1 = xp.offset(nl as isize); // xh limbs
-    let y1 = yp.offset(nl as isize); // yh limbs
+    let x1 = xp.offset(nl); // xh limbs
+    let y1 = yp.offset(nl); // yh limbs
 
     let zx1 = wp; // nl limbs
-    let zy1 = wp.offset(nl as isize); // nl limbs
+    let zy1 = wp.offset(nl); // nl limbs
     let mut z1_neg = false; // Keep track of whether the real z1 is negative
 
     // Calculate zx1
@@ -295,9 +332,9 @@ unsafe fn mul_toom22(wp: LimbsMut,
             ll::sub_n(zx1, x0, x1, nl);
         }
     } else { // nl > xh
-        if ll::is_zero(x0.offset(xh as isize), nl-xh) && ll::cmp(x0, x1, xh) == Ordering::Less {
+        if ll::is_zero(x0.offset(xh), nl-xh) && ll::cmp(x0, x1, xh) == Ordering::Less {
             ll::sub_n(zx1, x1, x0, xh);
-            ll::zero(zx1.offset(xh as isize), nl-xh); // Zero the extra limbs
+            ll::zero(zx1.offset(xh), nl-xh); // Zero the extra limbs
             z1_neg = true;
         } else {
             ll::sub(zx1, x0, nl, x1, xh);
@@ -313,9 +350,9 @@ unsafe fn mul_toom22(wp: LimbsMut,
             ll::sub_n(zy1, y0, y1, nl);
         }
     } else { // nl > yh
-        if ll::is_zero(y0.offset(yh as isize), nl-yh) && ll::cmp(y0, y1, yh) == Ordering::Less {
+        if ll::is_zero(y0.offset(yh), nl-yh) && ll::cmp(y0, y1, yh) == Ordering::Less {
             ll::sub_n(zy1, y1, y0, yh);
-            ll::zero(zy1.offset(yh as isize), nl-yh); // Zero the extra limbs
+            ll::zero(zy1.offset(yh), nl-yh); // Zero the extra limbs
             z1_neg = !z1_neg;
         } else {
             ll::sub(zy1, y0, nl, y1, yh);
@@ -324,8 +361,8 @@ unsafe fn mul_toom22(wp: LimbsMut,
 
     let z0 = wp;
     let z1 = scratch;
-    let z2 = wp.offset((nl * 2) as isize);
-    let scratch_out = scratch.offset((nl * 2) as isize);
+    let z2 = wp.offset(nl * 2);
+    let scratch_out = scratch.offset(nl * 2);
 
     // Calculate z1 - 2*nl limbs
     mul_rec(z1, zx1.as_const(), nl, zy1.as_const(), nl, scratch_out);
@@ -358,35 +395,35 @@ unsafe fn mul_toom22(wp: LimbsMut,
     // {wp + 2*nl, nl} = HIGH(z0) + HIGH(z2) + LOW(z2) + carry
 
     // LOW(z2) = HIGH(z0) + LOW(z2)
-    let cy = ll::add_n(wp.offset((2*nl) as isize),
-                       z2.as_const(), z0.offset(nl as isize).as_const(),
+    let cy = ll::add_n(wp.offset(2*nl),
+                       z2.as_const(), z0.offset(nl).as_const(),
                        nl);
 
     // LOW(z0) + LOW(z2)
-    let cy2 = cy + ll::add_n(wp.offset(nl as isize),
+    let cy2 = cy + ll::add_n(wp.offset(nl),
                              z0.as_const(), z2.as_const(),
                              nl);
 
     // LOW(z2) + HIGH(z2)
-    let mut cy = cy + ll::add(wp.offset((2*nl) as isize),
+    let mut cy = cy + ll::add(wp.offset(2*nl),
                               z2.as_const(), nl,
-                              z2.offset(nl as isize).as_const(), yh+xh-nl);
+                              z2.offset(nl).as_const(), yh+xh-nl);
 
     // Add or subtract `z1` depending on the sign of the real result
     // (we calculate such that it's always positive, easier this way)
     if z1_neg {
-        cy = cy + ll::add_n(wp.offset(nl as isize),
-                            wp.offset(nl as isize).as_const(), z1.as_const(),
+        cy = cy + ll::add_n(wp.offset(nl),
+                            wp.offset(nl).as_const(), z1.as_const(),
                             2*nl);
     } else {
-        cy = cy - ll::sub_n(wp.offset(nl as isize),
-                            wp.offset(nl as isize).as_const(), z1.as_const(),
+        cy = cy - ll::sub_n(wp.offset(nl),
+                            wp.offset(nl).as_const(), z1.as_const(),
                             2*nl);
     }
 
     // Apply the carries, has to be done last.
-    ll::incr(wp.offset((nl * 2) as isize), cy2);
-    ll::incr(wp.offset((nl * 3) as isize), cy);
+    ll::incr(wp.offset(nl * 2), cy2);
+    ll::incr(wp.offset(nl * 3), cy);
 }
 
 /**
@@ -396,35 +433,35 @@ unsafe fn mul_toom22(wp: LimbsMut,
  * instead of a single limb.
  */
 unsafe fn mul_unbalanced(mut wp: LimbsMut,
-                         mut xp: Limbs, mut xs: i32,
-                         yp: Limbs, ys: i32,
+                         mut xp: Limbs, mut xs: isize,
+                         yp: Limbs, ys: isize,
                          scratch: LimbsMut) {
     debug_assert!(xs  > ys);
 
-    mul_toom22(wp, xp, ys, yp, ys, scratch);
-
-    xs -= ys;
-    xp = xp.offset(ys as isize);
-    wp = wp.offset(ys as isize);
-
     // Temporary storage for the output of the multiplication
     // in the loop, the loop only needs ys*2 limbs, but the last
     // multiplication needs slightly more than that, but no more
     // than ys*3
-    let mut tmp = mem::TmpAllocator::new();
-    let w_tmp = tmp.allocate((ys * 3) as usize);
+    let w_tmp = scratch;
+    let scratch = scratch.offset(ys * 3);
+
+    mul_toom22(wp, xp, ys, yp, ys, scratch);
+
+    xs -= ys;
+    xp = xp.offset(ys);
+    wp = wp.offset(ys);
 
     while xs >= (ys * 2) {
         mul_toom22(w_tmp, xp, ys, yp, ys, scratch);
         xs -= ys;
-        xp = xp.offset(ys as isize);
+        xp = xp.offset(ys);
         let cy = ll::add_n(wp, wp.as_const(), w_tmp.as_const(), ys);
-        ll::copy_incr(w_tmp.offset(ys as isize).as_const(),
-                      wp.offset(ys as isize),
+        ll::copy_incr(w_tmp.offset(ys).as_const(),
+                      wp.offset(ys),
                       ys);
-        ll::incr(wp.offset(ys as isize), cy);
+        ll::incr(wp.offset(ys), cy);
 
-        wp = wp.offset(ys as isize);
+        wp = wp.offset(ys);
     }
 
     if xs >= ys {
@@ -434,10 +471,10 @@ unsafe fn mul_unbalanced(mut wp: LimbsMut,
     }
 
     let cy = ll::add_n(wp, wp.as_const(), w_tmp.as_const(), ys);
-    ll::copy_incr(w_tmp.offset(ys as isize).as_const(),
-                  wp.offset(ys as isize),
+    ll::copy_incr(w_tmp.offset(ys).as_const(),
+                  wp.offset(ys),
                   xs);
-    ll::incr(wp.offset(ys as isize), cy);
+    ll::incr(wp.offset(ys), cy);
 }
 
 /**
@@ -447,22 +484,49 @@ unsafe fn mul_unbalanced(mut wp: LimbsMut,
  *
  * `{wp, xs*2}` must not overlap with `{xp, xs}`
  */
-pub unsafe fn sqr(wp: LimbsMut, xp: Limbs, xs: i32) {
+pub unsafe fn sqr(wp: LimbsMut, xp: Limbs, xs: isize) {
     debug_assert!(xs > 0);
-    debug_assert!(!overlap(wp, 2*xs, xp, xs));
+    check_overlap!(!overlap(wp, 2*xs, xp, xs));
 
     if xs <= TOOM22_THRESHOLD {
         mul_basecase(wp, xp, xs, xp, xs);
     } else {
         let mut tmp = mem::TmpAllocator::new();
-        let scratch = tmp.allocate((xs * 2) as usize);
+        let scratch = tmp.allocate(sqr_scratch_limbs(xs));
+        sqr_toom2(wp, xp, xs, scratch);
+    }
+}
+
+/**
+ * Returns the number of limbs of scratch space `sqr_with_scratch` needs to square `xs` limbs.
+ * This is never more than `mul_scratch_limbs(xs, xs)`.
+ */
+pub fn sqr_scratch_limbs(xs: isize) -> usize {
+    // `sqr_toom2` takes `xs + 1` limbs, and its halves need no more than `mul_rec` would
+    if xs <= TOOM22_THRESHOLD {
+        0
+    } else {
+        (xs as usize).saturating_mul(4).saturating_add(4)
+    }
+}
 
+/**
+ * Same as `sqr`, but uses `{scratch, sqr_scratch_limbs(xs)}` as scratch space instead of
+ * allocating it.
+ */
+pub unsafe fn sqr_with_scratch(wp: LimbsMut, xp: Limbs, xs: isize, scratch: LimbsMut) {
+    debug_assert!(xs > 0);
+    check_overlap!(!overlap(wp, 2*xs, xp, xs));
+
+    if xs <= TOOM22_THRESHOLD {
+        mul_basecase(wp, xp, xs, xp, xs);
+    } else {
         sqr_toom2(wp, xp, xs, scratch);
     }
 }
 
 #[inline(always)]
-pub unsafe fn sqr_rec(wp: LimbsMut, xp: Limbs, xs: i32, scratch: LimbsMut) {
+pub unsafe fn sqr_rec(wp: LimbsMut, xp: Limbs, xs: isize, scratch: LimbsMut) {
     if xs < TOOM22_THRESHOLD {
         mul_basecase(wp, xp, xs, xp, xs);
     } else {
@@ -470,7 +534,7 @@ pub unsafe fn sqr_rec(wp: LimbsMut, xp: Limbs, xs: i32, scratch: LimbsMut) {
     }
 }
 
-unsafe fn sqr_toom2(wp: LimbsMut, xp: Limbs, xs: i32, scratch: LimbsMut) {
+unsafe fn sqr_toom2(wp: LimbsMut, xp: Limbs, xs: isize, scratch: LimbsMut) {
     // This is very similar to regular mul_toom22, however it is slightly more efficient
     // as it can take advantage of the coefficents being the same.
     //
@@ -487,12 +551,12 @@ unsafe fn sqr_toom2(wp: LimbsMut, xp: Limbs, xs: i32, scratch: LimbsMut) {
     let xl = xs - xh;
 
     let x0 = xp;
-    let x1 = xp.offset(xl as isize);
+    let x1 = xp.offset(xl);
 
     let z0 = wp;
     let z1 = scratch;
-    let z2 = wp.offset((xl * 2) as isize);
-    let scratch_out = scratch.offset((xl * 2) as isize);
+    let z2 = wp.offset(xl * 2);
+    let scratch_out = scratch.offset(xl * 2);
 
     // Calculate z1
     mul_rec(z1, x0, xl, x1, xh, scratch_out);
@@ -506,7 +570,7 @@ unsafe fn sqr_toom2(wp: LimbsMut, xp: Limbs, xs: i32, scratch: LimbsMut) {
 
     // wp now contains the result of (B^2n)*z2 + z0
 
-    cy = cy + ll::add_n(wp.offset(xl as isize), wp.offset(xl as isize).as_const(), z1.as_const(), xs);
+    cy = cy + ll::add_n(wp.offset(xl), wp.offset(xl).as_const(), z1.as_const(), xs);
 
-    ll::incr(wp.offset((xl + xs) as isize), cy);
+    ll::incr(wp.offset(xl + xs), cy);
 }
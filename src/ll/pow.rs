@@ -27,14 +27,36 @@ use ll::limb_ptr::{Limbs, LimbsMut};
  * `{ap, an}` must not be zero.
  * `exp` must be greater than 2
  */
-pub unsafe fn pow(mut wp: LimbsMut, mut ap: Limbs, mut an: i32, mut exp: u32) {
+pub unsafe fn pow(wp: LimbsMut, ap: Limbs, an: isize, exp: usize) {
+    let mut tmp = mem::TmpAllocator::new();
+    let scratch = tmp.allocate(pow_scratch_limbs(num_pow_limbs(ap, an, exp)));
+    pow_with_scratch(wp, ap, an, exp, scratch);
+}
+
+/**
+ * Returns the number of limbs of scratch space `pow_with_scratch` needs for a result of
+ * `wn` limbs, as calculated by `num_pow_limbs`.
+ */
+pub fn pow_scratch_limbs(wn: isize) -> usize {
+    // The base and the products of each step, plus space for multiplying them
+    let sz = wn as usize;
+    sz.saturating_mul(2)
+        .saturating_add(1)
+        .saturating_add(ll::mul_scratch_limbs(wn, wn))
+}
+
+/**
+ * Same as `pow`, but uses `{scratch, pow_scratch_limbs(num_pow_limbs(ap, an, exp))}` as
+ * scratch space instead of allocating it.
+ */
+#[allow(clippy::unnecessary_cast)] // `Limb::trailing_zeros` gives a `u32` with 32-bit limbs
+pub unsafe fn pow_with_scratch(mut wp: LimbsMut, mut ap: Limbs, mut an: isize, mut exp: usize,
+                               scratch: LimbsMut) {
     debug_assert!(exp > 2);
     debug_assert!(!ll::is_zero(ap, an));
 
     let mut wn = num_pow_limbs(ap, an, exp);
-    debug_assert!(!ll::overlap(wp, wn, ap, an));
-
-    let mut tmp = mem::TmpAllocator::new();
+    check_overlap!(!ll::overlap(wp, wn, ap, an));
 
     ll::zero(wp, wn);
 
@@ -43,7 +65,7 @@ pub unsafe fn pow(mut wp: LimbsMut, mut ap: Limbs, mut an: i32, mut exp: u32) {
         ap = ap.offset(1);
         an -= 1;
         wp = wp.offset(exp as isize);
-        wn -= exp as i32;
+        wn -= exp as isize;
     }
     let trailing = (*ap).trailing_zeros() as u32;
 
@@ -62,7 +84,9 @@ pub unsafe fn pow(mut wp: LimbsMut, mut ap: Limbs, mut an: i32, mut exp: u32) {
     //
     // (Examples of such x are 2**(k L), where k > 0 is an integer and
     // L is the number of bits in a limb.)
-    let (bp, scratch) = tmp.allocate_2(sz, sz + 1);
+    let bp = scratch;
+    let mul_scratch = scratch.offset(sz as isize * 2 + 1);
+    let scratch = scratch.offset(sz as isize);
     let mut bn = an;
 
     if trailing > 0 {
@@ -74,9 +98,9 @@ pub unsafe fn pow(mut wp: LimbsMut, mut ap: Limbs, mut an: i32, mut exp: u32) {
     // Calculate the amount we'll need to shift by at the end,
     // we need to adjust wp here because the shift functions only
     // work with shifts of < Limb::BITS
-    let mut shift = trailing * exp;
-    while shift >= Limb::BITS as u32 {
-        shift -= Limb::BITS as u32;
+    let mut shift = trailing as usize * exp;
+    while shift >= Limb::BITS {
+        shift -= Limb::BITS;
         wp = wp.offset(1);
     }
 
@@ -86,9 +110,9 @@ pub unsafe fn pow(mut wp: LimbsMut, mut ap: Limbs, mut an: i32, mut exp: u32) {
     loop {
         if (exp & 1) == 1 {
             if wn > bn {
-                ll::mul(scratch, wp.as_const(), wn, bp.as_const(), bn);
+                ll::mul_with_scratch(scratch, wp.as_const(), wn, bp.as_const(), bn, mul_scratch);
             } else {
-                ll::mul(scratch, bp.as_const(), bn, wp.as_const(), wn);
+                ll::mul_with_scratch(scratch, bp.as_const(), bn, wp.as_const(), wn, mul_scratch);
             }
             wn = ll::normalize(scratch.as_const(), wn + bn);
             ll::copy_incr(scratch.as_const(), wp, wn);
@@ -102,7 +126,7 @@ pub unsafe fn pow(mut wp: LimbsMut, mut ap: Limbs, mut an: i32, mut exp: u32) {
             break;
         }
 
-        ll::sqr(scratch, bp.as_const(), bn);
+        ll::sqr_with_scratch(scratch, bp.as_const(), bn, mul_scratch);
         bn = ll::normalize(scratch.as_const(), bn + bn);
 
         ll::copy_incr(scratch.as_const(), bp, bn);
@@ -110,34 +134,34 @@ pub unsafe fn pow(mut wp: LimbsMut, mut ap: Limbs, mut an: i32, mut exp: u32) {
     }
 
     if shift > 0 {
-        let v = ll::shl(wp, wp.as_const(), wn, shift);
+        let v = ll::shl(wp, wp.as_const(), wn, shift as u32);
         if v > 0 {
-            *wp.offset(wn as isize) = v;
+            *wp.offset(wn) = v;
         }
     }
 }
 
 /// Calculates the number of limbs required to store the result of taking
 /// `{xp, xn}` to the power of `exp`
-pub unsafe fn num_pow_limbs(xp: Limbs, xn: i32, exp: u32) -> i32 {
+pub unsafe fn num_pow_limbs(xp: Limbs, xn: isize, exp: usize) -> isize {
     // This is a better approximation of log_b(x^e) than simply using the
     // the number of digits, n.
     // Instead it uses the most significant digit, a, to calculate
     // e*log_b(a*b^(n-1)), which is e*(log_b(a) + n - 1)
     let n = xn - 1;
-    let high_limb = *xp.offset(n as isize);
+    let high_limb = *xp.offset(n);
 
     // Calculate log_2(a), this is because floor(log_b(a)) will always be
     // 0
-    let lg2 = Limb::BITS as u32 - high_limb.leading_zeros() as u32;
+    let lg2 = Limb::BITS as u32 - high_limb.0.leading_zeros();
 
     // Get e*log_2(a)
-    let lg2e = exp as i32 * lg2 as i32;
+    let lg2e = exp as isize * lg2 as isize;
 
     // Now convert it to log_b using the formula
     // log_a(x) = log_b(x) / log_b(a)
-    let elog_b = lg2e / Limb::BITS as i32;
+    let elog_b = lg2e / Limb::BITS as isize;
 
     // Add a final 1 to account for the error in the estimate
-    elog_b + (exp as i32 * n) + 1
+    elog_b + (exp as isize * n) + 1
 }
@@ -0,0 +1,524 @@
+// Copyright 2015 The Ramp Developers
+//
+//    Licensed under the Apache License, Version 2.0 (the "License");
+//    you may not use this file except in compliance with the License.
+//    You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+//    Unless required by applicable law or agreed to in writing, software
+//    distributed under the License is distributed on an "AS IS" BASIS,
+//    WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//    See the License for the specific language governing permissions and
+//    limitations under the License.
+
+/*!
+ * Safe, slice-based versions of the low-level operations.
+ *
+ * The functions in the parent module work on raw `Limbs`/`LimbsMut` pointers and trust the caller
+ * to get the sizes right. The functions here take `&[Limb]` and `&mut [Limb]` instead, and check
+ * everything the underlying routine relies on before calling it, panicking if a requirement isn't
+ * met. Numbers are stored least-significant limb first, as in the rest of the module, and the
+ * outputs only need to be at least as long as the documented size: any limbs past that are left
+ * untouched.
+ *
+ * Since an output slice can't alias any of the inputs, routines that can also work in place have
+ * an `_in_place` variant that uses the first input as the output.
+ */
+
+use std::cmp::{self, Ordering};
+
+use ll;
+use ll::base::{self, Digit};
+use ll::limb::Limb;
+use ll::limb_ptr::{Limbs, LimbsMut};
+
+#[inline(always)]
+fn limbs(x: &[Limb]) -> Limbs {
+    unsafe { Limbs::new(x.as_ptr(), 0, x.len() as isize) }
+}
+
+#[inline(always)]
+fn limbs_mut(x: &mut [Limb]) -> LimbsMut {
+    unsafe { LimbsMut::new(x.as_mut_ptr(), 0, x.len() as isize) }
+}
+
+/// The length of `x` without any zero limbs in the most-significant positions.
+#[inline]
+pub fn normalized_len(x: &[Limb]) -> usize {
+    x.iter().rposition(|&l| l != 0).map_or(0, |i| i + 1)
+}
+
+/**
+ * Compares the numbers in `x` and `y`, which must have the same length.
+ */
+pub fn cmp(x: &[Limb], y: &[Limb]) -> Ordering {
+    assert!(x.len() == y.len(), "inputs must have the same length");
+    unsafe { ll::cmp(limbs(x), limbs(y), x.len() as isize) }
+}
+
+/**
+ * Adds `x` and `y`, which must have the same length `n`, storing the result in the first `n`
+ * limbs of `w`. The carry is returned.
+ */
+pub fn add_n(w: &mut [Limb], x: &[Limb], y: &[Limb]) -> Limb {
+    assert!(x.len() == y.len(), "inputs must have the same length");
+    assert!(w.len() >= x.len(), "output is too short");
+    if x.is_empty() { return Limb(0); }
+    unsafe { ll::add_n(limbs_mut(w), limbs(x), limbs(y), x.len() as isize) }
+}
+
+/**
+ * Subtracts `y` from `x`, which must have the same length `n`, storing the result in the first `n`
+ * limbs of `w`. The borrow is returned.
+ */
+pub fn sub_n(w: &mut [Limb], x: &[Limb], y: &[Limb]) -> Limb {
+    assert!(x.len() == y.len(), "inputs must have the same length");
+    assert!(w.len() >= x.len(), "output is too short");
+    if x.is_empty() { return Limb(0); }
+    unsafe { ll::sub_n(limbs_mut(w), limbs(x), limbs(y), x.len() as isize) }
+}
+
+/**
+ * Adds `x` and `y`, storing the result in the first `x.len()` limbs of `w`. `y` must not be longer
+ * than `x`. The carry is returned.
+ */
+pub fn add(w: &mut [Limb], x: &[Limb], y: &[Limb]) -> Limb {
+    assert!(x.len() >= y.len(), "second input is longer than the first");
+    assert!(w.len() >= x.len(), "output is too short");
+    if y.is_empty() {
+        w[..x.len()].copy_from_slice(x);
+        return Limb(0);
+    }
+    unsafe { ll::add(limbs_mut(w), limbs(x), x.len() as isize, limbs(y), y.len() as isize) }
+}
+
+/**
+ * Subtracts `y` from `x`, storing the result in the first `x.len()` limbs of `w`. `y` must not be
+ * longer than `x`. The borrow is returned.
+ */
+pub fn sub(w: &mut [Limb], x: &[Limb], y: &[Limb]) -> Limb {
+    assert!(x.len() >= y.len(), "second input is longer than the first");
+    assert!(w.len() >= x.len(), "output is too short");
+    if y.is_empty() {
+        w[..x.len()].copy_from_slice(x);
+        return Limb(0);
+    }
+    unsafe { ll::sub(limbs_mut(w), limbs(x), x.len() as isize, limbs(y), y.len() as isize) }
+}
+
+/**
+ * Adds `y` to `x` in place. `y` must not be longer than `x`. The carry is returned.
+ */
+pub fn add_in_place(x: &mut [Limb], y: &[Limb]) -> Limb {
+    assert!(x.len() >= y.len(), "second input is longer than the first");
+    if y.is_empty() { return Limb(0); }
+    let xp = limbs_mut(x);
+    unsafe { ll::add(xp, xp.as_const(), x.len() as isize, limbs(y), y.len() as isize) }
+}
+
+/**
+ * Subtracts `y` from `x` in place. `y` must not be longer than `x`. The borrow is returned.
+ */
+pub fn sub_in_place(x: &mut [Limb], y: &[Limb]) -> Limb {
+    assert!(x.len() >= y.len(), "second input is longer than the first");
+    if y.is_empty() { return Limb(0); }
+    let xp = limbs_mut(x);
+    unsafe { ll::sub(xp, xp.as_const(), x.len() as isize, limbs(y), y.len() as isize) }
+}
+
+/**
+ * Adds the single limb `y` to `x`, storing the result in the first `x.len()` limbs of `w`. `x`
+ * must be non-empty. The carry is returned.
+ */
+pub fn add_1(w: &mut [Limb], x: &[Limb], y: Limb) -> Limb {
+    assert!(!x.is_empty(), "input must not be empty");
+    assert!(w.len() >= x.len(), "output is too short");
+    unsafe { ll::add_1(limbs_mut(w), limbs(x), x.len() as isize, y) }
+}
+
+/**
+ * Subtracts the single limb `y` from `x`, storing the result in the first `x.len()` limbs of `w`.
+ * `x` must be non-empty. The borrow is returned.
+ */
+pub fn sub_1(w: &mut [Limb], x: &[Limb], y: Limb) -> Limb {
+    assert!(!x.is_empty(), "input must not be empty");
+    assert!(w.len() >= x.len(), "output is too short");
+    unsafe { ll::sub_1(limbs_mut(w), limbs(x), x.len() as isize, y) }
+}
+
+/**
+ * Multiplies `x` by the single limb `v`, storing the low `x.len()` limbs of the product in `w`.
+ * The highest limb of the product is returned.
+ */
+pub fn mul_1(w: &mut [Limb], x: &[Limb], v: Limb) -> Limb {
+    assert!(w.len() >= x.len(), "output is too short");
+    if x.is_empty() { return Limb(0); }
+    unsafe { ll::mul_1(limbs_mut(w), limbs(x), x.len() as isize, v) }
+}
+
+/**
+ * Multiplies `x` by the single limb `v` and adds the product to the first `x.len()` limbs of `w`.
+ * The highest limb of the result is returned.
+ */
+pub fn addmul_1(w: &mut [Limb], x: &[Limb], v: Limb) -> Limb {
+    assert!(w.len() >= x.len(), "output is too short");
+    if x.is_empty() { return Limb(0); }
+    unsafe { ll::addmul_1(limbs_mut(w), limbs(x), x.len() as isize, v) }
+}
+
+/**
+ * Multiplies `x` by the single limb `v` and subtracts the product from the first `x.len()` limbs
+ * of `w`. The highest limb of the product, adjusted for the borrow, is returned.
+ */
+pub fn submul_1(w: &mut [Limb], x: &[Limb], v: Limb) -> Limb {
+    assert!(w.len() >= x.len(), "output is too short");
+    if x.is_empty() { return Limb(0); }
+    unsafe { ll::submul_1(limbs_mut(w), limbs(x), x.len() as isize, v) }
+}
+
+/**
+ * Multiplies `x` by `y`, storing the product in the first `x.len() + y.len()` limbs of `w`. Both
+ * inputs must be non-empty.
+ */
+pub fn mul(w: &mut [Limb], x: &[Limb], y: &[Limb]) {
+    let (x, y) = if x.len() >= y.len() { (x, y) } else { (y, x) };
+    assert!(!y.is_empty(), "inputs must not be empty");
+    assert!(w.len() >= x.len() + y.len(), "output is too short");
+    unsafe { ll::mul(limbs_mut(w), limbs(x), x.len() as isize, limbs(y), y.len() as isize) }
+}
+
+/**
+ * Squares `x`, storing the result in the first `2 * x.len()` limbs of `w`. `x` must be non-empty.
+ */
+pub fn sqr(w: &mut [Limb], x: &[Limb]) {
+    assert!(!x.is_empty(), "input must not be empty");
+    assert!(w.len() >= 2 * x.len(), "output is too short");
+    unsafe { ll::sqr(limbs_mut(w), limbs(x), x.len() as isize) }
+}
+
+/**
+ * Divides `x` by the single limb `d`, storing the quotient in the first `x.len()` limbs of `q`.
+ * The remainder is returned.
+ *
+ * Panics if `d` is zero.
+ */
+pub fn divrem_1(q: &mut [Limb], x: &[Limb], d: Limb) -> Limb {
+    if d == 0 { ll::divide_by_zero(); }
+    assert!(q.len() >= x.len(), "quotient is too short");
+    if x.is_empty() { return Limb(0); }
+    unsafe { ll::divrem_1(limbs_mut(q), 0, limbs(x), x.len() as isize, d) }
+}
+
+/**
+ * Divides `n` by `d`, storing the quotient in the first `max(n.len() - ds + 1, 1)` limbs of `q`
+ * and the remainder in the first `d.len()` limbs of `r`, where `ds` is the length of `d` ignoring
+ * zero limbs in the most-significant positions. Both outputs are zero-padded up to those sizes.
+ *
+ * Panics if `d` is zero.
+ */
+pub fn divrem(q: &mut [Limb], r: &mut [Limb], n: &[Limb], d: &[Limb]) {
+    let ds = normalized_len(d);
+    if ds == 0 { ll::divide_by_zero(); }
+    let ns = normalized_len(n);
+
+    let qs = cmp::max((n.len() as isize - ds as isize) + 1, 1) as usize;
+    assert!(q.len() >= qs, "quotient is too short");
+    assert!(r.len() >= d.len(), "remainder is too short");
+
+    // The routine only writes as much as the normalized sizes need, so clear
+    // the rest up front.
+    for l in q[..qs].iter_mut().chain(r[..d.len()].iter_mut()) {
+        *l = Limb(0);
+    }
+    unsafe {
+        ll::divrem(limbs_mut(q), limbs_mut(r), limbs(n), ns as isize, limbs(d), ds as isize);
+    }
+}
+
+/**
+ * Shifts `x` left by `cnt` bits, storing the low `x.len()` limbs of the result in `w`. The bits
+ * shifted out of the top are returned in the low bits of a limb.
+ *
+ * Panics if `cnt` isn't less than the number of bits in a limb.
+ */
+pub fn shl(w: &mut [Limb], x: &[Limb], cnt: u32) -> Limb {
+    assert!((cnt as usize) < Limb::BITS, "shift is too large");
+    assert!(w.len() >= x.len(), "output is too short");
+    if x.is_empty() { return Limb(0); }
+    if cnt == 0 {
+        w[..x.len()].copy_from_slice(x);
+        return Limb(0);
+    }
+    unsafe { ll::shl(limbs_mut(w), limbs(x), x.len() as isize, cnt) }
+}
+
+/**
+ * Shifts `x` right by `cnt` bits, storing the result in the first `x.len()` limbs of `w`. The bits
+ * shifted out of the bottom are returned in the high bits of a limb.
+ *
+ * Panics if `cnt` isn't less than the number of bits in a limb.
+ */
+pub fn shr(w: &mut [Limb], x: &[Limb], cnt: u32) -> Limb {
+    assert!((cnt as usize) < Limb::BITS, "shift is too large");
+    assert!(w.len() >= x.len(), "output is too short");
+    if x.is_empty() { return Limb(0); }
+    if cnt == 0 {
+        w[..x.len()].copy_from_slice(x);
+        return Limb(0);
+    }
+    unsafe { ll::shr(limbs_mut(w), limbs(x), x.len() as isize, cnt) }
+}
+
+/**
+ * Shifts `x` left by `cnt` bits in place, returning the bits shifted out of the top.
+ */
+pub fn shl_in_place(x: &mut [Limb], cnt: u32) -> Limb {
+    assert!((cnt as usize) < Limb::BITS, "shift is too large");
+    if x.is_empty() || cnt == 0 { return Limb(0); }
+    let xp = limbs_mut(x);
+    unsafe { ll::shl(xp, xp.as_const(), x.len() as isize, cnt) }
+}
+
+/**
+ * Shifts `x` right by `cnt` bits in place, returning the bits shifted out of the bottom.
+ */
+pub fn shr_in_place(x: &mut [Limb], cnt: u32) -> Limb {
+    assert!((cnt as usize) < Limb::BITS, "shift is too large");
+    if x.is_empty() || cnt == 0 { return Limb(0); }
+    let xp = limbs_mut(x);
+    unsafe { ll::shr(xp, xp.as_const(), x.len() as isize, cnt) }
+}
+
+macro_rules! bitop {
+    ($(#[$attr:meta])* fn $name:ident) => {
+        $(#[$attr])*
+        pub fn $name(w: &mut [Limb], x: &[Limb], y: &[Limb]) {
+            assert!(x.len() == y.len(), "inputs must have the same length");
+            assert!(w.len() >= x.len(), "output is too short");
+            unsafe { ll::$name(limbs_mut(w), limbs(x), limbs(y), x.len() as isize) }
+        }
+    }
+}
+
+bitop! {
+    /// Performs a bitwise "and" (`&`) of `x` and `y`, which must have the same length `n`,
+    /// storing the result in the first `n` limbs of `w`.
+    fn and_n
+}
+bitop! {
+    /// Performs a bitwise "or" (`|`) of `x` and `y`, which must have the same length `n`,
+    /// storing the result in the first `n` limbs of `w`.
+    fn or_n
+}
+bitop! {
+    /// Performs a bitwise "xor" (`^`) of `x` and `y`, which must have the same length `n`,
+    /// storing the result in the first `n` limbs of `w`.
+    fn xor_n
+}
+
+/**
+ * Inverts every bit of `x`, storing the result in the first `x.len()` limbs of `w`.
+ */
+pub fn not(w: &mut [Limb], x: &[Limb]) {
+    assert!(w.len() >= x.len(), "output is too short");
+    unsafe { ll::not(limbs_mut(w), limbs(x), x.len() as isize) }
+}
+
+/**
+ * Computes the greatest common divisor of `a` and `b`, storing it in `g` and returning the
+ * number of limbs it takes up. Both inputs must be non-zero, and are used as scratch space, so
+ * their contents are unspecified afterwards. `g` must be at least as long as the shorter input,
+ * ignoring zero limbs in the most-significant positions.
+ */
+pub fn gcd(g: &mut [Limb], a: &mut [Limb], b: &mut [Limb]) -> usize {
+    let an = normalized_len(a);
+    let bn = normalized_len(b);
+    assert!(an > 0 && bn > 0, "inputs must not be zero");
+
+    let (a, an, b, bn) = if an >= bn { (a, an, b, bn) } else { (b, bn, a, an) };
+    assert!(g.len() >= bn, "output is too short");
+
+    // Limbs shared as trailing zeros by both inputs are never written by `ll::gcd`
+    for l in &mut g[..bn] {
+        *l = Limb(0);
+    }
+
+    unsafe {
+        let gn = ll::gcd(limbs_mut(g), limbs_mut(a), an as isize, limbs_mut(b), bn as isize);
+        ll::normalize(limbs(g), gn) as usize
+    }
+}
+
+/**
+ * Converts `x` to base `base`, passing the digits to `out` from most-significant to least.
+ * Zero limbs in the most-significant positions are ignored, and zero gives a single zero digit.
+ * Digits are truncated if they don't fit in `D`.
+ *
+ * Panics if `base` is less than 2 or greater than `MAX_BASE`.
+ */
+pub fn to_base<D: Digit, F: FnMut(D)>(base: u64, x: &[Limb], out: F) {
+    assert!((2..=base::MAX_BASE).contains(&base), "Invalid base: {}", base);
+    let n = normalized_len(x);
+    unsafe { base::to_base(base, limbs(x), n as isize, out) }
+}
+
+/**
+ * Converts the base `base` digits in `digits`, most-significant first, storing the result in `w`
+ * and returning the number of limbs it takes up. `w` must hold at least
+ * `base_digits_to_len(digits.len(), base)` limbs.
+ *
+ * Panics if `base` is less than 2 or greater than `MAX_BASE`, or if any digit isn't less than
+ * `base`.
+ */
+#[allow(clippy::unnecessary_cast)] // `BaseInt` is only `u64` with 64-bit limbs
+pub fn from_base<D: Digit>(w: &mut [Limb], digits: &[D], base: u64) -> usize {
+    assert!((2..=base::MAX_BASE).contains(&base), "Invalid base: {}", base);
+    if let Some(&d) = digits.iter().find(|d| d.to_limb().0 as u64 >= base) {
+        panic!("Invalid digit {} for base {}", d.to_limb().0, base);
+    }
+    if digits.is_empty() { return 0; }
+    assert!(w.len() >= base::base_digits_to_len(digits.len(), base), "output is too short");
+
+    unsafe {
+        let size = base::from_base(limbs_mut(w), digits.as_ptr(), digits.len() as isize, base);
+        ll::normalize(limbs(w), size as isize) as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cmp::Ordering;
+    use std::vec::Vec;
+    use ll::limb::Limb;
+
+    fn l(xs: &[::ll::limb::BaseInt]) -> Vec<Limb> {
+        xs.iter().map(|&x| Limb(x)).collect()
+    }
+
+    const MAX: ::ll::limb::BaseInt = !0;
+
+    #[test]
+    fn add_sub() {
+        let x = l(&[MAX, MAX, 1]);
+        let y = l(&[1, 0]);
+        let mut w = l(&[0; 4]);
+
+        assert_eq!(add(&mut w, &x, &y), 0);
+        assert_eq!(&w[..], &l(&[0, 0, 2, 0])[..]);
+        assert_eq!(add_n(&mut w, &x[..2], &x[..2]), 1);
+        assert_eq!(&w[..2], &l(&[MAX - 1, MAX])[..]);
+
+        assert_eq!(sub(&mut w, &y, &x[..2]), 1);
+        assert_eq!(&w[..2], &l(&[2, 0])[..]);
+        assert_eq!(sub_n(&mut w, &y, &y), 0);
+        assert_eq!(&w[..2], &l(&[0, 0])[..]);
+
+        let mut z = x.clone();
+        assert_eq!(add_in_place(&mut z, &y), 0);
+        assert_eq!(sub_in_place(&mut z, &y), 0);
+        assert_eq!(z, x);
+
+        assert_eq!(add_1(&mut w, &x[..2], Limb(1)), 1);
+        assert_eq!(sub_1(&mut w, &y, Limb(2)), 1);
+        assert_eq!(&w[..2], &l(&[MAX, MAX])[..]);
+    }
+
+    #[test]
+    fn mul_div() {
+        let x = l(&[MAX, MAX]);
+        let y = l(&[2]);
+        let mut w = l(&[0; 4]);
+
+        mul(&mut w[..3], &y, &x);
+        assert_eq!(&w[..3], &l(&[MAX - 1, MAX, 1])[..]);
+        assert_eq!(mul_1(&mut w, &x, Limb(2)), 1);
+        assert_eq!(addmul_1(&mut w, &y, Limb(1)), 1);
+        assert_eq!(w[0], 0);
+
+        sqr(&mut w, &x);
+        assert_eq!(&w[..], &l(&[1, 0, MAX - 1, MAX])[..]);
+
+        let mut q = l(&[0; 4]);
+        let mut r = l(&[0; 2]);
+        divrem(&mut q[..3], &mut r, &w, &x);
+        assert_eq!(&q[..3], &l(&[MAX, MAX, 0])[..]);
+        assert_eq!(&r[..], &l(&[0, 0])[..]);
+
+        // An unnormalized divisor still needs room for the whole quotient
+        let n = l(&[5, 0, MAX - 1]);
+        let mut q1 = l(&[0; 3]);
+        let rem = divrem_1(&mut q1, &n, Limb(3));
+        divrem(&mut q[..3], &mut r, &n, &l(&[3, 0]));
+        assert_eq!(&q[..3], &q1[..]);
+        assert_eq!(&r[..], &[rem, Limb(0)][..]);
+
+        assert_eq!(divrem_1(&mut q, &x, Limb(MAX)), 0);
+        assert_eq!(&q[..2], &l(&[1, 1])[..]);
+    }
+
+    #[test]
+    #[should_panic(expected = "divide by zero")]
+    fn divrem_zero() {
+        let mut q = l(&[0; 2]);
+        let mut r = l(&[0; 2]);
+        divrem(&mut q, &mut r, &l(&[1]), &l(&[0, 0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "output is too short")]
+    fn mul_short_output() {
+        let mut w = l(&[0; 2]);
+        mul(&mut w, &l(&[1, 1]), &l(&[1]));
+    }
+
+    #[test]
+    fn shifts() {
+        let x = l(&[1 << 3, MAX]);
+        let mut w = l(&[0; 2]);
+        assert_eq!(shl(&mut w, &x, 4), 0xf);
+        assert_eq!(&w[..], &l(&[1 << 7, MAX << 4])[..]);
+        assert_eq!(shr(&mut w, &x, 0), 0);
+        assert_eq!(w, x);
+
+        assert_eq!(shr_in_place(&mut w, 4), 1 << (Limb::BITS - 1));
+        assert_eq!(shl_in_place(&mut w, 4), 0);
+        assert_eq!(&w[..], &l(&[0, MAX])[..]);
+        assert_eq!(cmp(&w, &x), Ordering::Less);
+
+        xor_n(&mut w, &x, &x);
+        assert_eq!(normalized_len(&w), 0);
+    }
+
+    #[test]
+    fn gcd_and_bases() {
+        let mut a = l(&[12, 0, 0]);
+        let mut b = l(&[18]);
+        let mut g = l(&[0]);
+        assert_eq!(gcd(&mut g, &mut b, &mut a), 1);
+        assert_eq!(g[0], 6);
+
+        let mut a = l(&[0, 1]);
+        let mut b = l(&[0, 1]);
+        let mut g = l(&[7, 7]);
+        assert_eq!(gcd(&mut g, &mut a, &mut b), 2);
+        assert_eq!(g, l(&[0, 1]));
+
+        let mut digits = Vec::new();
+        to_base(10, &l(&[1234, 0]), |d: u8| digits.push(d));
+        assert_eq!(digits, [1, 2, 3, 4]);
+
+        let mut w = l(&[0; 2]);
+        assert_eq!(from_base(&mut w, &digits, 10), 1);
+        assert_eq!(w[0], 1234);
+        assert_eq!(from_base::<u8>(&mut w, &[], 10), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid digit 10 for base 10")]
+    fn from_base_invalid_digit() {
+        let mut w = l(&[0; 2]);
+        from_base(&mut w, &[1u8, 10], 10);
+    }
+}
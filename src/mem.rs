@@ -15,31 +15,63 @@
 //! Memory management functions. The base functions align to a pointer-width, so they shouldn't
 //! be used for anything that requires an alignment greater than that.
 
-use alloc::heap;
+use std::alloc::{self, Layout};
+use std::cmp;
 use std::mem;
-use std::intrinsics::abort;
-use std::io::{self, Write};
 use std::ptr;
+#[cfg(feature = "std")]
+use std::cell::RefCell;
 
 use ll::limb::Limb;
 use ll::limb_ptr::LimbsMut;
 
-pub unsafe fn allocate_bytes(size: usize) -> *mut u8 {
-    let ret = heap::allocate(size, mem::align_of::<usize>());
-    if ret.is_null() {
-        let _ = writeln!(io::stderr(), "Failed to allocate memory (size={})", size);
-        abort();
+/// Allocates `size` zeroed bytes, returning a null pointer if the memory
+/// couldn't be allocated.
+pub unsafe fn try_allocate_bytes(size: usize) -> *mut u8 {
+    match layout(size) {
+        Some(layout) => alloc::alloc_zeroed(layout),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Reports a failure to allocate `size` bytes and aborts the process.
+#[cfg(feature = "std")]
+#[cold]
+#[inline(never)]
+pub fn out_of_memory(size: usize) -> ! {
+    use std::io::{self, Write};
+
+    let _ = writeln!(io::stderr(), "Failed to allocate memory (size={})", size);
+    ::std::process::abort()
+}
+
+/// Reports a failure to allocate `size` bytes through the global allocation
+/// error handler.
+#[cfg(not(feature = "std"))]
+#[cold]
+#[inline(never)]
+pub fn out_of_memory(size: usize) -> ! {
+    match layout(size) {
+        Some(layout) => alloc::handle_alloc_error(layout),
+        None => panic!("Failed to allocate memory (size={})", size),
     }
-    ptr::write_bytes(ret, 0, size);
-    ret
 }
 
 pub unsafe fn deallocate_bytes(ptr: *mut u8, size: usize) {
-    heap::deallocate(ptr, size, mem::align_of::<usize>());
+    alloc::dealloc(ptr, layout(size).expect("invalid allocation size"));
+}
+
+/// The layout used for an allocation of `size` bytes. Zero-sized requests
+/// still get a real allocation, so every pointer handed out can be freed.
+fn layout(size: usize) -> Option<Layout> {
+    Layout::from_size_align(cmp::max(size, 1), mem::align_of::<usize>()).ok()
 }
 
 /// Allocate for temporary storage. Ensures that the allocations are
-/// freed when the structure drops
+/// released when the structure drops. Released blocks go back to a
+/// per-thread pool, so repeated operations (multiplication, division,
+/// exponentiation, ...) reuse their scratch space instead of hitting the
+/// heap every time.
 pub struct TmpAllocator {
     mark: *mut Marker
 }
@@ -57,30 +89,63 @@ impl TmpAllocator {
     }
 
     pub unsafe fn allocate_bytes(&mut self, size: usize) -> *mut u8 {
-        let size = size + mem::size_of::<Marker>();
-        let ptr = allocate_bytes(size);
+        let ptr = self.try_allocate_bytes(size);
+        if ptr.is_null() {
+            out_of_memory(size.saturating_add(mem::size_of::<Marker>()));
+        }
+        ptr
+    }
+
+    /// Like `allocate_bytes`, but returns a null pointer instead of aborting if
+    /// the memory couldn't be allocated.
+    pub unsafe fn try_allocate_bytes(&mut self, size: usize) -> *mut u8 {
+        let total = match size.checked_add(mem::size_of::<Marker>()) {
+            Some(total) => total,
+            None => return ptr::null_mut(),
+        };
+        let mut mark = pool_take(total);
+        let ptr;
+        if mark.is_null() {
+            ptr = try_allocate_bytes(total);
+            if ptr.is_null() {
+                return ptr;
+            }
+            mark = ptr as *mut Marker;
+            (*mark).size = total;
+        } else {
+            // Hand out zeroed memory, just like a fresh allocation
+            ptr = mark as *mut u8;
+            ptr::write_bytes(ptr.add(mem::size_of::<Marker>()), 0, size);
+        }
 
-        let mark = ptr as *mut Marker;
-        (*mark).size = size;
         (*mark).next = self.mark;
 
         self.mark = mark;
 
-        ptr.offset(mem::size_of::<Marker>() as isize)
+        ptr.add(mem::size_of::<Marker>())
     }
 
     /// Allocate space for n limbs
     pub unsafe fn allocate(&mut self, n: usize) -> LimbsMut {
-        let ptr = self.allocate_bytes(n * mem::size_of::<Limb>()) as *mut Limb;
-        LimbsMut::new(ptr, 0, n as i32)
+        let size = n.checked_mul(mem::size_of::<Limb>())
+            .and_then(|size| size.checked_add(mem::size_of::<Marker>()))
+            .expect("capacity overflow");
+        let ptr = self.allocate_bytes(size - mem::size_of::<Marker>()) as *mut Limb;
+        LimbsMut::new(ptr, 0, n as isize)
     }
 
-    /// Allocates space for n1+n2 limbs and returns a pair of pointers.
-    pub unsafe fn allocate_2(&mut self, n1: usize, n2: usize) -> (LimbsMut, LimbsMut) {
-        let mut x = self.allocate(n1 + n2);
-        let mut y = x.offset(n1 as isize);
-        (LimbsMut::new(&mut *x, 0, n1 as i32),
-         LimbsMut::new(&mut *y, 0, n2 as i32))
+    /// Like `allocate`, but returns `None` instead of aborting if the space is
+    /// too large or couldn't be allocated.
+    pub unsafe fn try_allocate(&mut self, n: usize) -> Option<LimbsMut> {
+        if n > isize::MAX as usize {
+            return None;
+        }
+        let size = n.checked_mul(mem::size_of::<Limb>())?;
+        let ptr = self.try_allocate_bytes(size) as *mut Limb;
+        if ptr.is_null() {
+            return None;
+        }
+        Some(LimbsMut::new(ptr, 0, n as isize))
     }
 }
 
@@ -89,6 +154,135 @@ impl Drop for TmpAllocator {
         unsafe {
             let mut next;
             let mut mark = self.mark;
+            while !mark.is_null() {
+                next = (*mark).next;
+                pool_give(mark);
+                mark = next;
+            }
+        }
+    }
+}
+
+/// Removes a block of at least `size` bytes (including the marker) from this
+/// thread's scratch pool, returning null if there isn't one.
+///
+/// The pool may already have been destroyed if this runs from another
+/// thread-local's destructor, in which case there is never a block to reuse.
+#[cfg(feature = "std")]
+unsafe fn pool_take(size: usize) -> *mut Marker {
+    SCRATCH_POOL.try_with(|pool| take_from(Some(pool), size))
+        .unwrap_or_else(|_| take_from(None, size))
+}
+
+/// Puts `mark` into this thread's scratch pool, or frees it.
+#[cfg(feature = "std")]
+unsafe fn pool_give(mark: *mut Marker) {
+    if SCRATCH_POOL.try_with(|pool| give_to(Some(pool), mark)).is_err() {
+        give_to(None, mark);
+    }
+}
+
+/// The part of `pool_take` after looking up the pool, which is `None` if it
+/// has been destroyed.
+#[cfg(feature = "std")]
+unsafe fn take_from(pool: Option<&RefCell<ScratchPool>>, size: usize) -> *mut Marker {
+    match pool {
+        Some(pool) => pool.borrow_mut().take(size),
+        None => ptr::null_mut(),
+    }
+}
+
+/// The part of `pool_give` after looking up the pool, which is `None` if it
+/// has been destroyed.
+#[cfg(feature = "std")]
+unsafe fn give_to(pool: Option<&RefCell<ScratchPool>>, mark: *mut Marker) {
+    match pool {
+        Some(pool) => pool.borrow_mut().give(mark),
+        None => {
+            let size = (*mark).size;
+            deallocate_bytes(mark as *mut u8, size);
+        }
+    }
+}
+
+// Without `std` there are no thread-locals to keep a pool in, so scratch
+// space always comes straight from the allocator.
+
+#[cfg(not(feature = "std"))]
+unsafe fn pool_take(_size: usize) -> *mut Marker {
+    ptr::null_mut()
+}
+
+#[cfg(not(feature = "std"))]
+unsafe fn pool_give(mark: *mut Marker) {
+    let size = (*mark).size;
+    deallocate_bytes(mark as *mut u8, size);
+}
+
+/// The maximum number of bytes of scratch space each thread keeps around
+/// for reuse.
+#[cfg(feature = "std")]
+const SCRATCH_POOL_LIMIT: usize = 1 << 20;
+
+#[cfg(feature = "std")]
+thread_local!(static SCRATCH_POOL: RefCell<ScratchPool> = const {
+    RefCell::new(ScratchPool {
+        free: ptr::null_mut(),
+        bytes: 0,
+    })
+});
+
+/// Blocks released by the `TmpAllocator`s of a thread, available for reuse.
+#[cfg(feature = "std")]
+struct ScratchPool {
+    free: *mut Marker,
+    bytes: usize
+}
+
+#[cfg(feature = "std")]
+impl ScratchPool {
+    /// Removes the smallest block of at least `size` bytes (including the
+    /// marker) from the pool, returning null if there isn't one.
+    unsafe fn take(&mut self, size: usize) -> *mut Marker {
+        let mut best: *mut *mut Marker = ptr::null_mut();
+        let mut link: *mut *mut Marker = &mut self.free;
+        while !(*link).is_null() {
+            let mark = *link;
+            if (*mark).size >= size && (best.is_null() || (*mark).size < (**best).size) {
+                best = link;
+            }
+            link = &mut (*mark).next;
+        }
+
+        if best.is_null() {
+            return ptr::null_mut();
+        }
+        let mark = *best;
+        *best = (*mark).next;
+        self.bytes -= (*mark).size;
+        mark
+    }
+
+    /// Puts `mark` into the pool, freeing it instead if that would make the
+    /// pool too large.
+    unsafe fn give(&mut self, mark: *mut Marker) {
+        let size = (*mark).size;
+        if self.bytes + size > SCRATCH_POOL_LIMIT {
+            deallocate_bytes(mark as *mut u8, size);
+            return;
+        }
+        (*mark).next = self.free;
+        self.free = mark;
+        self.bytes += size;
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for ScratchPool {
+    fn drop(&mut self) {
+        unsafe {
+            let mut next;
+            let mut mark = self.free;
             while !mark.is_null() {
                 next = (*mark).next;
                 let size = (*mark).size;
@@ -98,3 +292,75 @@ impl Drop for TmpAllocator {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::TmpAllocator;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn scratch_reuse() {
+        use ll::limb::Limb;
+
+        let first = unsafe {
+            let mut tmp = TmpAllocator::new();
+            let x = tmp.allocate(16);
+            for i in 0..16 {
+                *x.offset(i) = Limb(!0);
+            }
+            &*x as *const Limb
+        };
+
+        unsafe {
+            // The same block comes back, zeroed
+            let mut tmp = TmpAllocator::new();
+            let x = tmp.allocate(10);
+            assert_eq!(&*x as *const Limb, first);
+            for i in 0..10 {
+                assert_eq!(*x.offset(i), 0);
+            }
+
+            // ...but not while it's in use
+            let y = tmp.allocate(10);
+            assert!(!std::ptr::eq(&*y, first));
+        }
+    }
+
+    #[test]
+    fn try_allocate() {
+        unsafe {
+            let mut tmp = TmpAllocator::new();
+            assert!(tmp.try_allocate(usize::MAX).is_none());
+            assert!(tmp.try_allocate(isize::MAX as usize).is_none());
+            let x = tmp.try_allocate(16).unwrap();
+            assert_eq!(*x.offset(15), 0);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn scratch_without_pool() {
+        use std::cell::RefCell;
+        use std::mem;
+        use std::ptr;
+        use super::{give_to, take_from, try_allocate_bytes, Marker, ScratchPool};
+
+        unsafe {
+            let size = mem::size_of::<Marker>() + 64;
+            let mark = try_allocate_bytes(size) as *mut Marker;
+            assert!(!mark.is_null());
+            (*mark).size = size;
+
+            let pool = RefCell::new(ScratchPool { free: ptr::null_mut(), bytes: 0 });
+            give_to(Some(&pool), mark);
+            assert_eq!(pool.borrow().bytes, size);
+            assert_eq!(take_from(Some(&pool), size), mark);
+            assert_eq!(pool.borrow().bytes, 0);
+
+            // Once the pool is destroyed there's nothing to reuse, and blocks
+            // are freed straight away
+            assert!(take_from(None, size).is_null());
+            give_to(None, mark);
+        }
+    }
+}
@@ -114,10 +114,10 @@ impl<'a> MtgyModulus<'a> {
         assert!(!modulus.is_even(), "Montgomery modulus must be odd");
         assert_eq!(modulus.sign(), 1, "Montgomery modulus must be positive");
         use ll::limb::Limb;
-        let limbs_count = (modulus.bit_length() as usize + Limb::BITS - 1) / Limb::BITS;
+        let limbs_count = modulus.bit_length().div_ceil(Limb::BITS);
         let r = Int::one() << (limbs_count * Limb::BITS);
         MtgyModulus {
-            modulus: modulus,
+            modulus,
             modulus_inv0: ::ll::mtgy::inv1(*(&r - modulus).limbs()),
             limbs: limbs_count,
             r: r.clone(),
@@ -126,18 +126,18 @@ impl<'a> MtgyModulus<'a> {
 
     fn redc(&self, a: &mut Int) {
         unsafe {
-            assert_eq!(a.abs_size(), 2*self.limbs as i32);
-            let mut t = Int::with_capacity(2 * self.limbs as u32);
+            assert_eq!(a.abs_size(), 2*self.limbs as isize);
+            let mut t = Int::with_capacity(2 * self.limbs);
             ::ll::copy_incr(a.limbs(), t.limbs_uninit(), a.abs_size());
-            for i in a.abs_size()..(t.cap as i32) {
-                *t.limbs_uninit().offset(i as isize) = ::ll::limb::Limb(0);
+            for i in a.abs_size()..(t.cap as isize) {
+                *t.limbs_uninit().offset(i) = ::ll::limb::Limb(0);
             }
             ::ll::mtgy::redc(a.limbs_uninit(),
-                             self.limbs as i32,
+                             self.limbs as isize,
                              self.modulus.limbs(),
                              self.modulus_inv0,
                              t.limbs_uninit());
-            a.size = self.limbs as i32;
+            a.size = self.limbs as isize;
         }
     }
 
@@ -146,18 +146,18 @@ impl<'a> MtgyModulus<'a> {
     /// # Panic
     ///
     /// Panics if the two integers are not of the expected size (it is
-    /// only likely to happen in case of a mixup of two MtgyModulus).
+    ///   only likely to happen in case of a mixup of two MtgyModulus).
     pub fn mul(&self, a: &MtgyInt, b: &MtgyInt) -> MtgyInt {
         unsafe {
-            assert_eq!(a.0.abs_size(), self.limbs as i32);
-            assert_eq!(b.0.abs_size(), self.limbs as i32);
-            let mut t = Int::with_capacity(2 * self.limbs as u32);
-            t.size = t.cap as i32;
+            assert_eq!(a.0.abs_size(), self.limbs as isize);
+            assert_eq!(b.0.abs_size(), self.limbs as isize);
+            let mut t = Int::with_capacity(2 * self.limbs);
+            t.size = t.cap as isize;
             ::ll::mul(t.limbs_uninit(),
                       a.0.limbs(),
-                      self.limbs as i32,
+                      self.limbs as isize,
                       b.0.limbs(),
-                      self.limbs as i32);
+                      self.limbs as isize);
             self.redc(&mut t);
             MtgyInt(t)
         }
@@ -168,13 +168,13 @@ impl<'a> MtgyModulus<'a> {
     /// # Panic
     ///
     /// Panics if the integer is not of the expected size (it is
-    /// only likely to happen in case of a mixup of two MtgyModulus).
+    ///   only likely to happen in case of a mixup of two MtgyModulus).
     pub fn sqr(&self, a: &MtgyInt) -> MtgyInt {
         unsafe {
-            assert_eq!(a.0.abs_size(), self.limbs as i32);
-            let mut t = Int::with_capacity(2 * self.limbs as u32);
-            t.size = t.cap as i32;
-            ::ll::sqr(t.limbs_uninit(), a.0.limbs(), self.limbs as i32);
+            assert_eq!(a.0.abs_size(), self.limbs as isize);
+            let mut t = Int::with_capacity(2 * self.limbs);
+            t.size = t.cap as isize;
+            ::ll::sqr(t.limbs_uninit(), a.0.limbs(), self.limbs as isize);
             self.redc(&mut t);
             MtgyInt(t)
         }
@@ -188,15 +188,15 @@ impl<'a> MtgyModulus<'a> {
     /// # Panic
     ///
     /// * Panics if the basis integer is not of the expected size (it is
-    /// only likely to happen in case of a mixup of two MtgyModulus).
+    ///   only likely to happen in case of a mixup of two MtgyModulus).
     /// * Panics if exponent is negative.
     pub fn pow(&self, basis: &MtgyInt, exponent: &Int) -> MtgyInt {
         let mut result = self.to_mtgy(&Int::one());
         unsafe {
-            assert_eq!(basis.0.abs_size(), self.limbs as i32);
+            assert_eq!(basis.0.abs_size(), self.limbs as isize);
             assert!(exponent.sign() >= 0);
             ::ll::mtgy::modpow(result.0.limbs_uninit(),
-                               self.limbs as i32,
+                               self.limbs as isize,
                                self.modulus.limbs(),
                                self.modulus_inv0,
                                basis.0.limbs(),
@@ -212,11 +212,11 @@ impl<'a> MtgyModulus<'a> {
 
     fn pad_to(a: &mut Int, s: usize) {
         unsafe {
-            a.ensure_capacity(s as u32);
-            for i in a.abs_size()..(a.cap as i32) {
-                *a.limbs_uninit().offset(i as isize) = ::ll::limb::Limb(0);
+            a.ensure_capacity(s);
+            for i in a.abs_size()..(a.cap as isize) {
+                *a.limbs_uninit().offset(i) = ::ll::limb::Limb(0);
             }
-            a.size = s as i32;
+            a.size = s as isize;
         }
     }
 
@@ -232,14 +232,14 @@ impl<'a> MtgyModulus<'a> {
     /// # Panic
     ///
     /// * Panics if the integer is not of the expected size (it is
-    /// only likely to happen in case of a mixup of two MtgyModulus).
+    ///   only likely to happen in case of a mixup of two MtgyModulus).
     #[allow(dead_code)]
     pub fn to_int(&self, a: &MtgyInt) -> Int {
-        assert_eq!(a.0.abs_size(), self.limbs as i32);
+        assert_eq!(a.0.abs_size(), self.limbs as isize);
         let mut it = unsafe {
-            let mut it = Int::with_capacity(2 * self.limbs as u32);
-            ::ll::copy_incr(a.0.limbs(), it.limbs_uninit(), self.limbs as i32);
-            it.size = self.limbs as i32;
+            let mut it = Int::with_capacity(2 * self.limbs);
+            ::ll::copy_incr(a.0.limbs(), it.limbs_uninit(), self.limbs as isize);
+            it.size = self.limbs as isize;
             it.normalize();
             it
         };
@@ -251,7 +251,7 @@ impl<'a> MtgyModulus<'a> {
     }
 }
 
-#[cfg(target_pointer_width = "64")]
+#[cfg(limb_width = "64")]
 #[test]
 fn redc() {
     let cases = [("1547425065876476735897735405", "193514046488575", "87960930698705")];
@@ -25,11 +25,12 @@ use std::ops::{
     Add, Sub, Mul, Div, Rem, Neg,
     AddAssign, SubAssign, MulAssign, DivAssign,
 };
-use num_traits::{Zero, One};
+use num_traits::{Zero, One, Num, Signed, FromPrimitive};
 
+use float;
 use ll;
 
-use int::{Int, ParseIntError};
+use int::{Int, ParseIntError, StableHasher};
 
 use ieee754::Ieee754;
 
@@ -61,14 +62,14 @@ impl Rational {
 
         if n == 0 {
             return Rational {
-                n: n,
+                n,
                 d: Int::one()
             }
         }
 
         let mut rat = Rational {
-            n: n,
-            d: d
+            n,
+            d
         };
 
         rat.normalize();
@@ -108,6 +109,18 @@ impl Rational {
         }
     }
 
+    /**
+     * Returns a hash of the value that is the same on every platform and for every build
+     * of this library. Equal values give the same hash, and a whole number gives the same
+     * hash as the equal `Int`.
+     */
+    pub fn stable_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = StableHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
     /**
      * Returns the reciprocal of this Rational
      */
@@ -157,12 +170,37 @@ impl Rational {
     }
 
     /**
-     * Converts this Rational to an `f64` value.
+     * Converts this Rational to the nearest `f64` value, rounding ties to even.
      */
     pub fn to_f64(&self) -> f64 {
-        let mut normalized = self.clone();
-        normalized.normalize();
-        normalized.n.to_f64() / normalized.d.to_f64()
+        f64::from_bits(self.to_float_bits(float::F64))
+    }
+
+    /**
+     * Converts this Rational to the nearest `f32` value, rounding ties to even.
+     */
+    pub fn to_f32(&self) -> f32 {
+        f32::from_bits(self.to_float_bits(float::F32) as u32)
+    }
+
+    fn to_float_bits(&self, fmt: float::Format) -> u64 {
+        let neg = self.sign() < 0;
+        let n = self.n.clone().abs();
+        let d = self.d.clone().abs();
+        if n == 0 {
+            return float::to_bits(&n, 0, false, neg, fmt);
+        }
+
+        // Scale so the quotient has at least two more bits than the significand,
+        // then the remainder only matters for whether it's zero.
+        let k = n.bit_length() as isize - d.bit_length() as isize;
+        let s = fmt.mant_bits as isize + 2 - k;
+        let (q, r) = if s >= 0 {
+            (n << s as usize).divmod(&d)
+        } else {
+            n.divmod(&(d << (-s) as usize))
+        };
+        float::to_bits(&q, -s, r != 0, neg, fmt)
     }
 }
 
@@ -240,7 +278,7 @@ impl PartialEq<Int> for Rational {
 
         // Denominator is 1
         if self.d == 1 || self.d == -1 {
-            return self.n.abs_eq(&other);
+            return self.n.abs_eq(other);
         }
 
         let other = other * &self.d;
@@ -339,21 +377,20 @@ impl PartialOrd<Rational> for Int {
 
 impl hash::Hash for Rational {
     fn hash<H>(&self, state: &mut H) where H: hash::Hasher {
-        let gcd = self.n.gcd(&self.d);
-        let sign = self.sign();
-        sign.hash(state);
-        // GCD is one, so it's a normalized fraction
-        if gcd == 1 {
-            self.n.abs_hash(state);
-            self.d.abs_hash(state);
-        }
-
-        // Gets the normalized numerator and denominator
-        let n = &self.n / &gcd;
-        let d = &self.d / gcd;
+        let mut normalized;
+        let rat = if self.normalized() && self.d.sign() > 0 {
+            self
+        } else {
+            normalized = self.clone();
+            normalized.normalize();
+            &normalized
+        };
 
-        n.hash(state);
-        d.hash(state);
+        // A whole number hashes the same as the `Int` it compares equal to
+        rat.n.hash(state);
+        if rat.d != 1 {
+            rat.d.abs_hash(state);
+        }
     }
 }
 
@@ -435,7 +472,7 @@ impl<'a> Add<&'a Rational> for Rational {
     }
 }
 
-impl<'a> Add<Rational> for &'a Rational {
+impl Add<Rational> for &Rational {
     type Output = Rational;
 
     fn add(self, mut other: Rational) -> Rational {
@@ -444,7 +481,7 @@ impl<'a> Add<Rational> for &'a Rational {
     }
 }
 
-impl<'a, 'b> Add<&'a Rational> for &'b Rational {
+impl<'a> Add<&'a Rational> for &Rational {
     type Output = Rational;
 
     fn add(self, other: &'a Rational) -> Rational {
@@ -470,7 +507,7 @@ impl<'a> Add<&'a Int> for Rational {
     }
 }
 
-impl<'a> Add<Int> for &'a Rational {
+impl Add<Int> for &Rational {
     type Output = Rational;
 
     fn add(self, other: Int) -> Rational {
@@ -478,7 +515,7 @@ impl<'a> Add<Int> for &'a Rational {
     }
 }
 
-impl<'a, 'b> Add<&'a Int> for &'b Rational {
+impl<'a> Add<&'a Int> for &Rational {
     type Output = Rational;
 
     fn add(self, other: &'a Int) -> Rational {
@@ -502,7 +539,7 @@ impl<'a> Add<&'a Rational> for Int {
     }
 }
 
-impl<'a> Add<Rational> for &'a Int {
+impl Add<Rational> for &Int {
     type Output = Rational;
 
     fn add(self, other: Rational) -> Rational {
@@ -510,7 +547,7 @@ impl<'a> Add<Rational> for &'a Int {
     }
 }
 
-impl<'a, 'b> Add<&'a Rational> for &'b Int {
+impl<'a> Add<&'a Rational> for &Int {
     type Output = Rational;
 
     fn add(self, other: &'a Rational) -> Rational {
@@ -553,7 +590,7 @@ impl<'a> Sub<&'a Rational> for Rational {
     }
 }
 
-impl<'a> Sub<Rational> for &'a Rational {
+impl Sub<Rational> for &Rational {
     type Output = Rational;
 
     fn sub(self, mut other: Rational) -> Rational {
@@ -562,7 +599,7 @@ impl<'a> Sub<Rational> for &'a Rational {
     }
 }
 
-impl<'a, 'b> Sub<&'a Rational> for &'b Rational {
+impl<'a> Sub<&'a Rational> for &Rational {
     type Output = Rational;
 
     fn sub(self, other: &'a Rational) -> Rational {
@@ -579,7 +616,7 @@ impl Neg for Rational {
     }
 }
 
-impl<'a> Neg for &'a Rational {
+impl Neg for &Rational {
     type Output = Rational;
 
     fn neg(self) -> Rational {
@@ -630,7 +667,7 @@ impl<'a> Mul<&'a Rational> for Rational {
     }
 }
 
-impl<'a> Mul<Rational> for &'a Rational {
+impl Mul<Rational> for &Rational {
     type Output = Rational;
 
     fn mul(self, mut other: Rational) -> Rational {
@@ -639,7 +676,7 @@ impl<'a> Mul<Rational> for &'a Rational {
     }
 }
 
-impl<'a, 'b> Mul<&'a Rational> for &'b Rational {
+impl<'a> Mul<&'a Rational> for &Rational {
     type Output = Rational;
 
     fn mul(self, other: &'a Rational) -> Rational {
@@ -665,7 +702,7 @@ impl<'a> Mul<&'a Int> for Rational {
     }
 }
 
-impl<'a> Mul<Int> for &'a Rational {
+impl Mul<Int> for &Rational {
     type Output = Rational;
 
     fn mul(self, other: Int) -> Rational {
@@ -673,7 +710,7 @@ impl<'a> Mul<Int> for &'a Rational {
     }
 }
 
-impl<'a, 'b> Mul<&'a Int> for &'b Rational {
+impl<'a> Mul<&'a Int> for &Rational {
     type Output = Rational;
 
     fn mul(self, other: &'a Int) -> Rational {
@@ -697,7 +734,7 @@ impl<'a> Mul<&'a Rational> for Int {
     }
 }
 
-impl<'a> Mul<Rational> for &'a Int {
+impl Mul<Rational> for &Int {
     type Output = Rational;
 
     fn mul(self, other: Rational) -> Rational {
@@ -705,7 +742,7 @@ impl<'a> Mul<Rational> for &'a Int {
     }
 }
 
-impl<'a, 'b> Mul<&'a Rational> for &'b Int {
+impl<'a> Mul<&'a Rational> for &Int {
     type Output = Rational;
 
     fn mul(self, other: &'a Rational) -> Rational {
@@ -769,7 +806,7 @@ impl<'a> Div<&'a Rational> for Rational {
     }
 }
 
-impl<'a> Div<Rational> for &'a Rational {
+impl Div<Rational> for &Rational {
     type Output = Rational;
 
     fn div(self, mut other: Rational) -> Rational {
@@ -778,7 +815,7 @@ impl<'a> Div<Rational> for &'a Rational {
     }
 }
 
-impl<'a, 'b> Div<&'a Rational> for &'b Rational {
+impl<'a> Div<&'a Rational> for &Rational {
     type Output = Rational;
 
     fn div(self, other: &'a Rational) -> Rational {
@@ -804,7 +841,7 @@ impl<'a> Div<&'a Int> for Rational {
     }
 }
 
-impl<'a> Div<Int> for &'a Rational {
+impl Div<Int> for &Rational {
     type Output = Rational;
 
     fn div(self, other: Int) -> Rational {
@@ -812,7 +849,7 @@ impl<'a> Div<Int> for &'a Rational {
     }
 }
 
-impl<'a, 'b> Div<&'a Int> for &'b Rational {
+impl<'a> Div<&'a Int> for &Rational {
     type Output = Rational;
 
     fn div(self, other: &'a Int) -> Rational {
@@ -836,7 +873,7 @@ impl<'a> Div<&'a Rational> for Int {
     }
 }
 
-impl<'a> Div<Rational> for &'a Int {
+impl Div<Rational> for &Int {
     type Output = Rational;
 
     fn div(self, other: Rational) -> Rational {
@@ -844,7 +881,7 @@ impl<'a> Div<Rational> for &'a Int {
     }
 }
 
-impl<'a, 'b> Div<&'a Rational> for &'b Int {
+impl<'a> Div<&'a Rational> for &Int {
     type Output = Rational;
 
     fn div(self, other: &'a Rational) -> Rational {
@@ -852,6 +889,44 @@ impl<'a, 'b> Div<&'a Rational> for &'b Int {
     }
 }
 
+impl Rem<Rational> for Rational {
+    type Output = Rational;
+
+    #[inline]
+    fn rem(self, other: Rational) -> Rational {
+        &self % &other
+    }
+}
+
+impl<'a> Rem<&'a Rational> for Rational {
+    type Output = Rational;
+
+    #[inline]
+    fn rem(self, other: &'a Rational) -> Rational {
+        &self % other
+    }
+}
+
+impl Rem<Rational> for &Rational {
+    type Output = Rational;
+
+    #[inline]
+    fn rem(self, other: Rational) -> Rational {
+        self % &other
+    }
+}
+
+impl<'a> Rem<&'a Rational> for &Rational {
+    type Output = Rational;
+
+    /// The remainder of truncating division, which has the sign of `self`
+    fn rem(self, other: &'a Rational) -> Rational {
+        // a/b % c/d = (ad % cb) / bd
+        let n = (&self.n * &other.d) % (&other.n * &self.d);
+        Rational::new(n, &self.d * &other.d)
+    }
+}
+
 impl<U: Into<Int>> From<U> for Rational {
     fn from(val: U) -> Rational {
         Rational::new(val.into(), Int::one())
@@ -859,16 +934,22 @@ impl<U: Into<Int>> From<U> for Rational {
 }
 
 macro_rules! impl_from_float {
-    ($fty:ty, $signif_bits:expr) => {
+    ($fty:ty, $signif_bits:expr, $bias:expr) => {
         impl From<$fty> for Rational {
             fn from(val: $fty) -> Rational {
                 let (neg, exponent, significand) = val.decompose();
 
-                let mut coeff = Int::from(2).pow($signif_bits) + Int::from(significand);
+                // Zero and subnormals have no implicit leading bit, and the same
+                // exponent as the smallest normal numbers
+                let (mut coeff, exponent) = if exponent as i32 == -$bias {
+                    (Int::from(significand), exponent + 1)
+                } else {
+                    (Int::from(2).pow($signif_bits) + Int::from(significand), exponent)
+                };
                 if neg { coeff *= -1; }
 
                 let corrected_expt = (exponent as i32) - $signif_bits;
-                let pow2 = Int::from(2).pow(corrected_expt.abs() as usize);
+                let pow2 = Int::from(2).pow(corrected_expt.unsigned_abs() as usize);
 
                 if corrected_expt < 0 {
                     Rational::new(coeff, pow2)
@@ -881,27 +962,43 @@ macro_rules! impl_from_float {
     }
 }
 
-impl_from_float!(f32, 23);
-impl_from_float!(f64, 52);
+impl_from_float!(f32, 23, 127);
+impl_from_float!(f64, 52, 1023);
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct ParseRationalError(ParseIntError);
+pub struct ParseRationalError { kind: ParseErrorKind }
+
+#[derive(Debug, Clone, PartialEq)]
+enum ParseErrorKind {
+    Int(ParseIntError),
+    ZeroDenominator
+}
 
+const ZERO_DENOMINATOR: &str = "denominator is zero";
+
+#[cfg(feature = "std")]
 impl std::error::Error for ParseRationalError {
-    fn description<'a>(&'a self) -> &'a str {
-        self.0.description()
+    #[allow(deprecated)]
+    fn description(&self) -> &str {
+        match self.kind {
+            ParseErrorKind::Int(ref e) => e.description(),
+            ParseErrorKind::ZeroDenominator => ZERO_DENOMINATOR
+        }
     }
 }
 
 impl fmt::Display for ParseRationalError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.0.fmt(f)
+        match self.kind {
+            ParseErrorKind::Int(ref e) => e.fmt(f),
+            ParseErrorKind::ZeroDenominator => ZERO_DENOMINATOR.fmt(f)
+        }
     }
 }
 
 impl From<ParseIntError> for ParseRationalError {
     fn from(e: ParseIntError) -> ParseRationalError {
-        ParseRationalError(e)
+        ParseRationalError { kind: ParseErrorKind::Int(e) }
     }
 }
 
@@ -909,28 +1006,30 @@ impl std::str::FromStr for Rational {
     type Err = ParseRationalError;
 
     fn from_str(s: &str) -> Result<Rational, ParseRationalError> {
-        match s.find('/') {
-            Some(i) => Ok(Rational::new(Int::from_str(&s[..i])?, Int::from_str(&s[i + 1..])?)),
-            None => Ok(Rational::new(Int::from_str(s)?, Int::one())),
-        }
+        Rational::from_str_radix(s, 10)
     }
 }
 
 impl fmt::Debug for Rational {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}/{:?}", self.n, self.d)
+        f.pad(&format!("{:?}/{:?}", self.n, self.d))
     }
 }
 
 impl fmt::Display for Rational {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if !self.normalized() {
-            let mut normalized = self.clone();
-            normalized.normalize();
-            write!(f, "{}/{}", normalized.n, normalized.d)
+        let mut normalized;
+        let rat = if self.normalized() && self.d.sign() > 0 {
+            self
         } else {
-            write!(f, "{}/{}", self.n, self.d)
-        }
+            normalized = self.clone();
+            normalized.normalize();
+            &normalized
+        };
+
+        // Leave the sign to pad_integral, so `+` and zero-padding work
+        let body = format!("{}/{}", rat.n.clone().abs(), rat.d);
+        f.pad_integral(rat.n.sign() >= 0, "", &body)
     }
 }
 
@@ -953,20 +1052,210 @@ impl One for Rational {
     }
 }
 
+impl Num for Rational {
+    type FromStrRadixErr = ParseRationalError;
+
+    fn from_str_radix(s: &str, radix: u32) -> Result<Rational, ParseRationalError> {
+        match s.find('/') {
+            Some(i) => {
+                let n = Int::from_str_radix(&s[..i], radix as u8)?;
+                let d = Int::from_str_radix(&s[i + 1..], radix as u8)?;
+                if d.is_zero() {
+                    return Err(ParseRationalError { kind: ParseErrorKind::ZeroDenominator });
+                }
+                Ok(Rational::new(n, d))
+            }
+            None => Ok(Rational::new(Int::from_str_radix(s, radix as u8)?, Int::one())),
+        }
+    }
+}
+
+impl Signed for Rational {
+    fn abs(&self) -> Rational {
+        self.clone().abs()
+    }
+
+    fn abs_sub(&self, other: &Rational) -> Rational {
+        if *self <= *other {
+            Rational::zero()
+        } else {
+            self - other
+        }
+    }
+
+    fn signum(&self) -> Rational {
+        Rational::from(self.sign())
+    }
+
+    fn is_positive(&self) -> bool {
+        self.sign() > 0
+    }
+
+    fn is_negative(&self) -> bool {
+        self.sign() < 0
+    }
+}
+
+impl FromPrimitive for Rational {
+    fn from_i64(n: i64) -> Option<Rational> {
+        Some(Rational::from(n))
+    }
+
+    fn from_u64(n: u64) -> Option<Rational> {
+        Some(Rational::from(n))
+    }
+
+    fn from_i128(n: i128) -> Option<Rational> {
+        Some(Rational::from(n))
+    }
+
+    fn from_u128(n: u128) -> Option<Rational> {
+        Some(Rational::from(n))
+    }
+
+    fn from_f32(n: f32) -> Option<Rational> {
+        if n.is_finite() { Some(Rational::from(n)) } else { None }
+    }
+
+    fn from_f64(n: f64) -> Option<Rational> {
+        if n.is_finite() { Some(Rational::from(n)) } else { None }
+    }
+}
+
+
+// Conversions to and from num-rational's `BigRational`
+
+#[cfg(feature = "num-rational")]
+impl<'a> From<&'a ::num_rational::BigRational> for Rational {
+    fn from(r: &'a ::num_rational::BigRational) -> Rational {
+        Rational::new(Int::from(r.numer()), Int::from(r.denom()))
+    }
+}
+
+#[cfg(feature = "num-rational")]
+impl From<::num_rational::BigRational> for Rational {
+    #[inline]
+    fn from(r: ::num_rational::BigRational) -> Rational {
+        Rational::from(&r)
+    }
+}
+
+#[cfg(feature = "num-rational")]
+impl<'a> From<&'a Rational> for ::num_rational::BigRational {
+    fn from(r: &'a Rational) -> ::num_rational::BigRational {
+        ::num_rational::BigRational::new((&r.n).into(), (&r.d).into())
+    }
+}
+
+#[cfg(feature = "num-rational")]
+impl From<Rational> for ::num_rational::BigRational {
+    #[inline]
+    fn from(r: Rational) -> ::num_rational::BigRational {
+        ::num_rational::BigRational::from(&r)
+    }
+}
+
+// Serde support
+//
+// Human-readable formats get an `"n/d"` string, binary formats a `(n, d)`
+// tuple of `Int`s. Either way the value is written out normalized.
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for Rational {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeTuple;
+
+        if serializer.is_human_readable() {
+            return serializer.collect_str(self);
+        }
+
+        let mut normalized;
+        let rat = if self.normalized() && self.d.sign() > 0 {
+            self
+        } else {
+            normalized = self.clone();
+            normalized.normalize();
+            &normalized
+        };
+
+        let mut tup = serializer.serialize_tuple(2)?;
+        tup.serialize_element(&rat.n)?;
+        tup.serialize_element(&rat.d)?;
+        tup.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Rational {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Rational, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(RationalVisitor)
+        } else {
+            deserializer.deserialize_tuple(2, RationalVisitor)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+struct RationalVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::de::Visitor<'de> for RationalVisitor {
+    type Value = Rational;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an \"n/d\" string or an (n, d) tuple")
+    }
+
+    fn visit_str<E: ::serde::de::Error>(self, v: &str) -> Result<Rational, E> {
+        let (n, d) = match v.find('/') {
+            Some(i) => (&v[..i], &v[i + 1..]),
+            None => (v, "1"),
+        };
+        let n = n.parse::<Int>().map_err(E::custom)?;
+        let d = d.parse::<Int>().map_err(E::custom)?;
+        if d == 0 {
+            return Err(E::custom("denominator is zero"));
+        }
+        Ok(Rational::new(n, d))
+    }
+
+    fn visit_seq<A: ::serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Rational, A::Error> {
+        use serde::de::Error;
+
+        let n: Int = match seq.next_element()? {
+            Some(n) => n,
+            None => return Err(A::Error::invalid_length(0, &self)),
+        };
+        let d: Int = match seq.next_element()? {
+            Some(d) => d,
+            None => return Err(A::Error::invalid_length(1, &self)),
+        };
+        if d == 0 {
+            return Err(A::Error::custom("denominator is zero"));
+        }
+        Ok(Rational::new(n, d))
+    }
+}
+
 
 #[cfg(test)]
 mod test {
     use std;
     use std::hash::{Hash, Hasher};
+    #[cfg(feature = "std")]
     use rand::{self, Rng};
+    #[cfg(feature = "unstable")]
     use test::{self, Bencher};
     use super::*;
     use ll::limb::Limb;
     use std::str::FromStr;
+    use std::string::ToString;
     use num_traits::Zero;
 
     use std::cmp::Ordering;
 
+    #[cfg(feature = "std")]
     use int::RandomInt;
 
     macro_rules! assert_mp_eq (
@@ -975,8 +1264,8 @@ mod test {
                 let l : &Rational = &$l;
                 let r : &Rational = &$r;
                 if l != r {
-                    println!("assertion failed: {} == {}", stringify!($l), stringify!($r));
-                    panic!("{:?} != {:?}", l, r);
+                    panic!("assertion failed: {} == {}\n{:?} != {:?}",
+                           stringify!($l), stringify!($r), l, r);
                 }
             }
         )
@@ -1011,7 +1300,7 @@ mod test {
             ("1/1", "-1/2", "1/2")
         };
 
-        for &(ref l, ref r, ref a) in cases.iter() {
+        for (l, r, a) in cases.iter() {
             assert_mp_eq!(l + r, *a);
         }
     }
@@ -1027,7 +1316,7 @@ mod test {
             ("1/3", "1/4", "1/12")
         };
 
-        for &(ref l, ref r, ref a) in cases.iter() {
+        for (l, r, a) in cases.iter() {
             assert_mp_eq!(l - r, *a);
         }
     }
@@ -1042,11 +1331,11 @@ mod test {
             ("-1/-1", "1/-1")
         };
 
-        for &(ref l, ref r) in cases.iter() {
+        for (l, r) in cases.iter() {
             assert_eq!(&(-l), r);
         }
 
-        for &(ref l, ref r) in cases.iter() {
+        for (l, r) in cases.iter() {
             assert_eq!(-l.clone(), r.clone());
         }
     }
@@ -1062,7 +1351,7 @@ mod test {
             ("3/8", "2/5", "3/20")
         };
 
-        for &(ref l, ref r, ref a) in cases.iter() {
+        for (l, r, a) in cases.iter() {
             assert_mp_eq!(l * r, *a);
         }
     }
@@ -1077,7 +1366,7 @@ mod test {
             ("3/8", "2/5", "15/16")
         };
 
-        for &(ref l, ref r, ref a) in cases.iter() {
+        for (l, r, a) in cases.iter() {
             assert_mp_eq!(l / r, *a);
         }
     }
@@ -1116,7 +1405,7 @@ mod test {
             ("1337/-1337", "-1337/-1337")
         };
 
-        for &(ref r, ref l) in cases.into_iter() {
+        for (r, l) in cases.iter() {
             assert_eq!(&r.clone().abs(), l);
         }
     }
@@ -1143,19 +1432,13 @@ mod test {
             ("100/28", 4)
         };
 
-        for &(ref q, ref i) in cases.iter() {
+        for (q, i) in cases.iter() {
             assert_eq!(&q.clone().round(), i);
         }
     }
 
     #[test]
     fn from_int_primitive() {
-        use std::usize; use std::isize;
-        use std::u64; use std::i64;
-        use std::u32; use std::i32;
-        use std::u16; use std::i16;
-        use std::u8; use std::i8;
-
         let (a, b) = (usize::MAX, isize::MIN);
         let (c, d) = (u64::MAX, i64::MIN);
         let (e, f) = (u32::MAX, i32::MIN);
@@ -1188,8 +1471,147 @@ mod test {
                 assert_eq!(&Rational::from(f as f32), &expected);
             }
         }
+
+        assert_eq!(Rational::from(0.0f64), Rational::zero());
+        assert_eq!(Rational::from(-0.0f32), Rational::zero());
+        assert_eq!(Rational::from(5e-324f64), Rational::new(Int::one(), Int::from(2).pow(1074)));
+        assert_eq!(Rational::from(1e-45f32), Rational::new(Int::one(), Int::from(2).pow(149)));
+        let smallest_normal = Rational::new(Int::one(), Int::from(2).pow(1022));
+        assert_eq!(Rational::from(f64::MIN_POSITIVE), smallest_normal);
+    }
+
+    #[test]
+    fn to_float() {
+        let ten = Int::from(10);
+        let r = Rational::new(ten.pow(400), ten.pow(399));
+        assert_eq!(r.to_f64(), 10.0);
+        assert_eq!(r.to_f32(), 10.0);
+
+        let r = Rational::new(Int::from(-1), Int::from(3));
+        assert_eq!(r.to_f64(), -1.0 / 3.0);
+        assert_eq!(r.to_f32(), -1.0 / 3.0);
+
+        let r = Rational::new(Int::from(2).pow(2000) + Int::one(), Int::from(3) << 1000);
+        assert_eq!(r.to_f64(), 2f64.powi(1000) / 3.0);
+
+        // Subnormals, and rounding to the smallest one or to zero
+        let tiny = Int::from(2).pow(1074);
+        assert_eq!(Rational::new(Int::one(), tiny.clone()).to_f64(), 5e-324);
+        assert_eq!(Rational::new(Int::from(3), &tiny << 2).to_f64(), 5e-324);
+        assert_eq!(Rational::new(Int::one(), &tiny << 1).to_f64(), 0.0);
+        assert_eq!(Rational::new(Int::from(-3), &tiny << 1).to_f64(), -1e-323);
+
+        assert_eq!(Rational::new(ten.pow(400), Int::from(-7)).to_f64(), f64::NEG_INFINITY);
+        assert_eq!(Rational::new(Int::zero(), Int::from(5)).to_f64(), 0.0);
+    }
+
+    #[test]
+    fn num_traits() {
+        use num_traits::{Num, Signed, FromPrimitive};
+
+        let r = Rational::from_str_radix("-ff/10", 16).unwrap();
+        assert_eq!(r, Rational::new(Int::from(-255), Int::from(16)));
+        assert_eq!(Rational::from_str_radix("11", 2).unwrap(), Rational::from(3));
+        assert!(Rational::from_str_radix("1/2/3", 10).is_err());
+        let e = Rational::from_str_radix("1/0", 10).unwrap_err();
+        assert_eq!(e.to_string(), "denominator is zero");
+        assert!("-5/00".parse::<Rational>().is_err());
+        assert_eq!("0/5".parse::<Rational>().unwrap(), Rational::zero());
+
+        assert_eq!(Signed::abs(&r), Rational::new(Int::from(255), Int::from(16)));
+        assert_eq!(r.signum(), Rational::from(-1));
+        assert!(r.is_negative());
+        assert_eq!(Rational::one().abs_sub(&r), Rational::new(Int::from(271), Int::from(16)));
+
+        assert_eq!(Rational::from_u64(7), Some(Rational::from(7)));
+        assert_eq!(Rational::from_f64(-0.375), Some(Rational::new(Int::from(-3), Int::from(8))));
+        assert_eq!(Rational::from_f64(f64::INFINITY), None);
+    }
+
+    #[test]
+    fn rem() {
+        let a = Rational::new(Int::from(7), Int::from(2));
+        let b = Rational::new(Int::from(4), Int::from(3));
+        assert_eq!(&a % &b, Rational::new(Int::from(5), Int::from(6)));
+        assert_eq!(-a.clone() % b.clone(), Rational::new(Int::from(-5), Int::from(6)));
+        assert_eq!(a % Rational::from(-2), Rational::new(Int::from(3), Int::from(2)));
+    }
+
+    #[test]
+    #[cfg(feature = "num-rational")]
+    fn big_rational_conversions() {
+        use num_bigint::BigInt;
+        use num_rational::BigRational;
+
+        let r = Rational::new(Int::from(6), Int::from(-4));
+        let b = BigRational::new(BigInt::from(-3), BigInt::from(2));
+        assert_eq!(BigRational::from(&r), b);
+        assert_eq!(Rational::from(b), r);
+        assert_eq!(Rational::from(BigRational::from(Rational::zero())), Rational::zero());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash() {
+        fn hash_of<T: Hash>(x: &T) -> u64 {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            x.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let a = Rational::new(Int::from(6), Int::from(-4));
+        let b = Rational::new(Int::from(-3), Int::from(2));
+        let c = Rational::new(Int::from(3), Int::from(2));
+        assert_eq!(hash_of(&a), hash_of(&b));
+        assert!(hash_of(&a) != hash_of(&c));
+        assert_eq!(a.stable_hash(), b.stable_hash());
+
+        let whole = Rational::new(Int::from(-14), Int::from(-2));
+        assert_eq!(hash_of(&whole), hash_of(&Int::from(7)));
+        assert_eq!(whole.stable_hash(), Int::from(7).stable_hash());
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_roundtrip() {
+        use serde_json;
+        use bincode;
+
+        let cases = [("0/1", "0/1"), ("3/1", "3/1"), ("-2/4", "-1/2"), ("5/-15", "-1/3"),
+                     ("123456789012345678901234567890/11", "123456789012345678901234567890/11")];
+        for &(s, canonical) in &cases {
+            let r = Rational::from_str(s).unwrap();
+
+            let json = serde_json::to_string(&r).unwrap();
+            assert_eq!(json, format!("\"{}\"", canonical));
+            assert_mp_eq!(serde_json::from_str::<Rational>(&json).unwrap(), r);
+
+            let bin = bincode::serialize(&r).unwrap();
+            assert_mp_eq!(bincode::deserialize::<Rational>(&bin).unwrap(), r);
+        }
+
+        assert_mp_eq!(serde_json::from_str::<Rational>("\"-6\"").unwrap(),
+                      Rational::new(Int::from(-6), Int::one()));
+        assert!(serde_json::from_str::<Rational>("\"1/0\"").is_err());
+        assert!(serde_json::from_str::<Rational>("\"1/x\"").is_err());
+
+        let zero_d = bincode::serialize(&(Int::one(), Int::zero())).unwrap();
+        assert!(bincode::deserialize::<Rational>(&zero_d).is_err());
+    }
+
+    #[test]
+    fn format_flags() {
+        let r = Rational::new(Int::from(3), Int::from(-6));
+        assert_eq!(r.to_string(), "-1/2");
+        assert_eq!(format!("{:>7}", r), "   -1/2");
+        assert_eq!(format!("{:<7}|", r), "-1/2   |");
+        assert_eq!(format!("{:07}", r), "-0001/2");
+        assert_eq!(format!("{:+}", r.clone().abs()), "+1/2");
+        assert_eq!(format!("{:_^8}", Rational::from(2)), "__2/1___");
+        assert_eq!(format!("{:>6?}", r), "  -1/2");
+    }
+
+    #[cfg(feature = "std")]
     fn rand_rational(x: usize) -> Rational {
         let mut rng = rand::thread_rng();
 
@@ -1202,6 +1624,7 @@ mod test {
         Rational::new(xn, xd)
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_add(b: &mut Bencher) {
         let x = rand_rational(20);
@@ -1213,6 +1636,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_add_normalize(b: &mut Bencher) {
         let x = rand_rational(20);
@@ -1225,6 +1649,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_add_5(b: &mut Bencher) {
         let r1 = rand_rational(20);
@@ -1239,6 +1664,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_add_5_normalize(b: &mut Bencher) {
         let r1 = rand_rational(20);
@@ -1254,6 +1680,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_sub(b: &mut Bencher) {
         let x = rand_rational(20);
@@ -1265,6 +1692,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_sub_normalize(b: &mut Bencher) {
         let x = rand_rational(20);
@@ -1277,6 +1705,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_mul(b: &mut Bencher) {
         let x = rand_rational(20);
@@ -1288,6 +1717,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_mul_normalize(b: &mut Bencher) {
         let x = rand_rational(20);
@@ -1300,6 +1730,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_div(b: &mut Bencher) {
         let x = rand_rational(20);
@@ -1311,6 +1742,7 @@ mod test {
         });
     }
 
+    #[cfg(feature = "unstable")]
     #[bench]
     fn bench_div_normalize(b: &mut Bencher) {
         let x = rand_rational(20);
@@ -1,16 +1,14 @@
-#![feature(plugin)]
-#![plugin(quickcheck_macros)]
-
 #[cfg(feature="rust-gmp")]
 extern crate gmp;
 #[cfg(not(feature="rust-gmp"))]
 extern crate num_bigint;
 #[cfg(not(feature="rust-gmp"))]
 extern crate num_traits;
-#[cfg(not(feature="rust-gmp"))]
 extern crate num_integer;
 
 extern crate quickcheck;
+#[macro_use(quickcheck)]
+extern crate quickcheck_macros;
 
 extern crate framp as ramp;
 
@@ -27,6 +25,7 @@ pub use num_bigint::BigUint as RefImplU;
 use num_traits::sign::Signed;
 
 use ramp::Int;
+use ramp::rational::Rational;
 use ramp::traits::DivRem;
 
 use quickcheck::{Gen, Arbitrary, TestResult};
@@ -46,6 +45,15 @@ fn ref_from_hex(a:&str) -> RefImpl {
     RefImpl::parse_bytes(a.as_bytes(), 16).unwrap()
 }
 
+#[cfg(feature="rust-gmp")]
+fn ref_to_str_radix(a: &RefImpl, b: u8) -> String {
+    a.to_str_radix(b)
+}
+#[cfg(not(feature="rust-gmp"))]
+fn ref_to_str_radix(a: &RefImpl, b: u8) -> String {
+    a.to_str_radix(b.into())
+}
+
 // a hex string representing some integer, to be randomly generated by
 // quickcheck.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -98,7 +106,7 @@ impl Arbitrary for BigIntStr {
         let mut string = String::with_capacity(neg as usize + size);
 
         if neg {
-            string.push_str("-")
+            string.push('-')
         }
         // shouldn't start with zero
         let mut first = 0;
@@ -141,7 +149,7 @@ impl Arbitrary for BigIntStr {
         BigIntStr(string)
     }
 
-    fn shrink(&self) -> Box<Iterator<Item = Self>> {
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
         // shrink the "number" by just truncating the string from the
         // end.
         let mut string = self.clone();
@@ -150,9 +158,9 @@ impl Arbitrary for BigIntStr {
                 // small numbers strink slowly.
                 let rate = match string.0.len() {
                     0 => 0,
-                    1...10 => 1,
-                    11...100 => 5,
-                    100...1000 => 25,
+                    1..=10 => 1,
+                    11..=100 => 5,
+                    101..=1000 => 25,
                     _ => 125
                 };
                 for _ in 0..rate {
@@ -160,7 +168,7 @@ impl Arbitrary for BigIntStr {
                 }
                 string.clone()
             })
-            .take_while(|s| s.0 != "" && s.0 != "-");
+            .take_while(|s| !s.0.is_empty() && s.0 != "-");
         Box::new(iter)
     }
 }
@@ -181,6 +189,116 @@ fn to_str_hex_roundtrip(a: BigIntStr) {
     assert_eq!(t, a.0);
 }
 
+#[quickcheck]
+fn read_radix_roundtrip(a: BigIntStr, base: u8) {
+    let (ar, _) = a.parse();
+    let base = base % 35 + 2;
+
+    let mut buf = Vec::new();
+    ar.write_radix(&mut buf, base, false).unwrap();
+    assert_eq!(Int::read_radix(&mut &buf[..], base).unwrap(), ar);
+}
+
+#[quickcheck]
+fn digits_roundtrip(a: BigIntStr, base: u32) {
+    let (ar, _) = a.parse();
+    let base = base as u64 % ((1 << 32) - 1) + 2;
+
+    let digits: Vec<u32> = ar.digits(base).collect();
+    assert!(digits.iter().all(|&d| (d as u64) < base));
+    assert_eq!(Int::from_digits(&digits, base), ar.clone().abs());
+}
+
+// Parsing a decimal string gives a correctly rounded float, so it serves as the
+// reference for the conversions to floats.
+#[quickcheck]
+fn to_float(a: BigIntStr) {
+    let (ar, _) = a.parse();
+    let s = ar.to_string();
+
+    assert_eq!(ar.to_f64(), s.parse::<f64>().unwrap());
+    assert_eq!(ar.to_f32(), s.parse::<f32>().unwrap());
+}
+
+#[quickcheck]
+fn rational_to_float(a: BigIntStr, k: u16) {
+    let (ar, _) = a.parse();
+    let k = k % 400;
+    let r = Rational::new(ar.clone(), Int::from(10).pow(k as usize));
+    let s = format!("{}e-{}", ar, k);
+
+    assert_eq!(r.to_f64(), s.parse::<f64>().unwrap());
+    assert_eq!(r.to_f32(), s.parse::<f32>().unwrap());
+}
+
+fn check_from_f64(f: f64) -> TestResult {
+    if !f.is_finite() {
+        assert_eq!(Int::from_f64(f), None);
+        assert_eq!(Int::from_f64_rounded(f), None);
+        return TestResult::discard();
+    }
+
+    let t = Int::from_f64(f).unwrap();
+    assert_eq!(t.to_f64(), f.trunc());
+
+    let r = Int::from_f64_rounded(f).unwrap();
+    if f.abs() >= (1u64 << 52) as f64 {
+        assert_eq!(r, t);
+    } else {
+        // Exact, since `f` and `r` are close
+        let diff = (f - r.to_f64()).abs();
+        assert!(diff <= 0.5);
+        if diff == 0.5 {
+            assert!(r.is_even());
+        }
+    }
+    TestResult::passed()
+}
+
+#[quickcheck]
+fn from_f64(f: f64) -> TestResult {
+    check_from_f64(f)
+}
+
+#[quickcheck]
+fn from_f64_bits(bits: u64) -> TestResult {
+    check_from_f64(f64::from_bits(bits))
+}
+
+#[quickcheck]
+fn nth_root(a: BigIntStr, n: u8) {
+    use num_integer::Roots;
+
+    let (ar, _) = a.parse();
+    let ar = ar.abs();
+    let n = n as usize % 10 + 1;
+    let r = ar.nth_root(n as u32);
+
+    assert!(r.pow(n) <= ar);
+    assert!((r + Int::one()).pow(n) > ar);
+}
+
+#[quickcheck]
+fn extended_gcd(a: BigIntStr, b: BigIntStr) {
+    use num_integer::Integer;
+
+    let (ar, _) = a.parse();
+    let (br, _) = b.parse();
+    let e = ar.extended_gcd(&br);
+
+    assert_eq!(e.gcd, ar.gcd(&br));
+    assert_eq!(&e.x * &ar + &e.y * &br, e.gcd);
+}
+
+#[cfg(any(feature = "rust-gmp", feature = "num-bigint"))]
+#[quickcheck]
+fn ref_impl_conversion(a: BigIntStr) {
+    let (ar, ag) = a.parse();
+
+    assert_eq!(Int::from(&ag), ar);
+    assert_eq!(RefImpl::from(&ar), ag);
+}
+
 // methods
 #[quickcheck]
 fn abs(a: BigIntStr) -> TestResult {
@@ -291,7 +409,7 @@ fn trailing_zeros(a: BigIntStr) {
     } else {
         (0..).position(|idx| tstbit(&ag, idx)).unwrap()
     };
-    assert_eq!(ar.trailing_zeros() as usize, bit);
+    assert_eq!(ar.trailing_zeros(), bit);
 }
 
 #[quickcheck]
@@ -318,7 +436,7 @@ fn count_ones(a: BigIntStr) {
 fn bit_length(a: BigIntStr) {
     let (ar, ag) = a.parse();
 
-    assert_eq!(ar.bit_length(), ag.bit_length() as u32)
+    assert_eq!(ar.bit_length(), ag.bit_length())
 }
 
 #[quickcheck]
@@ -326,14 +444,14 @@ fn bit_length(a: BigIntStr) {
 fn bit_length(a: BigIntStr) {
     let (ar, ag) = a.parse();
 
-    assert_eq!(ar.bit_length(), std::cmp::max(1, ag.bits() as u32))
+    assert_eq!(ar.bit_length(), std::cmp::max(1, ag.bits()))
 }
 
 #[quickcheck]
 fn bit(a: BigIntStr, bit: u16) {
     let (ar, ag) = a.parse_u();
 
-    assert_eq!(ar.bit(bit as u32), tstbit(&ag, bit as usize));
+    assert_eq!(ar.bit(bit as usize), tstbit(&ag, bit as usize));
 }
 
 #[quickcheck]
@@ -341,14 +459,83 @@ fn bit(a: BigIntStr, bit: u16) {
 fn set_bit(a: BigIntStr, bit: u16, b: bool) {
     let (mut ar, mut ag) = a.parse();
 
-    ar.set_bit(bit as u32, b);
+    ar.set_bit(bit as usize, b);
     if b {
         ag.setbit(bit as usize);
     } else {
         ag.clrbit(bit as usize);
     }
     eq!(ar, ag);
-    assert_eq!(ar.bit(bit as u32), b);
+    assert_eq!(ar.bit(bit as usize), b);
+}
+
+#[quickcheck]
+#[cfg(not(feature="rust-gmp"))]
+fn bytes(a: BigIntStr) {
+    let (ar, ag) = a.parse();
+
+    assert_eq!(ar.to_bytes_be(), ag.to_bytes_be().1);
+    assert_eq!(ar.to_bytes_le(), ag.to_bytes_le().1);
+    assert_eq!(ar.to_signed_bytes_be(), ag.to_signed_bytes_be());
+    assert_eq!(ar.to_signed_bytes_le(), ag.to_signed_bytes_le());
+}
+
+#[quickcheck]
+fn bytes_roundtrip(a: BigIntStr) {
+    let (ar, _) = a.parse();
+    let abs = ar.clone().abs();
+
+    assert_eq!(Int::from_bytes_be(&ar.to_bytes_be()), abs);
+    assert_eq!(Int::from_bytes_le(&ar.to_bytes_le()), abs);
+    assert_eq!(Int::from_signed_bytes_be(&ar.to_signed_bytes_be()), ar);
+    assert_eq!(Int::from_signed_bytes_le(&ar.to_signed_bytes_le()), ar);
+
+    let len = ar.to_bytes_be().len();
+    let padded = ar.to_bytes_be_padded(len + 3).unwrap();
+    assert_eq!(&padded[..3], &[0, 0, 0]);
+    assert_eq!(Int::from_bytes_be(&padded), abs);
+    assert_eq!(ar.to_bytes_le_padded(len - 1), if ar == 0 { Some(vec![]) } else { None });
+}
+
+#[quickcheck]
+fn der_roundtrip(a: BigIntStr) {
+    let (ar, _) = a.parse();
+    let der = ar.to_der();
+
+    assert_eq!(Int::from_der(&der), Ok(ar));
+    assert!(Int::from_der(&der[..der.len() - 1]).is_err());
+}
+
+#[quickcheck]
+fn wire_roundtrip(a: BigIntStr) {
+    let (ar, _) = a.parse();
+
+    let mut buf = Vec::new();
+    ar.write_ssh_mpint(&mut buf).unwrap();
+    ar.write_gmp_raw(&mut buf).unwrap();
+    ar.clone().abs().write_pgp_mpi(&mut buf).unwrap();
+
+    let mut r = &buf[..];
+    assert_eq!(Int::read_ssh_mpint(&mut r).unwrap(), ar);
+    assert_eq!(Int::read_gmp_raw(&mut r).unwrap(), ar);
+    assert_eq!(Int::read_pgp_mpi(&mut r).unwrap(), ar.clone().abs());
+    assert!(r.is_empty());
+}
+
+#[quickcheck]
+fn leb128_roundtrip(a: BigIntStr) {
+    let (ar, _) = a.parse();
+
+    let mut buf = Vec::new();
+    ar.clone().abs().write_uleb128(&mut buf).unwrap();
+    ar.write_sleb128(&mut buf).unwrap();
+    ar.write_zigzag(&mut buf).unwrap();
+
+    let mut r = &buf[..];
+    assert_eq!(Int::read_uleb128(&mut r).unwrap(), ar.clone().abs());
+    assert_eq!(Int::read_sleb128(&mut r).unwrap(), ar);
+    assert_eq!(Int::read_zigzag(&mut r).unwrap(), ar);
+    assert!(r.is_empty());
 }
 
 fn order_asc<T: Ord>(a: T, b: T) -> (T, T) {
@@ -386,7 +573,7 @@ fn ref_via_hex<T: ::std::fmt::LowerHex>(x: T) -> RefImpl {
 #[quickcheck]
 fn divrem_usize(a: BigIntStr, b: usize, c: usize) -> TestResult {
     let (ar, ag) = a.parse();
-    if ar <= 0 || b <= 0 || c <= 0 || b == c {
+    if ar <= 0 || b == 0 || c == 0 || b == c {
         return TestResult::discard()
     }
     let (b, c) = order_asc(b, c);
@@ -822,9 +1009,10 @@ test_cmpop! {
 // conversions
 
 macro_rules! test_from {
-    ($($prim: ident;)*) => {
+    ($($prim: ident $wrapping: ident;)*) => {
         mod from {
             use ramp::Int;
+            use std::convert::TryFrom;
 
             $(#[quickcheck]
               fn $prim(x: $prim) {
@@ -832,16 +1020,61 @@ macro_rules! test_from {
                   assert_eq!(a.to_string(),
                              x.to_string());
 
-                  assert_eq!($prim::from(&a),
-                             x);
+                  assert_eq!($prim::try_from(&a), Ok(x));
+                  assert_eq!(a.$wrapping(), x);
               })*
+
+            #[quickcheck]
+            fn i128(hi: i64, lo: u64) {
+                let x = ((hi as i128) << 64) | lo as i128;
+                let a = Int::from(x);
+                assert_eq!(a.to_string(), x.to_string());
+                assert_eq!(i128::try_from(&a), Ok(x));
+            }
+
+            #[quickcheck]
+            fn u128(hi: u64, lo: u64) {
+                let x = ((hi as u128) << 64) | lo as u128;
+                let a = Int::from(x);
+                assert_eq!(a.to_string(), x.to_string());
+                assert_eq!(u128::try_from(&a), Ok(x));
+            }
         }
     }
 }
 
 test_from! {
-    i8; i16; i32; i64; isize;
-    u8; u16; u32; u64; usize;
+    i8 to_i8_wrapping; i16 to_i16_wrapping; i32 to_i32_wrapping; i64 to_i64_wrapping;
+    isize to_isize_wrapping;
+    u8 to_u8_wrapping; u16 to_u16_wrapping; u32 to_u32_wrapping; u64 to_u64_wrapping;
+    usize to_usize_wrapping;
+}
+
+// conversions of values that don't fit in the target type
+macro_rules! test_try_from {
+    ($($prim: ident $wrapping: ident;)*) => {
+        mod try_from {
+            use ramp::Int;
+            use std::convert::TryFrom;
+
+            $(#[quickcheck]
+              fn $prim(hi: i64, lo: u64) {
+                  let x = ((hi as i128) << 64) | lo as i128;
+                  let a = Int::from(x);
+                  let fits = x >= <$prim>::MIN as i128 && x <= <$prim>::MAX as i128;
+
+                  assert_eq!($prim::try_from(&a).ok(), if fits { Some(x as $prim) } else { None });
+                  assert_eq!(a.$wrapping(), x as $prim);
+              })*
+        }
+    }
+}
+
+test_try_from! {
+    i8 to_i8_wrapping; i16 to_i16_wrapping; i32 to_i32_wrapping; i64 to_i64_wrapping;
+    isize to_isize_wrapping;
+    u8 to_u8_wrapping; u16 to_u16_wrapping; u32 to_u32_wrapping; u64 to_u64_wrapping;
+    usize to_usize_wrapping;
 }
 
 // stringification
@@ -850,10 +1083,10 @@ test_from! {
 fn to_str_radix(a: BigIntStr, b: u8) -> TestResult {
     // fold, to avoid discarding too many times, but without a bias
     let b = b % 64;
-    if b < 2 || b > 36 { return TestResult::discard() }
+    if !(2..=36).contains(&b) { return TestResult::discard() }
     let (ar, ag) = a.parse();
 
-    TestResult::from_bool(ar.to_str_radix(b, false) == ag.to_str_radix(b.into()))
+    TestResult::from_bool(ar.to_str_radix(b, false) == ref_to_str_radix(&ag, b))
 }
 
 // decimal is the most common non-trivial (non-power-of-two) base, so
@@ -872,23 +1105,23 @@ fn to_str_decimal(a: BigIntStr) {
 fn write_radix(a: BigIntStr, b: u8) -> TestResult {
     // fold, to avoid discarding too many times, but without a bias
     let b = b % 64;
-    if b < 2 || b > 36 { return TestResult::discard() }
+    if !(2..=36).contains(&b) { return TestResult::discard() }
     let (ar, ag) = a.parse();
 
     let mut v = Vec::new();
     ar.write_radix(&mut v, b, false).unwrap();
-    TestResult::from_bool(v == ag.to_str_radix(b.into()).into_bytes())
+    TestResult::from_bool(v == ref_to_str_radix(&ag, b).into_bytes())
 }
 
 #[quickcheck]
 fn from_str_radix(a: BigIntStr, b: u8) -> TestResult {
     // fold, to avoid discarding too many times, but without a bias
     let b = b % 64;
-    if b < 2 || b > 36 { return TestResult::discard() }
+    if !(2..=36).contains(&b) { return TestResult::discard() }
 
     let (ar, ag) = a.parse();
 
-    let s = ag.to_str_radix(b.into());
+    let s = ref_to_str_radix(&ag, b);
 
     let sr = Int::from_str_radix(&s, b);
     TestResult::from_bool(sr.ok() == Some(ar))
@@ -4,13 +4,13 @@ extern crate num_bigint;
 
 use quickcheck::TestResult;
 use ramp::ll::limb;
-use ramp::ll::limb::Limb;
+use ramp::ll::limb::{BaseInt, Limb};
 use num_bigint::BigUint;
 
 #[cfg(feature = "full-quickcheck")]
-const QUICKCHECK_THOROUGNESS: usize = 100;
+const QUICKCHECK_THOROUGNESS: u64 = 100;
 #[cfg(not(feature = "full-quickcheck"))]
-const QUICKCHECK_THOROUGNESS: usize = 1;
+const QUICKCHECK_THOROUGNESS: u64 = 1;
 
 macro_rules! quickcheck {
     (@as_items $($i:item)*) => ($($i)*);
@@ -39,14 +39,14 @@ macro_rules! quickcheck {
     )
 }
 
-macro_rules! B { () => { BigUint::from(usize::max_value()) + BigUint::from(1usize) } }
-macro_rules! l { ($e:expr) => { Limb($e as limb::BaseInt) } }
-macro_rules! b  {   ($e:expr) => { BigUint::from($e as usize) };
+macro_rules! B { () => { BigUint::from(BaseInt::MAX as u64) + BigUint::from(1u64) } }
+macro_rules! l { ($e:expr) => { Limb($e as BaseInt) } }
+macro_rules! b  {   ($e:expr) => { BigUint::from($e as u64) };
                     ($h:expr,$l:expr) => { b!($h) * B!() + b!($l) }
                 }
 
 quickcheck!{
-    fn check_add(ha:usize, la:usize, hb:usize, lb:usize) -> TestResult {
+    fn check_add(ha: BaseInt, la: BaseInt, hb: BaseInt, lb: BaseInt) -> TestResult {
         let a = b!(ha,la);
         let b = b!(hb,lb);
         let num_sum = (a+b)%(B!()*B!());
@@ -59,7 +59,7 @@ quickcheck!{
 }
 
 quickcheck!{
-    fn check_sub(ha: usize, la: usize, hb: usize, lb: usize) -> TestResult {
+    fn check_sub(ha: BaseInt, la: BaseInt, hb: BaseInt, lb: BaseInt) -> TestResult {
         let a = b!(ha,la);
         let b = b!(hb,lb);
         if a < b {
@@ -75,7 +75,7 @@ quickcheck!{
 }
 
 quickcheck!{
-    fn check_mul(a: usize, b: usize) -> TestResult {
+    fn check_mul(a: BaseInt, b: BaseInt) -> TestResult {
         let num_prod = b!(a) * b!(b);
 
         let (hw, lw) = limb::mul(l!(a), l!(b));
@@ -86,8 +86,8 @@ quickcheck!{
 }
 
 quickcheck!{
-    fn check_div(hn: usize, ln: usize, d: usize) -> TestResult {
-        let d = (1 + usize::max_value() / 2).saturating_add(d / 2);
+    fn check_div(hn: BaseInt, ln: BaseInt, d: BaseInt) -> TestResult {
+        let d = (1 + BaseInt::MAX / 2).saturating_add(d / 2);
         if hn >= d {
             return TestResult::discard();
         }